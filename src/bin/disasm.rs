@@ -0,0 +1,46 @@
+use nes::emulator::rom::{ Rom, PRG_ROM_PAGE_SIZE };
+use nes::emulator::cdl::CodeDataLog;
+use nes::cli::disasm::{ self, DisasmOptions, USAGE };
+use nes::helpers::disassembler::{ ca65_listing, ca65_listing_logged };
+use std::env;
+use std::fs;
+use std::process::exit;
+
+fn main() {
+    let options = match disasm::parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            exit(0);
+        },
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            exit(2);
+        },
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
+
+fn run(options: &DisasmOptions) -> Result<(), String> {
+    let rom = Rom::load(&options.rom_path)?;
+    let bank = disasm::prg_bank(&rom, options.bank)?;
+    let listing = match &options.cdl {
+        Some(path) => {
+            let log = CodeDataLog::load(path, &rom)?;
+            ca65_listing_logged(bank, options.org(&rom), &log.prg[options.bank * PRG_ROM_PAGE_SIZE..])
+        },
+        None => ca65_listing(bank, options.org(&rom)),
+    };
+
+    match &options.output {
+        Some(path) => fs::write(path, listing).map_err(|e| format!("Could not write {}: {}", path, e)),
+        None => {
+            print!("{}", listing);
+            Ok(())
+        },
+    }
+}
@@ -0,0 +1,132 @@
+use nes::emulator::nes::Nes;
+use nes::emulator::movie::{ Movie, MoviePlayback, MovieRecording };
+use nes::emulator::cdl::CodeDataLog;
+use nes::cli::headless::{ self, HeadlessOptions, InputScript, USAGE };
+use nes::helpers::{ png, wav, trace::trace_with_symbols };
+use nes::helpers::symbols::Symbols;
+use nes::emulator::frame::Frame;
+use nes::emulator::palette::Palette;
+use std::env;
+use std::path::Path;
+use std::fs::File;
+use std::io::{ BufWriter, Write };
+use std::process::exit;
+
+fn main() {
+    let options = match headless::parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            exit(0);
+        },
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            exit(2);
+        },
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
+}
+
+fn run(options: &HeadlessOptions) -> Result<(), String> {
+    let mut nes = Nes::load(&options.rom_path)?;
+    nes.set_region(options.region);
+
+    let script = match &options.input {
+        Some(path) => InputScript::load(path)?,
+        None => InputScript::default(),
+    };
+    let palette = match &options.palette {
+        Some(path) => Palette::load(path)?,
+        None => Palette::default(),
+    };
+    if let Some(path) = &options.cdl {
+        nes.start_code_data_log(CodeDataLog::load_or_new(path, nes.rom())?);
+    }
+    let symbols = Symbols::load_all(&options.symbols, nes.cpu.bus.prg_rom.len())?;
+    let mut trace_file = match &options.trace {
+        Some(path) => Some(BufWriter::new(
+            File::create(path).map_err(|e| format!("Could not create trace file {}: {}", path, e))?
+        )),
+        None => None,
+    };
+
+    let mut playback = match &options.movie {
+        Some(path) => Some(MoviePlayback::start(Movie::load(path)?, &mut nes)?),
+        None => None,
+    };
+    let mut recording = options.record_movie.as_ref().map(|_| {
+        let rom_name = Path::new(&options.rom_path).file_stem().unwrap_or_default().to_string_lossy();
+        let from_power_on = playback.as_ref().is_none_or(|playback| playback.movie.start_state.is_none());
+        MovieRecording::start(&mut nes, &rom_name, from_power_on)
+    });
+
+    let mut frames = 0;
+    let mut samples = Vec::new();
+    let mut condition_met = false;
+    let limit = options.frame_limit(playback.as_ref().map(|playback| playback.movie.frames.len()));
+
+    'run: while limit.is_none_or(|limit| frames < limit) {
+        let commands = match playback.as_mut().and_then(|playback| playback.next_frame(&mut nes)) {
+            Some(frame) => frame.commands,
+            None => {
+                for port in 0..headless::PORTS {
+                    nes.set_buttons(port, script.buttons(port, frames));
+                }
+                0
+            },
+        };
+        if let Some(recording) = recording.as_mut() {
+            recording.record_frame(&nes, commands);
+        }
+        frames += 1;
+
+        loop {
+            if let Some(file) = trace_file.as_mut() {
+                writeln!(file, "{}", trace_with_symbols(&mut nes.cpu, &symbols)).map_err(|e| e.to_string())?;
+            }
+
+            let frame_done = nes.step();
+
+            if options.until.is_some_and(|condition| condition.holds(&nes.cpu)) {
+                condition_met = true;
+                break 'run;
+            }
+
+            if frame_done {
+                break;
+            }
+        }
+        samples.extend(nes.audio_samples());
+    }
+
+    if let Some(file) = trace_file.as_mut() {
+        file.flush().map_err(|e| e.to_string())?;
+    }
+    if let (Some(recording), Some(path)) = (&recording, &options.record_movie) {
+        recording.movie.save(path)?;
+    }
+    if let (Some(log), Some(path)) = (nes.code_data_log(), &options.cdl) {
+        log.save(path)?;
+    }
+
+    let reason = if condition_met { "condition met" } else { "frame limit reached" };
+    println!("Stopped after {} frames ({})", frames, reason);
+    println!("{}", headless::cpu_state(&nes.cpu));
+    println!("{}", headless::ram_dump(&nes.cpu.bus.cpu_vram));
+
+    if let Some(path) = &options.png {
+        let mut frame = Frame::new();
+        frame.pixels.copy_from_slice(&nes.framebuffer().pixels);
+        frame.apply_palette(&palette);
+        png::save(path, Frame::WIDTH, Frame::HEIGHT, &frame.data)?;
+    }
+    if let Some(path) = &options.wav {
+        wav::save(path, Nes::SAMPLE_RATE, &samples)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::fs;
+
+/*
+    INI style config file:
+
+    # Comment
+    scale = 4
+    [section]
+    key = value
+ */
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    pub sections: Vec<Section>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Section {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut sections = vec![Section::default()];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name.strip_suffix(']')
+                    .ok_or(format!("line {}: expected ']' after section name", i + 1))?;
+                sections.push(Section { name: name.trim().to_string(), entries: Vec::new() });
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or(format!("line {}: expected 'key = value'", i + 1))?;
+            let value = value.trim().trim_matches('"');
+            sections.last_mut().unwrap().entries.push((key.trim().to_string(), value.to_string()));
+        }
+
+        Ok(Config { sections })
+    }
+
+    pub fn load(path: &str) -> Result<Config, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read config {}: {}", path, e))?;
+        Config::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /* Entries of every section with this name, in file order */
+    pub fn entries<'a>(&'a self, section: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.sections.iter()
+            .filter(move |s| s.name == section)
+            .flat_map(|s| s.entries.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
+    pub fn get<'a>(&'a self, section: &'a str, key: &str) -> Option<&'a str> {
+        self.entries(section)
+            .filter(|(k, _)| *k == key)
+            .last()
+            .map(|(_, v)| v)
+    }
+}
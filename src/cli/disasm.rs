@@ -0,0 +1,83 @@
+use crate::emulator::rom::{ Rom, PRG_ROM_PAGE_SIZE };
+
+pub const USAGE: &str = "\
+Usage: nes-disasm [OPTIONS] <ROM>
+
+Writes a 16 KiB PRG bank as ca65 assembly, with labels for branch and jump targets.
+
+Options:
+  --bank <N>             PRG bank to disassemble, counting from 0 (default 0)
+  --org <ADDR>           Address the bank is mapped at in hex (default C000 for the last bank, 8000 otherwise)
+  --cdl <FILE>           FCEUX code/data log for the ROM, bytes it only saw read are kept as .byte
+  --output <FILE>        Write the assembly to FILE instead of stdout
+  -h, --help             Print this message";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct DisasmOptions {
+    pub rom_path: String,
+    pub bank: usize,
+    pub org: Option<u16>,
+    pub cdl: Option<String>,
+    pub output: Option<String>,
+}
+
+impl DisasmOptions {
+    /* The last bank holds the vectors so it usually sits at $C000 */
+    pub fn org(&self, rom: &Rom) -> u16 {
+        let banks = rom.prg_rom.len() / PRG_ROM_PAGE_SIZE;
+        self.org.unwrap_or(if self.bank + 1 == banks { 0xC000 } else { 0x8000 })
+    }
+}
+
+pub fn prg_bank(rom: &Rom, bank: usize) -> Result<&[u8], String> {
+    let banks = rom.prg_rom.len() / PRG_ROM_PAGE_SIZE;
+    rom.prg_rom.chunks(PRG_ROM_PAGE_SIZE)
+        .nth(bank)
+        .ok_or(format!("The ROM has {} PRG banks, there is no bank {}", banks, bank))
+}
+
+/* Ok(None) means help was requested */
+pub fn parse<I>(args: I) -> Result<Option<DisasmOptions>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = DisasmOptions::default();
+    let mut rom_path: Option<String> = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+
+        let Some(name) = arg.strip_prefix("--") else {
+            if arg.starts_with('-') {
+                return Err(format!("Unknown option: {}", arg));
+            }
+            if rom_path.is_some() {
+                return Err(format!("Unexpected argument: {}", arg));
+            }
+            rom_path = Some(arg);
+            continue;
+        };
+
+        let value = args.next().ok_or(format!("Option --{} requires a value", name))?;
+        match name {
+            "bank" => {
+                options.bank = value.parse::<usize>()
+                    .map_err(|_| format!("Invalid value for --bank: '{}' (expected a bank number)", value))?;
+            },
+            "org" => {
+                let hex = value.trim_start_matches('$').trim_start_matches("0x");
+                options.org = Some(u16::from_str_radix(hex, 16)
+                    .map_err(|_| format!("Invalid value for --org: '{}' (expected a hex address)", value))?);
+            },
+            "cdl" => options.cdl = Some(value),
+            "output" => options.output = Some(value),
+            _ => return Err(format!("Unknown option: --{}", name)),
+        }
+    }
+
+    options.rom_path = rom_path.ok_or("Missing ROM path")?;
+    Ok(Some(options))
+}
@@ -0,0 +1,261 @@
+use crate::emulator::cpu::Cpu;
+use crate::emulator::joypad::Buttons;
+use crate::emulator::region::Region;
+use std::fs;
+
+pub const USAGE: &str = "\
+Usage: nes-headless [OPTIONS] <ROM>
+
+Runs a ROM without a window and prints the final CPU registers and RAM.
+
+Options:
+  --frames <N>           Stop after N frames (default 60 unless --until is given)
+  --until <CONDITION>    Stop once the condition holds, checked after every instruction:
+                           pc=C000     program counter equals $C000
+                           0010=FF     RAM at $0010 equals $FF
+  --input <FILE>         Button script, one '<frame> [port] <buttons>' entry per line, port 1 or 2 (default 1)
+  --movie <FILE>         Play back an FCEUX .fm2 movie, runs its length unless --frames is given
+  --record-movie <FILE>  Save the input that was used as an .fm2 movie
+  --png <FILE>           Save the final frame as a PNG
+  --wav <FILE>           Save the audio as a 16 bit mono WAV, silent until there is an APU
+  --palette <FILE>       Palette used for the PNG
+  --region <REGION>      ntsc, pal or dendy (default ntsc)
+  --trace <FILE>         Write a nestest style trace of every instruction to FILE
+  --symbols <FILE>       Label names for the trace from a ca65 .dbg, FCEUX .nl or Mesen .mlb file,
+                         can be given more than once
+  --cdl <FILE>           Log which PRG and CHR bytes were used as code or data to an FCEUX .cdl file,
+                         an existing log for the ROM is added to
+  -h, --help             Print this message";
+
+pub const DEFAULT_FRAMES: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    ProgramCounter(u16),
+    Memory(u16, u8),
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Condition, String> {
+        let invalid = || format!("Invalid condition: '{}' (expected pc=ADDR or ADDR=VALUE in hex)", text);
+        let (left, right) = text.split_once('=').ok_or_else(invalid)?;
+        let (left, right) = (left.trim(), right.trim());
+
+        if left.eq_ignore_ascii_case("pc") {
+            return Ok(Condition::ProgramCounter(parse_hex(right).ok_or_else(invalid)?));
+        }
+
+        let addr = parse_hex(left).filter(|addr| *addr < 0x2000).ok_or_else(invalid)?;
+        let value = parse_hex(right).filter(|value| *value <= 0xFF).ok_or_else(invalid)?;
+        Ok(Condition::Memory(addr, value as u8))
+    }
+
+    /* Only looks at RAM so checking never triggers register side effects */
+    pub fn holds(&self, cpu: &Cpu) -> bool {
+        match *self {
+            Condition::ProgramCounter(addr) => cpu.program_counter == addr,
+            Condition::Memory(addr, value) => cpu.bus.cpu_vram[addr as usize & 0x07FF] == value,
+        }
+    }
+}
+
+fn parse_hex(text: &str) -> Option<u16> {
+    let text = text.trim_start_matches('$').trim_start_matches("0x");
+    u16::from_str_radix(text, 16).ok()
+}
+
+/* Controller ports an input script can drive */
+pub const PORTS: usize = 2;
+
+/*
+    Buttons to hold on a port from a frame onwards, until the next entry for
+    that port. Entries without a port are for port 1:
+
+    # frame [port] buttons
+    0   none
+    30  start
+    45  right+a
+    45  2 left
+ */
+#[derive(Debug, Default, PartialEq)]
+pub struct InputScript {
+    pub entries: Vec<(usize, usize, Buttons)>, // Frame, port from 0 and buttons
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<InputScript, String> {
+        let mut entries: Vec<(usize, usize, Buttons)> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let frame = parts.next().unwrap().parse::<usize>()
+                .map_err(|_| format!("line {}: expected a frame number", i + 1))?;
+            let mut next = parts.next();
+            let port = match next.map(|text| text.parse::<usize>()) {
+                Some(Ok(port)) if (1..=PORTS).contains(&port) => {
+                    next = parts.next();
+                    port - 1
+                },
+                Some(Ok(port)) => return Err(format!("line {}: port {} is not 1 or 2", i + 1, port)),
+                _ => 0,
+            };
+            let buttons = parse_buttons(next.unwrap_or("none"))
+                .map_err(|e| format!("line {}: {}", i + 1, e))?;
+
+            if entries.last().is_some_and(|(last, _, _)| *last > frame) {
+                return Err(format!("line {}: frames must be in order", i + 1));
+            }
+            entries.push((frame, port, buttons));
+        }
+
+        Ok(InputScript { entries })
+    }
+
+    pub fn load(path: &str) -> Result<InputScript, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read input file {}: {}", path, e))?;
+        InputScript::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn buttons(&self, port: usize, frame: usize) -> Buttons {
+        self.entries.iter()
+            .take_while(|(start, _, _)| *start <= frame)
+            .filter(|(_, entry_port, _)| *entry_port == port)
+            .last()
+            .map_or(Buttons::empty(), |(_, _, buttons)| *buttons)
+    }
+}
+
+pub fn parse_buttons(text: &str) -> Result<Buttons, String> {
+    let mut buttons = Buttons::empty();
+    if text.eq_ignore_ascii_case("none") || text == "-" {
+        return Ok(buttons);
+    }
+
+    for name in text.split('+') {
+        buttons |= match name.to_ascii_lowercase().as_str() {
+            "a" => Buttons::A,
+            "b" => Buttons::B,
+            "select" => Buttons::Select,
+            "start" => Buttons::Start,
+            "up" => Buttons::Up,
+            "down" => Buttons::Down,
+            "left" => Buttons::Left,
+            "right" => Buttons::Right,
+            _ => return Err(format!("unknown button '{}'", name)),
+        };
+    }
+
+    Ok(buttons)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct HeadlessOptions {
+    pub rom_path: String,
+    pub frames: Option<usize>,
+    pub until: Option<Condition>,
+    pub input: Option<String>,
+    pub movie: Option<String>,
+    pub record_movie: Option<String>,
+    pub png: Option<String>,
+    pub wav: Option<String>,
+    pub palette: Option<String>,
+    pub region: Region,
+    pub trace: Option<String>,
+    pub symbols: Vec<String>,
+    pub cdl: Option<String>,
+}
+
+impl HeadlessOptions {
+    /* Without a stop condition a frame limit is always applied, a movie's length by default */
+    pub fn frame_limit(&self, movie_length: Option<usize>) -> Option<usize> {
+        match (self.frames, self.until, movie_length) {
+            (Some(frames), _, _) => Some(frames),
+            (None, Some(_), _) => None,
+            (None, None, Some(length)) => Some(length),
+            (None, None, None) => Some(DEFAULT_FRAMES),
+        }
+    }
+}
+
+/* Ok(None) means help was requested */
+pub fn parse<I>(args: I) -> Result<Option<HeadlessOptions>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = HeadlessOptions::default();
+    let mut rom_path: Option<String> = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+
+        let Some(name) = arg.strip_prefix("--") else {
+            if arg.starts_with('-') {
+                return Err(format!("Unknown option: {}", arg));
+            }
+            if rom_path.is_some() {
+                return Err(format!("Unexpected argument: {}", arg));
+            }
+            rom_path = Some(arg);
+            continue;
+        };
+
+        let value = args.next().ok_or(format!("Option --{} requires a value", name))?;
+        match name {
+            "frames" => {
+                options.frames = Some(value.parse::<usize>()
+                    .map_err(|_| format!("Invalid value for --frames: '{}' (expected a number of frames)", value))?);
+            },
+            "until" => options.until = Some(Condition::parse(&value)?),
+            "input" => options.input = Some(value),
+            "movie" => options.movie = Some(value),
+            "record-movie" => options.record_movie = Some(value),
+            "png" => options.png = Some(value),
+            "wav" => options.wav = Some(value),
+            "palette" => options.palette = Some(value),
+            "region" => {
+                options.region = Region::from_name(&value)
+                    .ok_or(format!("Invalid value for --region: '{}' (expected ntsc, pal or dendy)", value))?;
+            },
+            "trace" => options.trace = Some(value),
+            "symbols" => options.symbols.push(value),
+            "cdl" => options.cdl = Some(value),
+            _ => return Err(format!("Unknown option: --{}", name)),
+        }
+    }
+
+    if options.input.is_some() && options.movie.is_some() {
+        return Err("--input and --movie can not be used together".to_string());
+    }
+
+    options.rom_path = rom_path.ok_or("Missing ROM path")?;
+    Ok(Some(options))
+}
+
+pub fn cpu_state(cpu: &Cpu) -> String {
+    format!(
+        "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+        cpu.program_counter, cpu.accumulator, cpu.register_x, cpu.register_y,
+        cpu.status.bits(), cpu.stack_pointer, cpu.cycles,
+    )
+}
+
+/* 16 bytes per line prefixed with the address of the first one */
+pub fn ram_dump(ram: &[u8]) -> String {
+    ram.chunks(16)
+        .enumerate()
+        .map(|(i, line)| {
+            let bytes: Vec<String> = line.iter().map(|byte| format!("{:02X}", byte)).collect();
+            format!("{:04X}: {}", i * 16, bytes.join(" "))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
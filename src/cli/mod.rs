@@ -0,0 +1,4 @@
+pub mod options;
+pub mod config;
+pub mod headless;
+pub mod disasm;
\ No newline at end of file
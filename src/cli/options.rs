@@ -0,0 +1,298 @@
+use crate::cli::config::Config;
+use crate::emulator::region::Region;
+use crate::emulator::rewind;
+use crate::emulator::input_device::DeviceKind;
+use crate::player::display::{ DisplaySettings, AspectRatio, Overscan };
+use crate::player::filters::FilterKind;
+use crate::player::gamepad::GamepadButton;
+use crate::player::bindings::Bindings;
+use crate::player::input::{ self, Socd };
+use crate::player::microphone::{ self, MicSource };
+
+pub const USAGE: &str = "\
+Usage: nes [OPTIONS] <ROM>
+
+Options:
+  --scale <N>            Window scale (default 3)
+  --region <REGION>      ntsc, pal or dendy (default ntsc)
+  --palette <FILE>       Load a 192 or 1536 byte .pal file
+  --filter <NAME>        none, ntsc, scale2x, scale3x, hq2x, xbrz2x, xbrz3x, scanlines or crt
+  --aspect <RATIO>       square, 8:7 or 4:3 (default square)
+  --overscan <T,B,L,R>   Pixels to crop from the top, bottom, left and right edges
+  --integer-scale        Only scale by whole numbers
+  --gamepad-a <BUTTON>   Controller button for NES A: a, b, x, y, leftshoulder, ... (default b)
+  --gamepad-b <BUTTON>   Controller button for NES B (default a)
+  --deadzone <N>         Fraction of the analog stick range to ignore, 0 to 1 (default 0.25)
+                         Key, controller and hotkey bindings are set in the config file,
+                         see the [keys.N], [gamepad.N] and [hotkeys] sections in the README
+  --four-score           Plug in a Four Score so players 3 and 4 can join
+  --port2 <DEVICE>       controller, zapper, vaus or power-pad (default controller)
+                         The Zapper aims and fires with the mouse, so does the Vaus paddle
+                         for Arkanoid, the Power Pad uses the keys in the [power_pad] section
+  --zapper               Same as --port2 zapper
+  --expansion <DEVICE>   Famicom expansion port: none or keyboard, the Family BASIC keyboard
+                         takes every key, Scroll Lock hands them back to the emulator
+  --microphone <SOURCE>  Famicom controller 2 microphone: key (hold Home) or audio (default key)
+  --mic-threshold <N>    Recording level that counts as blowing into the microphone, 0 to 1 (default 0.1)
+  --turbo-rate <HZ>      Presses per second for the turbo buttons, 1 to 30 (default 15)
+  --socd <POLICY>        Opposite directions held together: allow, neutral or last-wins (default allow)
+  --paused               Start paused (P toggles pause)
+  --mute                 Turn the sound off, accepted but has no effect until there is an APU
+  --frames <N>           Exit after running N frames
+  --rewind-memory <MB>   Memory for the rewind buffer, 0 turns rewind off (default 16)
+  --rewind-interval <N>  Frames between rewind snapshots (default 2)
+  --record-movie <FILE>  Record input from power on to an FCEUX .fm2 movie, saved on exit
+  --play-movie <FILE>    Play back an .fm2 movie, the keyboard takes over when it ends
+  --trace <FILE>         Write a nestest style trace of every instruction to FILE
+  --symbols <FILE>       Label names for the trace and debugger from a ca65 .dbg, FCEUX .nl or
+                         Mesen .mlb file, can be given more than once
+  --cdl <FILE>           Log which PRG and CHR bytes are used as code or data to an FCEUX .cdl file,
+                         saved on exit, an existing log for the ROM is added to
+  --debug                Start halted with a debugger prompt in the terminal, type help for its commands
+  --gdb <PORT>           Start halted and wait for a GDB remote protocol client on 127.0.0.1:PORT
+  --config <FILE>        Read options from an INI file, command line options take precedence
+  -h, --help             Print this message";
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub rom_path: String,
+    pub display: DisplaySettings,
+    pub filter: FilterKind,
+    pub region: Region,
+    pub bindings: Bindings,
+    pub turbo_rate: usize,
+    pub socd: Socd,
+    pub four_score: bool,
+    pub port_2: DeviceKind,
+    pub expansion: Option<DeviceKind>,
+    pub microphone: MicSource,
+    pub mic_threshold: f32,
+    pub palette: Option<String>,
+    pub paused: bool,
+    pub mute: bool,
+    pub frames: Option<usize>,
+    pub rewind_memory: usize,
+    pub rewind_interval: usize,
+    pub record_movie: Option<String>,
+    pub play_movie: Option<String>,
+    pub trace: Option<String>,
+    pub symbols: Vec<String>,
+    pub cdl: Option<String>,
+    pub debug: bool,
+    pub gdb: Option<u16>,
+    pub config: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            rom_path: String::new(),
+            display: DisplaySettings::default(),
+            filter: FilterKind::None,
+            region: Region::Ntsc,
+            bindings: Bindings::default(),
+            turbo_rate: input::DEFAULT_TURBO_RATE,
+            socd: Socd::Allow,
+            four_score: false,
+            port_2: DeviceKind::Joypad,
+            expansion: None,
+            microphone: MicSource::Key,
+            mic_threshold: microphone::DEFAULT_THRESHOLD,
+            palette: None,
+            paused: false,
+            mute: false,
+            frames: None,
+            rewind_memory: rewind::DEFAULT_MEMORY,
+            rewind_interval: rewind::DEFAULT_INTERVAL,
+            record_movie: None,
+            play_movie: None,
+            trace: None,
+            symbols: Vec::new(),
+            cdl: None,
+            debug: false,
+            gdb: None,
+            config: None,
+        }
+    }
+}
+
+const FLAGS: [&str; 6] = ["integer-scale", "paused", "mute", "four-score", "zapper", "debug"];
+
+/* Ok(None) means help was requested */
+pub fn parse<I>(args: I) -> Result<Option<Options>, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut settings: Vec<(String, String)> = Vec::new();
+    let mut rom_path: Option<String> = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+
+        match arg.strip_prefix("--") {
+            Some(name) if FLAGS.contains(&name) => settings.push((name.to_string(), "true".to_string())),
+            Some(name) => {
+                let value = args.next().ok_or(format!("Option --{} requires a value", name))?;
+                settings.push((name.to_string(), value));
+            },
+            None if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            None if rom_path.is_some() => return Err(format!("Unexpected argument: {}", arg)),
+            None => rom_path = Some(arg),
+        }
+    }
+
+    let mut options = Options::default();
+
+    if let Some((_, path)) = settings.iter().find(|(name, _)| name == "config") {
+        let config = Config::load(path)?;
+        for (key, value) in config.entries("") {
+            options.set(key, value).map_err(|e| format!("{}: {}", path, e))?;
+        }
+        options.bindings.load(&config).map_err(|e| format!("{}: {}", path, e))?;
+    }
+
+    for (name, value) in settings.iter() {
+        options.set(name, value)?;
+    }
+
+    if options.record_movie.is_some() && options.play_movie.is_some() {
+        return Err("--record-movie and --play-movie can not be used together".to_string());
+    }
+    if options.four_score && options.port_2 != DeviceKind::Joypad {
+        return Err(format!("--four-score needs a controller in port 2, not a {}", options.port_2.name()));
+    }
+
+    options.rom_path = rom_path.ok_or("Missing ROM path")?;
+    Ok(Some(options))
+}
+
+impl Options {
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let invalid = |expected: &str| format!("Invalid value for --{}: '{}' (expected {})", name, value, expected);
+
+        match name {
+            "scale" => {
+                self.display.scale = value.parse::<f64>().ok()
+                    .filter(|scale| *scale > 0.0)
+                    .ok_or(invalid("a positive number"))?;
+            },
+            "region" => {
+                self.region = Region::from_name(value).ok_or(invalid("ntsc, pal or dendy"))?;
+            },
+            "palette" => self.palette = Some(value.to_string()),
+            "filter" => {
+                self.filter = FilterKind::from_name(value).ok_or(invalid("a filter name, see --help"))?;
+            },
+            "aspect" => {
+                self.display.aspect = match value {
+                    "square" | "1:1" => AspectRatio::Square,
+                    "8:7" => AspectRatio::PixelAspect8x7,
+                    "4:3" => AspectRatio::Display4x3,
+                    _ => return Err(invalid("square, 8:7 or 4:3")),
+                };
+            },
+            "overscan" => {
+                let edges: Vec<usize> = value.split(',')
+                    .map(|edge| edge.trim().parse::<usize>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| invalid("four numbers like 8,8,0,0"))?;
+                if edges.len() != 4 {
+                    return Err(invalid("four numbers like 8,8,0,0"));
+                }
+                self.display.overscan = Overscan { top: edges[0], bottom: edges[1], left: edges[2], right: edges[3] };
+            },
+            "gamepad-a" => {
+                let button = GamepadButton::from_name(value).ok_or(invalid("a controller button name"))?;
+                self.bindings.gamepads.iter_mut().for_each(|gamepad| gamepad.a = button);
+            },
+            "gamepad-b" => {
+                let button = GamepadButton::from_name(value).ok_or(invalid("a controller button name"))?;
+                self.bindings.gamepads.iter_mut().for_each(|gamepad| gamepad.b = button);
+            },
+            "deadzone" => {
+                let deadzone = value.parse::<f32>().ok()
+                    .filter(|deadzone| (0.0..=1.0).contains(deadzone))
+                    .ok_or(invalid("a number from 0 to 1"))?;
+                self.bindings.gamepads.iter_mut().for_each(|gamepad| gamepad.deadzone = deadzone);
+            },
+            "turbo-rate" => {
+                self.turbo_rate = value.parse::<usize>().ok()
+                    .filter(|rate| (1..=input::MAX_TURBO_RATE).contains(rate))
+                    .ok_or(invalid("a number from 1 to 30"))?;
+            },
+            "socd" => {
+                self.socd = Socd::from_name(value).ok_or(invalid("allow, neutral or last-wins"))?;
+            },
+            "expansion" => {
+                self.expansion = match value {
+                    "none" => None,
+                    _ => Some(DeviceKind::from_name(value).filter(|kind| kind.is_expansion()).ok_or(invalid("none or keyboard"))?),
+                };
+            },
+            "microphone" => {
+                self.microphone = MicSource::from_name(value).ok_or(invalid("key or audio"))?;
+            },
+            "mic-threshold" => {
+                self.mic_threshold = value.parse::<f32>().ok()
+                    .filter(|threshold| (0.0..=1.0).contains(threshold))
+                    .ok_or(invalid("a number from 0 to 1"))?;
+            },
+            "integer-scale" => self.display.integer_scaling = parse_bool(value).ok_or(invalid("true or false"))?,
+            "paused" => self.paused = parse_bool(value).ok_or(invalid("true or false"))?,
+            "mute" => self.mute = parse_bool(value).ok_or(invalid("true or false"))?,
+            "four-score" => self.four_score = parse_bool(value).ok_or(invalid("true or false"))?,
+            "port2" => {
+                self.port_2 = DeviceKind::from_name(value)
+                    .filter(|kind| !kind.is_expansion())
+                    .ok_or(invalid("controller, zapper, vaus or power-pad"))?;
+            },
+            "zapper" => {
+                if parse_bool(value).ok_or(invalid("true or false"))? {
+                    self.port_2 = DeviceKind::Zapper;
+                } else if self.port_2 == DeviceKind::Zapper {
+                    self.port_2 = DeviceKind::Joypad;
+                }
+            },
+            "frames" => {
+                self.frames = Some(value.parse::<usize>().map_err(|_| invalid("a number of frames"))?);
+            },
+            "rewind-memory" => {
+                let megabytes = value.parse::<f64>().ok()
+                    .filter(|megabytes| *megabytes >= 0.0)
+                    .ok_or(invalid("a size in megabytes"))?;
+                self.rewind_memory = (megabytes * 1024.0 * 1024.0) as usize;
+            },
+            "rewind-interval" => {
+                self.rewind_interval = value.parse::<usize>().ok()
+                    .filter(|frames| *frames > 0)
+                    .ok_or(invalid("a positive number of frames"))?;
+            },
+            "record-movie" => self.record_movie = Some(value.to_string()),
+            "play-movie" => self.play_movie = Some(value.to_string()),
+            "trace" => self.trace = Some(value.to_string()),
+            "symbols" => self.symbols.push(value.to_string()),
+            "cdl" => self.cdl = Some(value.to_string()),
+            "debug" => self.debug = parse_bool(value).ok_or(invalid("true or false"))?,
+            "gdb" => {
+                self.gdb = Some(value.parse::<u16>().ok()
+                    .filter(|port| *port > 0)
+                    .ok_or(invalid("a port number"))?);
+            },
+            "config" => self.config = Some(value.to_string()),
+            _ => return Err(format!("Unknown option: --{}", name)),
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
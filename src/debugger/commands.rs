@@ -0,0 +1,169 @@
+use crate::emulator::nes::Nes;
+use crate::helpers::trace::trace_with_symbols;
+use crate::helpers::symbols::Symbols;
+use crate::debugger::core::{ Debugger, Access, RunMode, Stop };
+use crate::debugger::condition::{ Condition, parse_number };
+
+pub const HELP: &str = "\
+Numbers are decimal unless written $C000, 0xC000 or %1010. Names from
+--symbols files work anywhere an address does.
+
+  break <ADDR> [if <COND>]             Stop before the instruction at ADDR runs
+  watch <r|w|x> <ADDR>[-<ADDR>] [if <COND>]
+                                       Stop on reads, writes or execution in the range, e.g. watch rw $0300-$03FF
+  delete <ID>, enable <ID>, disable <ID>
+  list                                 Breakpoints and watchpoints
+  continue                             Run until something stops the game
+  step [N]                             Run N instructions (default 1), following JSRs
+  next                                 Like step but runs a JSR to its return
+  finish                               Run until the current routine returns
+  scanline <N>                         Run until the PPU starts scanline N (0-261, 241 is vblank)
+  nmi                                  Run until the NMI handler is entered
+  pause                                Stop a running game
+  regs                                 The next instruction and the registers
+  mem <ADDR> [LENGTH]                  Dump memory without side effects (default 64 bytes)
+  help
+
+Conditions use A, X, Y, SP, P, PC, the flags C Z I D V N, SCANLINE, DOT,
+FRAME and [ADDR] for memory, e.g. break $C123 if A == $10 && [$0300] > 3";
+
+/* Short names like gdb, b for break and so on */
+const ALIASES: [(&str, &str); 10] = [
+    ("b", "break"), ("w", "watch"), ("d", "delete"), ("l", "list"), ("c", "continue"),
+    ("s", "step"), ("n", "next"), ("f", "finish"), ("r", "regs"), ("x", "mem"),
+];
+
+const DEFAULT_DUMP_LENGTH: usize = 64;
+
+/*
+    Runs one command line. Commands that run the game only change the run
+    mode and return at once, the stop is reported when Debugger::run_frame
+    returns it.
+ */
+pub fn run_command(debugger: &mut Debugger, nes: &mut Nes, line: &str) -> Result<String, String> {
+    let line = line.trim();
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let command = ALIASES.iter().find(|(alias, _)| *alias == command).map_or(command, |(_, name)| *name);
+    let rest = rest.trim();
+    let args: Vec<&str> = rest.split_whitespace().collect();
+
+    match command {
+        "" => Ok(String::new()),
+        "help" | "h" => Ok(HELP.to_string()),
+        "break" => {
+            let (address, condition) = split_condition(rest, &debugger.symbols)?;
+            let address = parse_address(address, &debugger.symbols)?;
+            let id = debugger.add_breakpoint(address, address, Access::EXECUTE, condition);
+            Ok(format!("Breakpoint {} at {}", id, describe_address(address, &debugger.symbols)))
+        },
+        "watch" => {
+            let (target, condition) = split_condition(rest, &debugger.symbols)?;
+            let (access, range) = target.split_once(char::is_whitespace).ok_or("usage: watch <r|w|x> <ADDR>[-<ADDR>]")?;
+            let access = Access::parse(access).ok_or(format!("'{}' is not r, w, x or a mix of them", access))?;
+            let (start, end) = match range.trim().split_once('-') {
+                Some((start, end)) => (parse_address(start, &debugger.symbols)?, parse_address(end, &debugger.symbols)?),
+                None => (parse_address(range, &debugger.symbols)?, parse_address(range, &debugger.symbols)?),
+            };
+            let id = debugger.add_breakpoint(start, end, access, condition);
+            let (start, end) = (start.min(end), start.max(end));
+            Ok(format!("Watchpoint {} on {} {}-${:04X}", id, access, describe_address(start, &debugger.symbols), end))
+        },
+        "delete" | "enable" | "disable" => {
+            let id = args.first().and_then(|id| id.parse::<usize>().ok()).ok_or(format!("usage: {} <ID>", command))?;
+            let found = match command {
+                "delete" => debugger.delete(id),
+                _ => debugger.set_enabled(id, command == "enable"),
+            };
+            if !found {
+                return Err(format!("No breakpoint {}", id));
+            }
+            Ok(String::new())
+        },
+        "list" => {
+            if debugger.breakpoints.is_empty() {
+                return Ok("No breakpoints".to_string());
+            }
+            Ok(debugger.breakpoints.iter().map(|breakpoint| breakpoint.describe(&debugger.symbols)).collect::<Vec<_>>().join("\n"))
+        },
+        "continue" => run(debugger, nes, RunMode::Running),
+        "step" => {
+            let count = match args.first() {
+                Some(count) => parse_number(count).filter(|count| *count > 0).ok_or(format!("invalid count '{}'", count))? as usize,
+                None => 1,
+            };
+            run(debugger, nes, RunMode::StepInto(count))
+        },
+        "next" => {
+            debugger.step_over(nes);
+            Ok(String::new())
+        },
+        "finish" => {
+            debugger.step_out(nes);
+            Ok(String::new())
+        },
+        "scanline" => {
+            let scanline = args.first().and_then(|scanline| parse_number(scanline)).filter(|scanline| *scanline < 262)
+                .ok_or("usage: scanline <0-261>")?;
+            run(debugger, nes, RunMode::Scanline(scanline as usize))
+        },
+        "nmi" => run(debugger, nes, RunMode::Nmi),
+        "pause" => Ok(debugger.interrupt().map_or("Not running".to_string(), |stop| describe_stop(&stop, nes, &debugger.symbols))),
+        "regs" => Ok(trace_with_symbols(&mut nes.cpu, &debugger.symbols)),
+        "mem" => {
+            let address = parse_address(args.first().ok_or("usage: mem <ADDR> [LENGTH]")?, &debugger.symbols)?;
+            let length = match args.get(1) {
+                Some(length) => parse_number(length).ok_or(format!("invalid length '{}'", length))? as usize,
+                None => DEFAULT_DUMP_LENGTH,
+            };
+            Ok(dump(nes, address, length))
+        },
+        _ => Err(format!("Unknown command '{}', try help", command)),
+    }
+}
+
+/* The reason and where the game stopped */
+pub fn describe_stop(stop: &Stop, nes: &mut Nes, symbols: &Symbols) -> String {
+    format!("{}\n{}", stop, trace_with_symbols(&mut nes.cpu, symbols))
+}
+
+fn run(debugger: &mut Debugger, nes: &Nes, mode: RunMode) -> Result<String, String> {
+    debugger.resume(mode, nes);
+    Ok(String::new())
+}
+
+fn split_condition<'a>(text: &'a str, symbols: &Symbols) -> Result<(&'a str, Option<Condition>), String> {
+    match text.split_once(" if ") {
+        Some((target, condition)) => Ok((target.trim(), Some(Condition::parse_with_symbols(condition, symbols)?))),
+        None => Ok((text.trim(), None)),
+    }
+}
+
+/* A number or a name from the symbol files */
+fn parse_address(text: &str, symbols: &Symbols) -> Result<u16, String> {
+    parse_number(text.trim())
+        .and_then(|address| u16::try_from(address).ok())
+        .or_else(|| symbols.address(text.trim()))
+        .ok_or(format!("invalid address '{}'", text.trim()))
+}
+
+/* $C5F5 <UpdatePlayer> */
+fn describe_address(address: u16, symbols: &Symbols) -> String {
+    match symbols.name(address) {
+        Some(name) => format!("${:04X} <{}>", address, name),
+        None => format!("${:04X}", address),
+    }
+}
+
+/* 16 bytes a line, e.g. 0300: 00 01 02 ... */
+fn dump(nes: &Nes, address: u16, length: usize) -> String {
+    (0..length)
+        .map(|offset| address.wrapping_add(offset as u16))
+        .collect::<Vec<_>>()
+        .chunks(16)
+        .map(|line| {
+            let bytes: Vec<String> = line.iter().map(|address| format!("{:02X}", nes.cpu.mem_read_debugging(*address))).collect();
+            format!("{:04X}: {}", line[0], bytes.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+use crate::emulator::nes::Nes;
+use crate::helpers::symbols::Symbols;
+
+/*
+    Conditions on breakpoints, e.g. `A == $10 && X > 3` or `[$0300] & $80 != 0`.
+    Registers are A, X, Y, SP, P and PC, flags C, Z, I, D, V and N are 0 or 1,
+    SCANLINE, DOT and FRAME come from the PPU and [addr] reads memory without
+    side effects. Numbers are decimal, $hex, 0xhex or %binary, names from
+    symbol files stand for their address. Operators bind like in C: ! then
+    & | then comparisons then && then ||.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    text: String,
+    expr: Expr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Number(u32),
+    Register(Register),
+    Memory(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Register {
+    A, X, Y, Sp, P, Pc,
+    Flag(u8), // Bit of P
+    Scanline, Dot, Frame,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Or, And,
+    Equal, NotEqual, Less, LessEqual, Greater, GreaterEqual,
+    BitOr, BitAnd,
+    Add, Subtract,
+}
+
+/* Loosest first, each level is parsed from the operands of the next */
+const PRECEDENCE: [&[(&str, Operator)]; 6] = [
+    &[("||", Operator::Or)],
+    &[("&&", Operator::And)],
+    &[
+        ("==", Operator::Equal), ("!=", Operator::NotEqual), ("<=", Operator::LessEqual),
+        (">=", Operator::GreaterEqual), ("<", Operator::Less), (">", Operator::Greater),
+    ],
+    &[("|", Operator::BitOr)],
+    &[("&", Operator::BitAnd)],
+    &[("+", Operator::Add), ("-", Operator::Subtract)],
+];
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Condition, String> {
+        Condition::parse_with_symbols(text, &Symbols::default())
+    }
+
+    /* Registers win over symbols with the same name */
+    pub fn parse_with_symbols(text: &str, symbols: &Symbols) -> Result<Condition, String> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens: &tokens, position: 0, symbols };
+        let expr = parser.binary(0)?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected '{}' in condition", token));
+        }
+        Ok(Condition { text: text.trim().to_string(), expr })
+    }
+
+    pub fn is_true(&self, nes: &Nes) -> bool {
+        evaluate(&self.expr, nes) != 0
+    }
+
+    pub fn evaluate(&self, nes: &Nes) -> u32 {
+        evaluate(&self.expr, nes)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn evaluate(expr: &Expr, nes: &Nes) -> u32 {
+    let cpu = &nes.cpu;
+    match expr {
+        Expr::Number(value) => *value,
+        Expr::Register(register) => match register {
+            Register::A => cpu.accumulator as u32,
+            Register::X => cpu.register_x as u32,
+            Register::Y => cpu.register_y as u32,
+            Register::Sp => cpu.stack_pointer as u32,
+            Register::P => cpu.status.bits() as u32,
+            Register::Pc => cpu.program_counter as u32,
+            Register::Flag(bit) => (cpu.status.bits() >> bit) as u32 & 1,
+            Register::Scanline => cpu.bus.ppu.scanline as u32,
+            Register::Dot => cpu.bus.ppu.cycles as u32,
+            Register::Frame => nes.frame_count() as u32,
+        },
+        Expr::Memory(address) => cpu.mem_read_debugging(evaluate(address, nes) as u16) as u32,
+        Expr::Not(operand) => (evaluate(operand, nes) == 0) as u32,
+        Expr::Binary(operator, left, right) => {
+            let left = evaluate(left, nes);
+            /* Short circuit so [addr] on the right is not read for nothing */
+            match operator {
+                Operator::Or if left != 0 => return 1,
+                Operator::And if left == 0 => return 0,
+                _ => {},
+            }
+            let right = evaluate(right, nes);
+            match operator {
+                Operator::Or | Operator::And => (right != 0) as u32,
+                Operator::Equal => (left == right) as u32,
+                Operator::NotEqual => (left != right) as u32,
+                Operator::Less => (left < right) as u32,
+                Operator::LessEqual => (left <= right) as u32,
+                Operator::Greater => (left > right) as u32,
+                Operator::GreaterEqual => (left >= right) as u32,
+                Operator::BitOr => left | right,
+                Operator::BitAnd => left & right,
+                Operator::Add => left.wrapping_add(right),
+                Operator::Subtract => left.wrapping_sub(right),
+            }
+        },
+    }
+}
+
+/* Numbers parse to their value, everything else stays text */
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(u32),
+    Text(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 15] = ["||", "&&", "==", "!=", "<=", ">=", "<", ">", "|", "&", "+", "-", "!", "(", ")"];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        /* The first character is ASCII in both word cases so slicing after it is safe */
+        let word = || &rest[..1 + rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len() - 1)];
+        let length = if c == '$' || c == '%' || c.is_ascii_digit() {
+            let word = word();
+            tokens.push(Token::Number(parse_number(word).ok_or(format!("invalid number '{}'", word))?));
+            word.len()
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let word = word();
+            tokens.push(Token::Text(word.to_string()));
+            word.len()
+        } else if c == '[' || c == ']' {
+            tokens.push(Token::Text(c.to_string()));
+            1
+        } else {
+            let symbol = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)).ok_or(format!("unexpected '{}' in condition", c))?;
+            tokens.push(Token::Text(symbol.to_string()));
+            symbol.len()
+        };
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/* $C000, 0xC000, %1010 or 42 */
+pub fn parse_number(text: &str) -> Option<u32> {
+    if let Some(hex) = text.strip_prefix('$').or_else(|| text.strip_prefix("0x")).or_else(|| text.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = text.strip_prefix('%') {
+        u32::from_str_radix(binary, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    symbols: &'a Symbols,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.peek().cloned().ok_or("unfinished condition")?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        match self.next()? {
+            Token::Text(text) if text == symbol => Ok(()),
+            token => Err(format!("expected '{}' but found '{}'", symbol, token)),
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Text(text)) = self.peek() {
+            let Some((_, operator)) = PRECEDENCE[level].iter().find(|(symbol, _)| symbol == text) else { break };
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(*operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Text(text) => match text.as_str() {
+                "!" => Ok(Expr::Not(Box::new(self.unary()?))),
+                "(" => {
+                    let expr = self.binary(0)?;
+                    self.expect(")")?;
+                    Ok(expr)
+                },
+                "[" => {
+                    let address = self.binary(0)?;
+                    self.expect("]")?;
+                    Ok(Expr::Memory(Box::new(address)))
+                },
+                name => register(&name.to_ascii_uppercase()).map(Expr::Register)
+                    .or_else(|| self.symbols.address(name).map(|address| Expr::Number(address as u32)))
+                    .ok_or(format!("unknown register or symbol '{}'", name)),
+            },
+        }
+    }
+}
+
+fn register(name: &str) -> Option<Register> {
+    match name {
+        "A" => Some(Register::A),
+        "X" => Some(Register::X),
+        "Y" => Some(Register::Y),
+        "SP" | "S" => Some(Register::Sp),
+        "P" => Some(Register::P),
+        "PC" => Some(Register::Pc),
+        "C" => Some(Register::Flag(0)),
+        "Z" => Some(Register::Flag(1)),
+        "I" => Some(Register::Flag(2)),
+        "D" => Some(Register::Flag(3)),
+        "V" => Some(Register::Flag(6)),
+        "N" => Some(Register::Flag(7)),
+        "SCANLINE" => Some(Register::Scanline),
+        "DOT" => Some(Register::Dot),
+        "FRAME" => Some(Register::Frame),
+        _ => None,
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::{ self, BufRead, Write };
+use std::sync::mpsc::{ self, Receiver };
+use std::thread;
+
+pub const PROMPT: &str = "(nes) ";
+
+/*
+    Reads debugger commands from the terminal on its own thread so the window
+    keeps running while nothing is typed. An empty line repeats the last
+    command like gdb does.
+ */
+pub struct Console {
+    lines: Receiver<String>,
+    last: Option<String>,
+}
+
+impl Console {
+    pub fn spawn() -> Console {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Console { lines, last: None }
+    }
+
+    pub fn next_command(&mut self) -> Option<String> {
+        let line = self.lines.try_recv().ok()?;
+        if line.trim().is_empty() {
+            return self.last.clone();
+        }
+        self.last = Some(line.clone());
+        Some(line)
+    }
+
+    pub fn prompt(&self) {
+        print!("{}", PROMPT);
+        io::stdout().flush().unwrap();
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+use bitflags::bitflags;
+use crate::emulator::nes::Nes;
+use crate::emulator::cpu::MemoryAccess;
+use crate::emulator::opcodes::{ OPCODES, Code };
+use crate::debugger::condition::Condition;
+use crate::helpers::symbols::Symbols;
+use std::rc::Rc;
+
+bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Access: u8 {
+        const READ    = 0b001;
+        const WRITE   = 0b010;
+        const EXECUTE = 0b100;
+    }
+}
+
+impl Access {
+    /* r, w, x or any mix like rw */
+    pub fn parse(text: &str) -> Option<Access> {
+        text.chars().try_fold(Access::empty(), |access, c| match c {
+            'r' => Some(access | Access::READ),
+            'w' => Some(access | Access::WRITE),
+            'x' => Some(access | Access::EXECUTE),
+            _ => None,
+        }).filter(|access| !access.is_empty())
+    }
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (flag, c) in [(Access::READ, 'r'), (Access::WRITE, 'w'), (Access::EXECUTE, 'x')] {
+            if self.contains(flag) {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/* A PC breakpoint is an execute watchpoint on a single address */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub start: u16,
+    pub end: u16,
+    pub access: Access,
+    pub condition: Option<Condition>,
+    pub enabled: bool,
+}
+
+impl Breakpoint {
+    fn hit(&self, address: u16, access: Access, nes: &Nes) -> bool {
+        self.enabled
+            && self.access.intersects(access)
+            && (self.start..=self.end).contains(&address)
+            && self.condition.as_ref().is_none_or(|condition| condition.is_true(nes))
+    }
+}
+
+impl Breakpoint {
+    /* Like Display with the name of the first address after it, e.g. 1: break $C5F5 <UpdatePlayer> */
+    pub fn describe(&self, symbols: &Symbols) -> String {
+        let mut text = if self.access == Access::EXECUTE && self.start == self.end {
+            format!("{}: break ${:04X}", self.id, self.start)
+        } else if self.start == self.end {
+            format!("{}: watch {} ${:04X}", self.id, self.access, self.start)
+        } else {
+            format!("{}: watch {} ${:04X}-${:04X}", self.id, self.access, self.start, self.end)
+        };
+        if let Some(name) = symbols.name(self.start) {
+            text += &format!(" <{}>", name);
+        }
+        if let Some(condition) = &self.condition {
+            text += &format!(" if {}", condition);
+        }
+        if !self.enabled {
+            text += " (disabled)";
+        }
+        text
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe(&Symbols::default()))
+    }
+}
+
+/* Why the debugger stopped */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stop {
+    Breakpoint(usize),
+    Watchpoint { id: usize, access: MemoryAccess },
+    Step,
+    Nmi,
+    Scanline(usize),
+    Interrupted,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Breakpoint(id) => write!(f, "Breakpoint {}", id),
+            Stop::Watchpoint { id, access } if access.write => {
+                write!(f, "Watchpoint {}: wrote ${:02X} to ${:04X}", id, access.value, access.address)
+            },
+            Stop::Watchpoint { id, access } => {
+                write!(f, "Watchpoint {}: read ${:02X} from ${:04X}", id, access.value, access.address)
+            },
+            Stop::Step => write!(f, "Stepped"),
+            Stop::Nmi => write!(f, "NMI"),
+            Stop::Scanline(scanline) => write!(f, "Scanline {}", scanline),
+            Stop::Interrupted => write!(f, "Paused"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunMode {
+    Halted,
+    Running,
+    StepInto(usize),                         // Instructions left
+    StepOver { return_to: u16, stack: u8 },  // Runs until the JSR returns
+    StepOut { stack: u8 },                   // Runs until an RTS or RTI leaves the current routine
+    Scanline(usize),
+    Nmi,
+}
+
+/*
+    Breakpoints, watchpoints and run control on top of Nes::step. The
+    frontend calls run_frame instead of Nes::run_frame, it returns early
+    with the reason when something stops execution.
+ */
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    pub mode: RunMode,
+    pub symbols: Rc<Symbols>,   // Shared with the frontend's trace
+    next_id: usize,
+    resume_from: Option<u16>, // Breakpoints at this PC are skipped once so continuing gets past them
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger::new()
+    }
+}
+
+impl Debugger {
+    /* Starts halted so breakpoints can be set before the game runs */
+    pub fn new() -> Self {
+        Debugger {
+            breakpoints: Vec::new(),
+            mode: RunMode::Halted,
+            symbols: Rc::default(),
+            next_id: 1,
+            resume_from: None,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, start: u16, end: u16, access: Access, condition: Option<Condition>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.breakpoints.push(Breakpoint { id, start: start.min(end), end: start.max(end), access, condition, enabled: true });
+        id
+    }
+
+    pub fn delete(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+        self.breakpoints.len() != count
+    }
+
+    pub fn set_enabled(&mut self, id: usize, enabled: bool) -> bool {
+        self.breakpoints.iter_mut()
+            .find(|breakpoint| breakpoint.id == id)
+            .map(|breakpoint| breakpoint.enabled = enabled)
+            .is_some()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.mode == RunMode::Halted
+    }
+
+    pub fn resume(&mut self, mode: RunMode, nes: &Nes) {
+        self.mode = mode;
+        self.resume_from = Some(nes.cpu.program_counter);
+    }
+
+    /* Step over only differs from step into on a JSR */
+    pub fn step_over(&mut self, nes: &Nes) {
+        let pc = nes.cpu.program_counter;
+        let mode = match OPCODES.get(&nes.cpu.mem_read_debugging(pc)) {
+            Some(opcode) if matches!(opcode.code, Code::JSR) => RunMode::StepOver { return_to: pc.wrapping_add(3), stack: nes.cpu.stack_pointer },
+            _ => RunMode::StepInto(1),
+        };
+        self.resume(mode, nes);
+    }
+
+    pub fn step_out(&mut self, nes: &Nes) {
+        self.resume(RunMode::StepOut { stack: nes.cpu.stack_pointer }, nes);
+    }
+
+    pub fn interrupt(&mut self) -> Option<Stop> {
+        if self.is_halted() {
+            return None;
+        }
+        self.mode = RunMode::Halted;
+        Some(Stop::Interrupted)
+    }
+
+    /*
+        Runs until the frame is finished or something stops it, `before` sees
+        every instruction before it runs, e.g. to trace it. Nothing runs while
+        halted. Returns whether the frame finished, which can happen on the
+        same instruction that stopped it.
+     */
+    pub fn run_frame(&mut self, nes: &mut Nes, mut before: impl FnMut(&mut Nes)) -> (bool, Option<Stop>) {
+        while !self.is_halted() {
+            let (frame_done, stop) = self.step(nes, &mut before);
+            if stop.is_some() {
+                self.mode = RunMode::Halted;
+            }
+            if frame_done || stop.is_some() {
+                return (frame_done, stop);
+            }
+        }
+        (false, None)
+    }
+
+    fn step(&mut self, nes: &mut Nes, before: &mut impl FnMut(&mut Nes)) -> (bool, Option<Stop>) {
+        if nes.enter_nmi() && self.mode == RunMode::Nmi {
+            return (false, Some(Stop::Nmi));
+        }
+
+        let pc = nes.cpu.program_counter;
+        if self.resume_from.take() != Some(pc) {
+            if let Some(breakpoint) = self.breakpoints.iter().find(|breakpoint| breakpoint.hit(pc, Access::EXECUTE, nes)) {
+                return (false, Some(Stop::Breakpoint(breakpoint.id)));
+            }
+        }
+
+        before(nes);
+        let opcode = OPCODES.get(&nes.cpu.mem_read_debugging(pc));
+        let scanline = nes.cpu.bus.ppu.scanline;
+        let watching = self.breakpoints.iter().any(|breakpoint| breakpoint.enabled && breakpoint.access.intersects(Access::READ | Access::WRITE));
+        if watching {
+            nes.cpu.access_log = Some(Vec::new());
+        }
+        let frame_done = nes.execute_instruction();
+        let accesses = nes.cpu.access_log.take().unwrap_or_default();
+
+        /* The instruction's own bytes are fetched, not read */
+        let length = opcode.map_or(1, |opcode| opcode.bytes);
+        for access in accesses.iter().filter(|access| access.write || access.address.wrapping_sub(pc) >= length) {
+            let kind = if access.write { Access::WRITE } else { Access::READ };
+            if let Some(breakpoint) = self.breakpoints.iter().find(|breakpoint| breakpoint.hit(access.address, kind, nes)) {
+                return (frame_done, Some(Stop::Watchpoint { id: breakpoint.id, access: *access }));
+            }
+        }
+
+        let cpu = &nes.cpu;
+        let stop = match &mut self.mode {
+            RunMode::StepInto(left) => {
+                *left = left.saturating_sub(1);
+                *left == 0
+            },
+            RunMode::StepOver { return_to, stack } => cpu.program_counter == *return_to && cpu.stack_pointer == *stack,
+            RunMode::StepOut { stack } => {
+                /* Above the entry stack pointer, even if the stack wrapped around */
+                let popped = cpu.stack_pointer.wrapping_sub(*stack) as i8;
+                opcode.is_some_and(|opcode| matches!(opcode.code, Code::RTS | Code::RTI)) && popped > 0
+            },
+            RunMode::Scanline(target) => cpu.bus.ppu.scanline == *target && scanline != *target,
+            _ => false,
+        };
+        if !stop {
+            return (frame_done, None);
+        }
+        let stop = match self.mode {
+            RunMode::Scanline(target) => Stop::Scanline(target),
+            _ => Stop::Step,
+        };
+        (frame_done, Some(stop))
+    }
+}
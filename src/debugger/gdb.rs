@@ -0,0 +1,406 @@
+use std::io::{ ErrorKind, Read, Write };
+use std::net::{ SocketAddr, TcpListener, TcpStream, ToSocketAddrs };
+use crate::emulator::nes::Nes;
+use crate::emulator::cpu::Status;
+use crate::emulator::memory::Mem;
+use crate::debugger::core::{ Debugger, Access, RunMode, Stop };
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.nes.6502">
+    <reg name="a" bitsize="8" regnum="0"/>
+    <reg name="x" bitsize="8"/>
+    <reg name="y" bitsize="8"/>
+    <reg name="p" bitsize="8"/>
+    <reg name="sp" bitsize="8" type="data_ptr"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+  </feature>
+</target>
+"#;
+
+const MAX_MEMORY_READ: usize = 0x2000;
+
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/* $data#checksum */
+pub fn encode_packet(data: &str) -> String {
+    format!("${}#{:02x}", data, checksum(data.as_bytes()))
+}
+
+/* S05 is SIGTRAP, watchpoints also say which address was touched */
+pub fn stop_reply(stop: &Stop) -> String {
+    match stop {
+        Stop::Watchpoint { access, .. } if access.write => format!("T05watch:{:x};", access.address),
+        Stop::Watchpoint { access, .. } => format!("T05rwatch:{:x};", access.address),
+        Stop::Interrupted => "S02".to_string(),
+        _ => "S05".to_string(),
+    }
+}
+
+enum Received {
+    Packet(String),
+    Corrupt,
+    Interrupt,
+    Resend,
+}
+
+/*
+    A minimal GDB remote serial protocol stub so debuggers with 6502 support
+    can attach over TCP. It drives the same Debugger as the console: g/G/p/P
+    registers, m/M memory, Z/z breakpoints (0 and 1) and watchpoints (2 write,
+    3 read, 4 access), c continue, s step, ? and Ctrl+C. Everything is non
+    blocking, the frontend calls poll once per frame and report_stop when the
+    debugger stops.
+ */
+pub struct GdbStub {
+    listener: TcpListener,
+    client: Option<TcpStream>,
+    input: Vec<u8>,
+    last_reply: String,
+    last_stop: String,
+    running: bool,                              // A stop reply is owed for c or s
+    watchpoints: Vec<(u8, u16, u16, usize)>,    // Z type, start, end and the debugger id
+}
+
+impl GdbStub {
+    pub fn listen(address: impl ToSocketAddrs) -> Result<GdbStub, String> {
+        let listener = TcpListener::bind(address).map_err(|e| format!("Could not listen for gdb: {}", e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+        Ok(GdbStub {
+            listener,
+            client: None,
+            input: Vec::new(),
+            last_reply: String::new(),
+            last_stop: "S05".to_string(),
+            running: false,
+            watchpoints: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.listener.local_addr().unwrap()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /* Accepts a client, then answers whatever it sent since the last call */
+    pub fn poll(&mut self, debugger: &mut Debugger, nes: &mut Nes) {
+        if self.client.is_none() {
+            if let Ok((stream, _)) = self.listener.accept() {
+                if stream.set_nonblocking(true).is_ok() {
+                    self.client = Some(stream);
+                    self.input.clear();
+                }
+            }
+        }
+        self.read();
+
+        while let Some(received) = self.next_received() {
+            match received {
+                Received::Packet(packet) => {
+                    self.send_raw("+");
+                    if let Some(reply) = self.handle_packet(debugger, nes, &packet) {
+                        self.send(&reply);
+                    }
+                },
+                Received::Corrupt => self.send_raw("-"),
+                Received::Resend => {
+                    let reply = self.last_reply.clone();
+                    self.send_raw(&reply);
+                },
+                Received::Interrupt => {
+                    if let Some(stop) = debugger.interrupt() {
+                        self.report_stop(&stop);
+                    }
+                },
+            }
+        }
+    }
+
+    /* Sends the stop reply for the c or s the client is waiting on */
+    pub fn report_stop(&mut self, stop: &Stop) {
+        self.last_stop = stop_reply(stop);
+        if self.running {
+            self.running = false;
+            let reply = self.last_stop.clone();
+            self.send(&reply);
+        }
+    }
+
+    /* The reply to one packet, None when it comes later as a stop reply or not at all */
+    pub fn handle_packet(&mut self, debugger: &mut Debugger, nes: &mut Nes, packet: &str) -> Option<String> {
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => self.last_stop.clone(),
+            "g" => read_registers(nes),
+            "G" => ok_or_error(write_registers(nes, args)),
+            "p" => parse_hex(args).and_then(|n| read_register(nes, n as usize)).unwrap_or_else(error),
+            "P" => ok_or_error(args.split_once('=').and_then(|(n, value)| write_register(nes, parse_hex(n)? as usize, value))),
+            "m" => read_memory(nes, args).unwrap_or_else(error),
+            "M" => ok_or_error(write_memory(nes, args)),
+            "Z" => ok_or_error(self.insert_breakpoint(debugger, args)),
+            "z" => ok_or_error(self.remove_breakpoint(debugger, args)),
+            "c" | "s" => {
+                if let Some(address) = parse_hex(args) {
+                    nes.cpu.program_counter = address as u16;
+                }
+                debugger.resume(if command == "c" { RunMode::Running } else { RunMode::StepInto(1) }, nes);
+                self.running = true;
+                return None;
+            },
+            "D" => {
+                self.detach(debugger, nes);
+                self.send("OK");
+                self.client = None;
+                return None;
+            },
+            "k" => {
+                self.detach(debugger, nes);
+                self.client = None;
+                return None;
+            },
+            "H" => "OK".to_string(),
+            "q" => query(args),
+            _ => String::new(),
+        };
+        Some(reply)
+    }
+
+    fn insert_breakpoint(&mut self, debugger: &mut Debugger, args: &str) -> Option<()> {
+        let (kind, start, end) = parse_breakpoint(args)?;
+        let access = match kind {
+            0 | 1 => Access::EXECUTE,
+            2 => Access::WRITE,
+            3 => Access::READ,
+            4 => Access::READ | Access::WRITE,
+            _ => return None,
+        };
+        let id = debugger.add_breakpoint(start, end, access, None);
+        self.watchpoints.push((kind, start, end, id));
+        Some(())
+    }
+
+    fn remove_breakpoint(&mut self, debugger: &mut Debugger, args: &str) -> Option<()> {
+        let breakpoint = parse_breakpoint(args)?;
+        let index = self.watchpoints.iter().position(|&(kind, start, end, _)| (kind, start, end) == breakpoint)?;
+        let (_, _, _, id) = self.watchpoints.remove(index);
+        debugger.delete(id);
+        Some(())
+    }
+
+    /* The game carries on without the breakpoints gdb set */
+    fn detach(&mut self, debugger: &mut Debugger, nes: &Nes) {
+        for (_, _, _, id) in self.watchpoints.drain(..) {
+            debugger.delete(id);
+        }
+        self.running = false;
+        if debugger.is_halted() {
+            debugger.resume(RunMode::Running, nes);
+        }
+    }
+
+    fn read(&mut self) {
+        let Some(client) = self.client.as_mut() else { return };
+        let mut buffer = [0; 1024];
+        loop {
+            match client.read(&mut buffer) {
+                Ok(0) => {
+                    self.client = None;
+                    return;
+                },
+                Ok(len) => self.input.extend_from_slice(&buffer[..len]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(_) => {
+                    self.client = None;
+                    return;
+                },
+            }
+        }
+    }
+
+    fn next_received(&mut self) -> Option<Received> {
+        loop {
+            match *self.input.first()? {
+                0x03 => {
+                    self.input.remove(0);
+                    return Some(Received::Interrupt);
+                },
+                b'-' => {
+                    self.input.remove(0);
+                    return Some(Received::Resend);
+                },
+                b'$' => {
+                    let end = self.input.iter().position(|&byte| byte == b'#')?;
+                    let sent = self.input.get(end + 1..end + 3)?;
+                    let sent = std::str::from_utf8(sent).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    let data = self.input[1..end].to_vec();
+                    self.input.drain(..end + 3);
+
+                    if sent != Some(checksum(&data)) {
+                        return Some(Received::Corrupt);
+                    }
+                    return Some(Received::Packet(String::from_utf8_lossy(&data).into_owned()));
+                },
+                _ => { self.input.remove(0); }, // + acknowledgements and line noise
+            }
+        }
+    }
+
+    fn send(&mut self, data: &str) {
+        self.last_reply = encode_packet(data);
+        let reply = self.last_reply.clone();
+        self.send_raw(&reply);
+    }
+
+    fn send_raw(&mut self, data: &str) {
+        if let Some(client) = self.client.as_mut() {
+            if client.write_all(data.as_bytes()).is_err() {
+                self.client = None;
+            }
+        }
+    }
+}
+
+fn error() -> String {
+    "E01".to_string()
+}
+
+fn ok_or_error(result: Option<()>) -> String {
+    match result {
+        Some(()) => "OK".to_string(),
+        None => error(),
+    }
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 16).ok()
+}
+
+fn parse_bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/* type,address,length where length is the byte count for watchpoints and the kind for breakpoints */
+fn parse_breakpoint(args: &str) -> Option<(u8, u16, u16)> {
+    let mut fields = args.split(',');
+    let kind = parse_hex(fields.next()?)? as u8;
+    let start = parse_hex(fields.next()?)? as u16;
+    let length = parse_hex(fields.next()?)? as u16;
+    let end = match kind {
+        2..=4 => start.wrapping_add(length.max(1) - 1),
+        _ => start,
+    };
+    Some((kind, start, end))
+}
+
+/* The order g and G send them in: a, x, y, p and sp are a byte each, pc is two bytes little endian */
+fn register_bytes(nes: &Nes) -> [u8; 7] {
+    let cpu = &nes.cpu;
+    let [pc_lo, pc_hi] = cpu.program_counter.to_le_bytes();
+    [cpu.accumulator, cpu.register_x, cpu.register_y, cpu.status.bits(), cpu.stack_pointer, pc_lo, pc_hi]
+}
+
+fn read_registers(nes: &Nes) -> String {
+    to_hex(&register_bytes(nes))
+}
+
+fn write_registers(nes: &mut Nes, hex: &str) -> Option<()> {
+    let bytes: [u8; 7] = parse_bytes(hex)?.try_into().ok()?;
+    let cpu = &mut nes.cpu;
+    cpu.accumulator = bytes[0];
+    cpu.register_x = bytes[1];
+    cpu.register_y = bytes[2];
+    cpu.status = Status::from_bits_truncate(bytes[3]);
+    cpu.stack_pointer = bytes[4];
+    cpu.program_counter = u16::from_le_bytes([bytes[5], bytes[6]]);
+    Some(())
+}
+
+fn read_register(nes: &Nes, n: usize) -> Option<String> {
+    let bytes = register_bytes(nes);
+    match n {
+        0..=4 => Some(to_hex(&bytes[n..n + 1])),
+        5 => Some(to_hex(&bytes[5..7])),
+        _ => None,
+    }
+}
+
+fn write_register(nes: &mut Nes, n: usize, hex: &str) -> Option<()> {
+    let bytes = parse_bytes(hex)?;
+    let cpu = &mut nes.cpu;
+    match (n, bytes.as_slice()) {
+        (0, &[value]) => cpu.accumulator = value,
+        (1, &[value]) => cpu.register_x = value,
+        (2, &[value]) => cpu.register_y = value,
+        (3, &[value]) => cpu.status = Status::from_bits_truncate(value),
+        (4, &[value]) => cpu.stack_pointer = value,
+        (5, &[lo, hi]) => cpu.program_counter = u16::from_le_bytes([lo, hi]),
+        _ => return None,
+    }
+    Some(())
+}
+
+/* Reads have no side effects on the PPU or controllers */
+fn read_memory(nes: &Nes, args: &str) -> Option<String> {
+    let (address, length) = args.split_once(',')?;
+    let address = parse_hex(address)? as u16;
+    let length = (parse_hex(length)? as usize).min(MAX_MEMORY_READ);
+    let bytes: Vec<u8> = (0..length).map(|i| nes.cpu.mem_read_debugging(address.wrapping_add(i as u16))).collect();
+    Some(to_hex(&bytes))
+}
+
+/* Writes go through the bus like the CPU's would, registers included, PRG ROM can't be written */
+fn write_memory(nes: &mut Nes, args: &str) -> Option<()> {
+    let (range, hex) = args.split_once(':')?;
+    let (address, length) = range.split_once(',')?;
+    let address = parse_hex(address)? as u16;
+    let bytes = parse_bytes(hex)?;
+    if bytes.len() != parse_hex(length)? as usize {
+        return None;
+    }
+    if (0..bytes.len()).any(|i| address.wrapping_add(i as u16) >= 0x8000) {
+        return None;
+    }
+    for (i, byte) in bytes.into_iter().enumerate() {
+        nes.cpu.mem_write(address.wrapping_add(i as u16), byte);
+    }
+    Some(())
+}
+
+fn query(args: &str) -> String {
+    if args.starts_with("Supported") {
+        return "PacketSize=4000;qXfer:features:read+".to_string();
+    }
+    if let Some(range) = args.strip_prefix("Xfer:features:read:target.xml:") {
+        return read_target_xml(range).unwrap_or_else(error);
+    }
+    match args {
+        "Attached" => "1".to_string(),
+        "C" => "QC1".to_string(),
+        "fThreadInfo" => "m1".to_string(),
+        "sThreadInfo" => "l".to_string(),
+        _ => String::new(),
+    }
+}
+
+/* m for a chunk with more to come, l for the last one */
+fn read_target_xml(range: &str) -> Option<String> {
+    let (offset, length) = range.split_once(',')?;
+    let offset = (parse_hex(offset)? as usize).min(TARGET_XML.len());
+    let end = offset.saturating_add(parse_hex(length)? as usize).min(TARGET_XML.len());
+    let marker = if end == TARGET_XML.len() { 'l' } else { 'm' };
+    Some(format!("{}{}", marker, &TARGET_XML[offset..end]))
+}
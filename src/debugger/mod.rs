@@ -0,0 +1,5 @@
+pub mod core;
+pub mod condition;
+pub mod commands;
+pub mod console;
+pub mod gdb;
\ No newline at end of file
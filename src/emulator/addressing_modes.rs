@@ -1,7 +1,7 @@
 use crate::emulator::cpu::Cpu;
 use crate::emulator::memory::Mem;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum AddressingMode {
     Immediate,
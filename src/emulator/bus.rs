@@ -1,6 +1,9 @@
 use crate::emulator::rom::Rom;
 use crate::emulator::ppu::Ppu;
-use crate::emulator::joypad::Joypad;
+use crate::emulator::joypad::{ Joypad, Buttons, FourScore, FOUR_SCORE_SIGNATURES };
+use crate::emulator::input_device::InputDevice;
+use std::any::Any;
+use crate::emulator::region::Region;
 
 const BUS_ADDRESS_SPACE: usize = 0x800;
 
@@ -8,7 +11,11 @@ pub struct Bus {
     pub cpu_vram: [u8; BUS_ADDRESS_SPACE],
     pub prg_rom: Vec<u8>,
     pub ppu: Ppu,
-    pub joypad: Joypad,
+    pub port_1: Box<dyn InputDevice>, // $4016
+    pub port_2: Box<dyn InputDevice>, // $4017
+    pub expansion: Option<Box<dyn InputDevice>>, // Famicom expansion port, also read in $4017
+    pub microphone: bool, // Famicom controller 2 microphone, read in $4016
+    pub(crate) ppu_clock_remainder: usize,
 }
 
 impl Bus {
@@ -17,19 +24,103 @@ impl Bus {
             cpu_vram: [0; BUS_ADDRESS_SPACE],
             ppu: Ppu::new(rom.chr_rom, rom.screen_mirroring),
             prg_rom: rom.prg_rom,
-            joypad: Joypad::new(),
+            port_1: Box::new(Joypad::new()),
+            port_2: Box::new(Joypad::new()),
+            expansion: None,
+            microphone: false,
+            ppu_clock_remainder: 0,
         }
     }
 
+    /* Ports are numbered from 0, port 2 is the expansion port and anything past it is ignored */
+    pub fn plug_in(&mut self, port: usize, device: Box<dyn InputDevice>) {
+        match port {
+            0 => self.port_1 = device,
+            1 => self.port_2 = device,
+            2 => self.expansion = Some(device),
+            _ => {},
+        }
+    }
+
+    pub fn port(&self, port: usize) -> Option<&dyn InputDevice> {
+        match port {
+            0 => Some(self.port_1.as_ref()),
+            1 => Some(self.port_2.as_ref()),
+            2 => self.expansion.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn port_mut(&mut self, port: usize) -> Option<&mut dyn InputDevice> {
+        match port {
+            0 => Some(self.port_1.as_mut()),
+            1 => Some(self.port_2.as_mut()),
+            2 => self.expansion.as_deref_mut(),
+            _ => None,
+        }
+    }
+
+    /* The device in a port if it is a T */
+    pub fn device<T: InputDevice>(&self, port: usize) -> Option<&T> {
+        self.port(port).and_then(|device| (device as &dyn Any).downcast_ref())
+    }
+
+    pub fn device_mut<T: InputDevice>(&mut self, port: usize) -> Option<&mut T> {
+        self.port_mut(port).and_then(|device| (device as &mut dyn Any).downcast_mut())
+    }
+
+    /* Players 0 and 1 are the controllers in the ports, 2 and 3 are on a Four Score */
+    pub fn set_buttons(&mut self, player: usize, buttons: Buttons) {
+        if let Some(device) = self.port_mut(player % 2).filter(|_| player < 4) {
+            device.set_buttons(player / 2, buttons);
+        }
+    }
+
+    pub fn buttons(&self, player: usize) -> Buttons {
+        self.port(player % 2).filter(|_| player < 4).map_or(Buttons::empty(), |device| device.buttons(player / 2))
+    }
+
+    /* Connecting puts controllers in both ports, disconnecting leaves them there */
+    pub fn set_four_score(&mut self, connected: bool) {
+        for (port, signature) in FOUR_SCORE_SIGNATURES.into_iter().enumerate() {
+            if connected && self.device::<Joypad>(port).is_none() {
+                self.plug_in(port, Box::new(Joypad::new()));
+            }
+            if let Some(joypad) = self.device_mut::<Joypad>(port) {
+                joypad.four_score = connected.then(|| FourScore::new(signature));
+            }
+        }
+    }
+
+    pub fn four_score(&self) -> bool {
+        self.device::<Joypad>(0).is_some_and(|joypad| joypad.four_score.is_some())
+    }
+
+    /* Peripherals are not part of the console state, a new Bus keeps what was plugged in */
+    pub fn plug_in_from(&mut self, other: &Bus) {
+        self.port_1 = other.port_1.clone();
+        self.port_2 = other.port_2.clone();
+        self.expansion = other.expansion.clone();
+        self.microphone = other.microphone;
+    }
+
     pub fn read_rom(&self, mut addr: u16) -> u8 {
         addr -= 0x8000;
         if self.prg_rom.len() == 0x4000 && addr >= 0x4000 {
-            addr = addr % 0x4000; // Mirror in case of 16KB ROM
+            addr %= 0x4000; // Mirror in case of 16KB ROM
         }
         self.prg_rom[addr as usize]
     }
 
+    pub fn set_region(&mut self, region: Region) {
+        self.ppu.region = region;
+        self.ppu_clock_remainder = 0;
+    }
+
     pub fn tick(&mut self, cycles: usize) {
-        self.ppu.tick(cycles * 3);
+        let (dots, per_cycles) = self.ppu.region.ppu_clock_ratio();
+        let total = cycles * dots + self.ppu_clock_remainder;
+        self.ppu_clock_remainder = total % per_cycles;
+        self.ppu.tick(total / per_cycles);
     }
 }
\ No newline at end of file
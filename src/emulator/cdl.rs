@@ -0,0 +1,156 @@
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use bitflags::bitflags;
+use crate::emulator::cpu::Cpu;
+use crate::emulator::rom::Rom;
+use crate::emulator::addressing_modes::AddressingMode;
+
+bitflags! {
+    /* Bits 2 and 3 hold which 8 KiB window ($8000, $A000, $C000 or $E000) the byte was last seen at */
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PrgFlags: u8 {
+        const CODE          = 0b0000_0001;
+        const DATA          = 0b0000_0010;
+        const WINDOW        = 0b0000_1100;
+        const INDIRECT_CODE = 0b0001_0000; // Reached through JMP ($xxxx)
+        const INDIRECT_DATA = 0b0010_0000; // Read through ($xx,X) or ($xx),Y
+        const PCM_DATA      = 0b0100_0000; // Fetched by the DMC, never set without an APU
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ChrFlags: u8 {
+        const RENDERED = 0b0000_0001;
+        const READ     = 0b0000_0010; // Through $2007
+    }
+}
+
+/*
+    FCEUX's Code/Data Logger file: a flag byte for every PRG ROM byte
+    followed by one for every CHR ROM byte. Flags only ever get added, so a
+    log can be loaded again and grown over several sessions.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeDataLog {
+    pub prg: Vec<u8>,
+    pub chr: Vec<u8>,
+}
+
+impl CodeDataLog {
+    pub fn new(rom: &Rom) -> Self {
+        CodeDataLog {
+            prg: vec![0; rom.prg_rom.len()],
+            chr: vec![0; rom.chr_rom.len()],
+        }
+    }
+
+    pub fn from_bytes(data: &[u8], rom: &Rom) -> Result<Self, String> {
+        let prg_size = rom.prg_rom.len();
+        if data.len() != prg_size + rom.chr_rom.len() {
+            return Err(format!("expected {} bytes for this ROM, found {}", prg_size + rom.chr_rom.len(), data.len()));
+        }
+        Ok(CodeDataLog {
+            prg: data[..prg_size].to_vec(),
+            chr: data[prg_size..].to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [&self.prg[..], &self.chr[..]].concat()
+    }
+
+    pub fn load(path: &str, rom: &Rom) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+        CodeDataLog::from_bytes(&data, rom).map_err(|e| format!("{} is not a code/data log for this ROM, {}", path, e))
+    }
+
+    /* Continues the log at `path` if there is one */
+    pub fn load_or_new(path: &str, rom: &Rom) -> Result<Self, String> {
+        if Path::new(path).exists() {
+            CodeDataLog::load(path, rom)
+        } else {
+            Ok(CodeDataLog::new(rom))
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_bytes()).map_err(|e| format!("Could not write {}: {}", path, e))
+    }
+
+    pub fn prg_flags(&self, offset: usize) -> PrgFlags {
+        PrgFlags::from_bits_truncate(self.prg.get(offset).copied().unwrap_or(0))
+    }
+
+    pub fn chr_flags(&self, offset: usize) -> ChrFlags {
+        ChrFlags::from_bits_truncate(self.chr.get(offset).copied().unwrap_or(0))
+    }
+
+    /* PRG bytes logged as code, as data and CHR bytes logged at all */
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let code = (0..self.prg.len()).filter(|&offset| self.prg_flags(offset).contains(PrgFlags::CODE)).count();
+        let data = (0..self.prg.len()).filter(|&offset| self.prg_flags(offset).contains(PrgFlags::DATA)).count();
+        let chr = self.chr.iter().filter(|flags| **flags != 0).count();
+        (code, data, chr)
+    }
+}
+
+/* Whether a disassembler should keep the byte as .byte, it was read but never run */
+pub fn is_data(flags: u8) -> bool {
+    let flags = PrgFlags::from_bits_truncate(flags);
+    flags.contains(PrgFlags::DATA) && !flags.intersects(PrgFlags::CODE | PrgFlags::INDIRECT_CODE)
+}
+
+/* The PRG half of the log while the CPU runs, the PPU keeps the CHR half */
+#[derive(Debug, Clone)]
+pub struct PrgLog {
+    pub flags: Vec<u8>,
+    instruction: Range<u16>, // Bytes of the instruction being run, reads elsewhere are data
+    indirect: bool,
+}
+
+impl PrgLog {
+    pub fn new(flags: Vec<u8>) -> Self {
+        PrgLog { flags, instruction: 0..0, indirect: false }
+    }
+
+    /* Only NROM is known, a 16 KiB PRG is mirrored at $8000 and $C000 */
+    fn mark(&mut self, address: u16, flags: PrgFlags) {
+        if address < 0x8000 || self.flags.is_empty() {
+            return;
+        }
+        let offset = (address as usize - 0x8000) % self.flags.len();
+        let window = ((address >> 13) & 0b11) as u8;
+        self.flags[offset] |= flags.bits() | window << 2;
+    }
+
+    pub(crate) fn read(&mut self, address: u16) {
+        let flags = if self.instruction.contains(&address) {
+            PrgFlags::CODE
+        } else if self.indirect {
+            PrgFlags::DATA | PrgFlags::INDIRECT_DATA
+        } else {
+            PrgFlags::DATA
+        };
+        self.mark(address, flags);
+    }
+}
+
+impl Cpu {
+    /* Called again once the opcode tells how long the instruction is */
+    pub(crate) fn log_instruction(&mut self, address: u16, length: u16, mode: &AddressingMode) {
+        if let Some(log) = self.prg_log.as_mut() {
+            log.instruction = address..address.saturating_add(length);
+            log.indirect = matches!(mode, AddressingMode::Indirect_X | AddressingMode::Indirect_Y);
+        }
+    }
+
+    pub(crate) fn log_instruction_end(&mut self, mode: &AddressingMode) {
+        if let Some(log) = self.prg_log.as_mut() {
+            log.instruction = 0..0;
+            log.indirect = false;
+            if *mode == AddressingMode::JumpIndirect {
+                log.mark(self.program_counter, PrgFlags::INDIRECT_CODE);
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::emulator::{ bus::Bus, memory::Mem, rom::Rom, ppu::Ppu };
 use crate::emulator::interrupts::Interrupt;
+use crate::emulator::cdl::PrgLog;
 use bitflags::bitflags;
 
 const RESET_VECTOR: usize = 0xFFFC;
@@ -26,6 +27,14 @@ impl Default for Status {
     }
 }
 
+/* A read or write the CPU made, only logged while a debugger watches memory */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryAccess {
+    pub address: u16,
+    pub value: u8,
+    pub write: bool,
+}
+
 pub struct Cpu {
     pub accumulator: u8, 
     pub register_x: u8,
@@ -40,6 +49,9 @@ pub struct Cpu {
     pub bus: Bus,
     
     pub interrupt: Option<Interrupt>,
+
+    pub access_log: Option<Vec<MemoryAccess>>,
+    pub prg_log: Option<PrgLog>,
 }
 
 impl Cpu {
@@ -54,6 +66,8 @@ impl Cpu {
             bus,
             cycles: 0,
             interrupt: None,
+            access_log: None,
+            prg_log: None,
         }
     }
 
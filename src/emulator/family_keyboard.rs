@@ -0,0 +1,118 @@
+use crate::emulator::ppu::Ppu;
+use crate::emulator::input_device::{ InputDevice, DeviceKind };
+use crate::emulator::savestate::{ StateWriter, StateReader };
+
+pub const ROWS: usize = 9;
+
+/* Key names by row and column, for $4017 bits 1 to 4 */
+pub const KEYS: [[[&str; 4]; 2]; ROWS] = [
+    [["]", "[", "RETURN", "F8"], ["STOP", "¥", "RSHIFT", "KANA"]],
+    [[";", ":", "@", "F7"], ["^", "-", "/", "_"]],
+    [["K", "L", "O", "F6"], ["0", "P", ",", "."]],
+    [["J", "U", "I", "F5"], ["8", "9", "N", "M"]],
+    [["H", "G", "Y", "F4"], ["6", "7", "V", "B"]],
+    [["D", "R", "T", "F3"], ["4", "5", "C", "F"]],
+    [["A", "S", "W", "F2"], ["3", "E", "Z", "X"]],
+    [["CTR", "Q", "ESC", "F1"], ["2", "1", "GRPH", "LSHIFT"]],
+    [["LEFT", "RIGHT", "UP", "CLR"], ["DOWN", "INS", "DEL", "SPACE"]],
+];
+
+const RESET: u8 = 0b0000_0001;
+const COLUMN: u8 = 0b0000_0010;
+const ENABLE: u8 = 0b0000_0100;
+
+/*
+    https://www.nesdev.org/wiki/Family_BASIC_Keyboard
+    Plugs into the Famicom expansion port. Software resets the scan with
+    $4016 bit 0, then toggles bit 1 to pick a column, every time the column
+    goes back to 0 the next row is selected. $4017 bits 1 to 4 return the 4
+    keys of that row and column, 0 for pressed. Nothing reads back while
+    bit 2 is clear, and the row after the last one reads 0, which is how
+    software finds out a keyboard is there.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FamilyKeyboard {
+    pub keys: [[u8; 2]; ROWS], // Pressed keys, bit n is $4017 bit n + 1
+    pub row: usize,
+    pub column: usize,
+    pub enabled: bool,
+}
+
+impl FamilyKeyboard {
+    pub fn new() -> Self {
+        FamilyKeyboard::default()
+    }
+
+    /* Returns false for names that are not on the keyboard */
+    pub fn set_key(&mut self, name: &str, pressed: bool) -> bool {
+        let Some((row, column, bit)) = key_position(name) else { return false };
+        if pressed {
+            self.keys[row][column] |= 1 << bit;
+        } else {
+            self.keys[row][column] &= !(1 << bit);
+        }
+        true
+    }
+
+    pub fn is_pressed(&self, name: &str) -> bool {
+        key_position(name).is_some_and(|(row, column, bit)| self.keys[row][column] & (1 << bit) != 0)
+    }
+
+    pub fn release_all(&mut self) {
+        self.keys = [[0; 2]; ROWS];
+    }
+}
+
+fn key_position(name: &str) -> Option<(usize, usize, usize)> {
+    (0..ROWS)
+        .flat_map(|row| (0..2).flat_map(move |column| (0..4).map(move |bit| (row, column, bit))))
+        .find(|(row, column, bit)| KEYS[*row][*column][*bit].eq_ignore_ascii_case(name))
+}
+
+impl InputDevice for FamilyKeyboard {
+    fn kind(&self) -> DeviceKind {
+        DeviceKind::FamilyKeyboard
+    }
+
+    fn write(&mut self, value: u8) {
+        self.enabled = value & ENABLE != 0;
+        if !self.enabled {
+            return;
+        }
+        let column = ((value & COLUMN) >> 1) as usize;
+        if value & RESET != 0 {
+            self.row = 0;
+        } else if self.column == 1 && column == 0 {
+            self.row = (self.row + 1).min(ROWS);
+        }
+        self.column = column;
+    }
+
+    fn read(&mut self, ppu: &Ppu) -> u8 {
+        self.read_debugging(ppu)
+    }
+
+    fn read_debugging(&self, _ppu: &Ppu) -> u8 {
+        if !self.enabled || self.row >= ROWS {
+            return 0;
+        }
+        (!self.keys[self.row][self.column] & 0x0F) << 1
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u8(self.row as u8);
+        writer.u8(self.column as u8);
+        writer.bool(self.enabled);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.row = (reader.u8()? as usize).min(ROWS);
+        self.column = reader.u8()? as usize & 1;
+        self.enabled = reader.bool()?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn InputDevice> {
+        Box::new(*self)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::emulator::palette::Palette;
+
+pub struct Frame {
+    pub data: Vec<u8>,
+    pub pixels: Vec<u16>,
+}
+ 
+impl Frame {
+    pub const WIDTH: usize = 256;
+    pub const HEIGHT: usize = 240;
+    pub const RGB_DATA_LEN: usize = 3;
+ 
+    pub fn new() -> Self {
+        Frame {
+            data: vec![0; Frame::WIDTH * Frame::HEIGHT * Frame::RGB_DATA_LEN],
+            pixels: vec![0; Frame::WIDTH * Frame::HEIGHT],
+        }
+    }
+ 
+    pub fn update_pixel(&mut self, x: usize, y: usize, pixel: u16) {
+        if x < Frame::WIDTH && y < Frame::HEIGHT {
+            self.pixels[y * Frame::WIDTH + x] = pixel;
+        }
+    }
+
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        for (i, &pixel) in self.pixels.iter().enumerate() {
+            let rgb = palette.pixel(pixel);
+            let base = i * Frame::RGB_DATA_LEN;
+            self.data[base] = rgb.0;
+            self.data[base + 1] = rgb.1;
+            self.data[base + 2] = rgb.2;
+        }
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Frame::new()
+    }
+}
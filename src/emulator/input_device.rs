@@ -0,0 +1,99 @@
+use std::any::Any;
+use crate::emulator::ppu::Ppu;
+use crate::emulator::joypad::{ Buttons, Joypad };
+use crate::emulator::zapper::Zapper;
+use crate::emulator::vaus::Vaus;
+use crate::emulator::power_pad::PowerPad;
+use crate::emulator::family_keyboard::FamilyKeyboard;
+use crate::emulator::savestate::{ StateWriter, StateReader };
+
+/*
+    Anything plugged into a controller port. Writes to $4016 reach both ports,
+    reads from $4016 and $4017 go to the device in that port and return the
+    low data bits, the bus adds the open bus bits. Famicom expansion port
+    devices sit in port 2 (counted from 0) and their reads are added to $4017.
+
+    Frontends reach the concrete device to feed it input:
+
+    if let Some(zapper) = nes.device_mut::<Zapper>(1) {
+        zapper.trigger = true;
+    }
+ */
+pub trait InputDevice: Any {
+    fn kind(&self) -> DeviceKind;
+
+    fn write(&mut self, value: u8);
+    fn read(&mut self, ppu: &Ppu) -> u8;
+    fn read_debugging(&self, ppu: &Ppu) -> u8; // Same as read without shifting anything
+
+    /* Controllers on this device, only joypads have any */
+    fn set_buttons(&mut self, _controller: usize, _buttons: Buttons) {}
+    fn buttons(&self, _controller: usize) -> Buttons {
+        Buttons::empty()
+    }
+
+    /* Only the serial state, what is held comes from the frontend every frame */
+    fn save_state(&self, writer: &mut StateWriter);
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String>;
+
+    fn clone_box(&self) -> Box<dyn InputDevice>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeviceKind {
+    #[default]
+    Joypad,
+    Zapper,
+    Vaus,
+    PowerPad,
+    FamilyKeyboard,
+}
+
+impl DeviceKind {
+    pub fn from_name(name: &str) -> Option<DeviceKind> {
+        match name {
+            "controller" | "joypad" => Some(DeviceKind::Joypad),
+            "zapper" => Some(DeviceKind::Zapper),
+            "vaus" | "arkanoid" => Some(DeviceKind::Vaus),
+            "power-pad" => Some(DeviceKind::PowerPad),
+            "keyboard" | "family-basic" => Some(DeviceKind::FamilyKeyboard),
+            _ => None,
+        }
+    }
+
+    /* Saved as a byte in save states */
+    pub fn from_index(index: u8) -> Option<DeviceKind> {
+        [DeviceKind::Joypad, DeviceKind::Zapper, DeviceKind::Vaus, DeviceKind::PowerPad, DeviceKind::FamilyKeyboard].get(index as usize).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeviceKind::Joypad => "controller",
+            DeviceKind::Zapper => "Zapper",
+            DeviceKind::Vaus => "Vaus paddle",
+            DeviceKind::PowerPad => "Power Pad",
+            DeviceKind::FamilyKeyboard => "Family BASIC keyboard",
+        }
+    }
+
+    /* Goes in the Famicom expansion port rather than a controller port */
+    pub fn is_expansion(&self) -> bool {
+        *self == DeviceKind::FamilyKeyboard
+    }
+
+    pub fn create(&self) -> Box<dyn InputDevice> {
+        match self {
+            DeviceKind::Joypad => Box::new(Joypad::new()),
+            DeviceKind::Zapper => Box::new(Zapper::new()),
+            DeviceKind::Vaus => Box::new(Vaus::new()),
+            DeviceKind::PowerPad => Box::new(PowerPad::new()),
+            DeviceKind::FamilyKeyboard => Box::new(FamilyKeyboard::new()),
+        }
+    }
+}
+
+impl Clone for Box<dyn InputDevice> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
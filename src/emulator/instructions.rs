@@ -14,7 +14,10 @@ impl Cpu {
     }
 
     pub fn step(&mut self) -> bool  {
+        let start = self.program_counter;
+        self.log_instruction(start, 1, &AddressingMode::Implied);
         let code = OPCODES.get(&self.next()).expect("Invalid opcode");
+        self.log_instruction(start, code.bytes, &code.mode);
         let (addr, bytes_used, crossed_page) = self.get_operand_address(&code.mode);
         self.program_counter = self.program_counter.wrapping_add(bytes_used);
         
@@ -70,7 +73,7 @@ impl Cpu {
                 let addr = addr.unwrap();
                 let val = self.mem_read(addr);
 
-                let res = self.addition(val.wrapping_neg().wrapping_sub(1) as u8);
+                let res = self.addition(val.wrapping_neg().wrapping_sub(1));
                 self.accumulator = res;
                 self.update_zero_and_negative_flag(res);
             },
@@ -478,7 +481,7 @@ impl Cpu {
                 let val = self.mem_read(addr).wrapping_add(1);
                 self.mem_write(addr, val);
 
-                let res = self.addition(val.wrapping_neg().wrapping_sub(1) as u8);
+                let res = self.addition(val.wrapping_neg().wrapping_sub(1));
                 self.accumulator = res;
                 self.update_zero_and_negative_flag(self.accumulator);
             }
@@ -577,6 +580,7 @@ impl Cpu {
 
             Code::BRK => { /* BRK */
                 self.status.insert(Status::BREAKONE);
+                self.log_instruction_end(&code.mode);
                 return false; // Change later
             }, 
             
@@ -585,8 +589,8 @@ impl Cpu {
             Code::DOP_U => (), /* DOP */
             Code::NOP => (), /* NOP */
         }
+        self.log_instruction_end(&code.mode);
 
-        
         let mut cycle_inc: usize = code.cycles;
         match code.code {
             /* 
@@ -1,7 +1,10 @@
 use bitflags::bitflags;
+use crate::emulator::ppu::Ppu;
+use crate::emulator::input_device::{ InputDevice, DeviceKind };
+use crate::emulator::savestate::{ StateWriter, StateReader };
 
 bitflags! {
-    #[derive(Clone, Copy, Debug)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     pub struct Buttons: u8 {
         const A      = 0b0000_0001;
         const B      = 0b0000_0010;
@@ -14,59 +17,146 @@ bitflags! {
     }
 }
 
+/* Adapter IDs sent after the two controllers, the 1 bit arrives on read 20 of $4016 and read 19 of $4017 */
+pub const FOUR_SCORE_SIGNATURES: [u8; 2] = [0x08, 0x04];
+
+/*
+    https://www.nesdev.org/wiki/Four_Score
+    Sits on both ports, each port then reports its own controller, then the
+    one for player 3 or 4, then the adapter ID, 24 bits in all
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FourScore {
+    pub state: Buttons, // Player 3 on $4016, player 4 on $4017
+    pub signature: u8,
+}
+
+impl FourScore {
+    pub fn new(signature: u8) -> Self {
+        FourScore { state: Buttons::empty(), signature }
+    }
+}
+
+/* 
+    https://www.nesdev.org/wiki/Standard_controller
+    While strobe is high the shift register keeps reloading from the buttons,
+    once it goes low each read shifts out one button in A B Select Start Up 
+    Down Left Right order, then 1s after the 8th read (24th with a Four Score)
+ */
+#[derive(Clone)]
 pub struct Joypad {
     pub strobe: bool,
-    pub buttons: Box<dyn Iterator<Item = Buttons>>,
     pub state: Buttons,
-    pub buffer: u8,
+    pub buffer: u32, // Shift register
+    pub four_score: Option<FourScore>,
 }
 
 impl Joypad {
     pub fn new() -> Self {
         Joypad {
             strobe: false,
-            buttons: Box::from(Buttons::all().iter()),
             state: Buttons::empty(),
             buffer: 0,
+            four_score: None,
         }
     }
 
-    pub fn write(&mut self, value: u8) {
+    /* Everything the port sends after a strobe, first bit lowest */
+    pub fn report(&self) -> u32 {
+        let report = self.state.bits() as u32;
+        match self.four_score {
+            Some(adapter) => report | (adapter.state.bits() as u32) << 8 | (adapter.signature as u32) << 16 | 0xFF00_0000,
+            None => report | 0xFFFF_FF00,
+        }
+    }
+
+    pub fn press(&mut self, button: Buttons) {
+        self.state.insert(button);
+    }
+
+    pub fn release(&mut self, button: Buttons) {
+        self.state.remove(button);
+    }
+}
+
+impl Default for Joypad {
+    fn default() -> Self {
+        Joypad::new()
+    }
+}
+
+impl InputDevice for Joypad {
+    fn kind(&self) -> DeviceKind {
+        DeviceKind::Joypad
+    }
+
+    fn write(&mut self, value: u8) {
         self.strobe = value & 1 == 1;
         if self.strobe {
-            self.buttons = Box::from(Buttons::all().iter());
+            self.buffer = self.report();
+        }
+    }
+
+    fn read(&mut self, _ppu: &Ppu) -> u8 {
+        if self.strobe {
+            self.buffer = self.report();
+            return self.buffer as u8 & 1;
+        }
+
+        let bit = self.buffer as u8 & 1;
+        self.buffer = self.buffer >> 1 | 0x8000_0000;
+        bit
+    }
+
+    fn read_debugging(&self, _ppu: &Ppu) -> u8 {
+        if self.strobe {
+            self.state.bits() & 1
         } else {
-            self.buffer = self.next();
+            self.buffer as u8 & 1
         }
     }
 
-    pub fn read(&mut self) -> u8 {
-        let current_state = self.buffer;
-        if !self.strobe {
-            if let Some(button) = self.buttons.next() {
-                self.buffer = if button.bits() & self.state.bits() != 0 {
-                    1
-                } else {
-                    0
-                };
-            }   
+    /* Controller 1 is player 3 or 4 on a Four Score */
+    fn set_buttons(&mut self, controller: usize, buttons: Buttons) {
+        match (controller, self.four_score.as_mut()) {
+            (0, _) => self.state = buttons,
+            (1, Some(adapter)) => adapter.state = buttons,
+            _ => {},
         }
-        current_state
     }
 
-    pub fn press(&mut self, button: Buttons) {
-        self.state.insert(button);
+    fn buttons(&self, controller: usize) -> Buttons {
+        match (controller, self.four_score) {
+            (0, _) => self.state,
+            (1, Some(adapter)) => adapter.state,
+            _ => Buttons::empty(),
+        }
     }
 
-    pub fn release(&mut self, button: Buttons) {
-        self.state.remove(button);
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.strobe);
+        writer.u8(self.state.bits());
+        writer.u32(self.buffer);
+        writer.bool(self.four_score.is_some());
+        if let Some(adapter) = self.four_score {
+            writer.u8(adapter.state.bits());
+        }
     }
 
-    pub fn next(&mut self) -> u8 {
-        if let Some(button) = self.buttons.next() {
-            button.bits() & self.state.bits()
-        } else {
-            1
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.strobe = reader.bool()?;
+        self.state = Buttons::from_bits_truncate(reader.u8()?);
+        self.buffer = reader.u32()?;
+        match (reader.bool()?, self.four_score.as_mut()) {
+            (true, Some(adapter)) => adapter.state = Buttons::from_bits_truncate(reader.u8()?),
+            (false, None) => {},
+            (true, None) => return Err("Save state was made with a Four Score, none is plugged in".to_string()),
+            (false, Some(_)) => return Err("Save state was made without a Four Score, one is plugged in".to_string()),
         }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    fn clone_box(&self) -> Box<dyn InputDevice> {
+        Box::new(self.clone())
+    }
+}
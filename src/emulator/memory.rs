@@ -1,4 +1,4 @@
-use crate::emulator::cpu::Cpu;
+use crate::emulator::cpu::{ Cpu, MemoryAccess };
 use crate::emulator::bus::Bus;
 
 pub const RAM: u16 = 0x0000;
@@ -21,6 +21,11 @@ pub const PPU_DATA: u16 = 0x2007;
 pub const OAM_DMA: u16 = 0x4014;
 
 pub const JOYPAD_1: u16 = 0x4016;
+pub const JOYPAD_2: u16 = 0x4017;
+
+/* Controller reads only drive the low bits, the rest keep the $40 left on the bus by the address */
+pub const JOYPAD_OPEN_BUS: u8 = 0x40;
+pub const MICROPHONE: u8 = 0x04;
 
 pub trait Mem {
     fn mem_read(&mut self, addr: u16) -> u8;
@@ -46,7 +51,14 @@ impl Mem for Bus {
             RAM ..= RAM_MIRRORS_END => {
                 self.cpu_vram[(addr & 0b111_11111111) as usize]
             },
-            JOYPAD_1 => self.joypad.read(),
+            JOYPAD_1 => {
+                let microphone = if self.microphone { MICROPHONE } else { 0 };
+                self.port_1.read(&self.ppu) | microphone | JOYPAD_OPEN_BUS
+            },
+            JOYPAD_2 => {
+                let expansion = self.expansion.as_mut().map_or(0, |device| device.read(&self.ppu));
+                self.port_2.read(&self.ppu) | expansion | JOYPAD_OPEN_BUS
+            },
             
             PPU_STATUS => self.ppu.read_status(),
             PPU_OAM_DATA => self.ppu.read_oam_data(),
@@ -67,7 +79,13 @@ impl Mem for Bus {
             RAM ..= RAM_MIRRORS_END => {
                 self.cpu_vram[(addr & 0b111_11111111) as usize] = data;
             },
-            JOYPAD_1 => self.joypad.write(data),
+            JOYPAD_1 => { // Both ports share the strobe line, the expansion port gets all 3 output bits
+                self.port_1.write(data);
+                self.port_2.write(data);
+                if let Some(device) = self.expansion.as_mut() {
+                    device.write(data);
+                }
+            },
 
             PPU_CONTROLLER => self.ppu.write_controller(data),
             PPU_MASK => self.ppu.write_mask(data),
@@ -84,8 +102,8 @@ impl Mem for Bus {
             OAM_DMA => {
                 let mut buffer = [0u8; 256];
                 let hi: u16 = (data as u16) << 8;
-                for i in 0..256 {
-                    buffer[i] = self.mem_read(hi + i as u16);
+                for (i, byte) in buffer.iter_mut().enumerate() {
+                    *byte = self.mem_read(hi + i as u16);
                 }
 
                 self.ppu.write_oam_dma(&buffer);
@@ -101,20 +119,22 @@ impl Mem for Bus {
 
 impl Mem for Cpu {
     fn mem_read(&mut self, addr: u16) -> u8 {
-        self.bus.mem_read(addr)
+        let value = self.bus.mem_read(addr);
+        if let Some(log) = self.access_log.as_mut() {
+            log.push(MemoryAccess { address: addr, value, write: false });
+        }
+        if let Some(log) = self.prg_log.as_mut() {
+            log.read(addr);
+        }
+        value
     }
 
     fn mem_write(&mut self, addr: u16, data: u8) {
+        if let Some(log) = self.access_log.as_mut() {
+            log.push(MemoryAccess { address: addr, value: data, write: true });
+        }
         self.bus.mem_write(addr, data)
     }
-
-    fn mem_read_u16(&mut self, pos: u16) -> u16 {
-        self.bus.mem_read_u16(pos)
-    }
-
-    fn mem_write_u16(&mut self, pos: u16, data: u16) {
-        self.bus.mem_write_u16(pos, data)
-    }
 }
 
 
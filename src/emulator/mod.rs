@@ -6,5 +6,19 @@ pub mod rom;
 pub mod instructions;
 pub mod addressing_modes;
 pub mod ppu;
+pub mod render;
+pub mod palette;
+pub mod frame;
 pub mod interrupts;
-pub mod joypad;
\ No newline at end of file
+pub mod joypad;
+pub mod zapper;
+pub mod vaus;
+pub mod power_pad;
+pub mod family_keyboard;
+pub mod input_device;
+pub mod region;
+pub mod nes;
+pub mod savestate;
+pub mod rewind;
+pub mod movie;
+pub mod cdl;
\ No newline at end of file
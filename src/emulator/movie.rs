@@ -0,0 +1,354 @@
+use crate::emulator::nes::Nes;
+use crate::emulator::joypad::Buttons;
+use crate::emulator::region::Region;
+use crate::emulator::rom::Rom;
+use crate::emulator::savestate;
+use crate::helpers::checksum::md5;
+use std::fs;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+/*
+    https://fceux.com/web/help/fm2.html
+    Movies recorded from power on are portable. The savestate field of a movie
+    that starts from a state holds one of our own save states, so FCEUX can not
+    play those and we can not play ones that start from an FCEUX state.
+ */
+pub const SOFT_RESET: u8 = 0b0000_0001;
+pub const HARD_RESET: u8 = 0b0000_0010;
+
+const STATE_COMMENT: &str = "savestate is not an FCEUX state, only this emulator can play this movie";
+const PORTS: usize = 4; // Players 3 and 4 are only used with a Four Score
+const GAMEPAD: &str = "1"; // SI_GAMEPAD
+const BUTTON_ORDER: [(char, Buttons); 8] = [
+    ('R', Buttons::Right), ('L', Buttons::Left), ('D', Buttons::Down), ('U', Buttons::Up),
+    ('T', Buttons::Start), ('S', Buttons::Select), ('B', Buttons::B), ('A', Buttons::A),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MovieFrame {
+    pub commands: u8,
+    pub ports: [Buttons; PORTS],
+}
+
+impl MovieFrame {
+    /* Commands run first so the reset happens at the start of the frame */
+    pub fn apply(&self, nes: &mut Nes) {
+        run_commands(nes, self.commands);
+        for (port, buttons) in self.ports.iter().enumerate() {
+            nes.set_buttons(port, *buttons);
+        }
+    }
+}
+
+pub fn run_commands(nes: &mut Nes, commands: u8) {
+    if commands & HARD_RESET != 0 {
+        nes.power_cycle();
+    } else if commands & SOFT_RESET != 0 {
+        nes.reset();
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Movie {
+    pub rom_filename: String,
+    pub rom_checksum: Option<[u8; 16]>,
+    pub guid: String,
+    pub pal: bool,
+    pub four_score: bool,
+    pub rerecord_count: usize,
+    pub comments: Vec<String>,
+    pub start_state: Option<Vec<u8>>, // None means the movie starts at power on
+    pub frames: Vec<MovieFrame>,
+}
+
+/* FCEUX identifies a ROM by the MD5 of its PRG and CHR data */
+pub fn rom_checksum(rom: &Rom) -> [u8; 16] {
+    let mut data = rom.prg_rom.clone();
+    data.extend_from_slice(&rom.chr_rom);
+    md5(&data)
+}
+
+impl Movie {
+    /*
+        Starts a recording from the current state, or power cycles the console
+        first so the movie starts at power on like FCEUX does by default
+     */
+    pub fn record(nes: &mut Nes, rom_filename: &str, from_power_on: bool) -> Movie {
+        let start_state = if from_power_on {
+            nes.power_cycle();
+            None
+        } else {
+            Some(nes.save_state())
+        };
+
+        Movie {
+            rom_filename: rom_filename.to_string(),
+            rom_checksum: Some(rom_checksum(nes.rom())),
+            guid: new_guid(),
+            pal: nes.region() == Region::Pal,
+            four_score: nes.four_score(),
+            start_state,
+            ..Default::default()
+        }
+    }
+
+    /* Records the buttons currently held along with the commands run this frame */
+    pub fn push(&mut self, nes: &Nes, commands: u8) {
+        self.frames.push(MovieFrame {
+            commands,
+            ports: std::array::from_fn(|port| nes.buttons(port)),
+        });
+    }
+
+    pub fn parse(text: &str) -> Result<Movie, String> {
+        let mut movie = Movie::default();
+        let mut ports = [GAMEPAD.to_string(), GAMEPAD.to_string()];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.starts_with('|') {
+                let kinds = if movie.four_score { vec![GAMEPAD; PORTS] } else { vec![ports[0].as_str(), ports[1].as_str()] };
+                let frame = parse_frame(line, &kinds).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                movie.frames.push(frame);
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "romFilename" => movie.rom_filename = value.to_string(),
+                "romChecksum" => {
+                    let checksum = parse_binary(value).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    movie.rom_checksum = Some(checksum.try_into()
+                        .map_err(|_| format!("line {}: romChecksum is not an MD5", i + 1))?);
+                },
+                "guid" => movie.guid = value.to_string(),
+                "palFlag" => movie.pal = value == "1",
+                "rerecordCount" => movie.rerecord_count = value.parse().unwrap_or(0),
+                "comment" if value == STATE_COMMENT => {},
+                "comment" => movie.comments.push(value.to_string()),
+                "savestate" => {
+                    let state = parse_binary(value).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    if !state.starts_with(&savestate::MAGIC) {
+                        return Err(format!("line {}: savestate is not from this emulator, only movies recorded from power on can be played", i + 1));
+                    }
+                    movie.start_state = Some(state);
+                },
+                "port0" => ports[0] = value.to_string(),
+                "port1" => ports[1] = value.to_string(),
+                "fourscore" => movie.four_score = value == "1",
+                _ => {},
+            }
+        }
+
+        Ok(movie)
+    }
+
+    pub fn load(path: &str) -> Result<Movie, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read movie {}: {}", path, e))?;
+        Movie::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn to_fm2(&self) -> String {
+        let mut lines = vec![
+            "version 3".to_string(),
+            "emuVersion 22020".to_string(),
+            format!("rerecordCount {}", self.rerecord_count),
+            format!("palFlag {}", self.pal as u8),
+            format!("romFilename {}", self.rom_filename),
+        ];
+        if let Some(checksum) = &self.rom_checksum {
+            lines.push(format!("romChecksum base64:{}", base64_encode(checksum)));
+        }
+        lines.push(format!("guid {}", self.guid));
+        lines.push(format!("fourscore {}", self.four_score as u8));
+        lines.extend([
+            "microphone 0", "port0 1", "port1 1", "port2 0", "FDS 0", "NewPPU 0",
+        ].iter().map(|line| line.to_string()));
+        lines.extend(self.comments.iter().map(|comment| format!("comment {}", comment)));
+        if let Some(state) = &self.start_state {
+            lines.push(format!("comment {}", STATE_COMMENT));
+            lines.push(format!("savestate base64:{}", base64_encode(state)));
+        }
+
+        let ports = if self.four_score { PORTS } else { 2 };
+        for frame in self.frames.iter() {
+            let buttons: Vec<String> = frame.ports[..ports].iter().map(|buttons| format_buttons(*buttons)).collect();
+            lines.push(format!("|{}|{}||", frame.commands, buttons.join("|")));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_fm2()).map_err(|e| format!("Could not write movie {}: {}", path, e))
+    }
+}
+
+pub struct MovieRecording {
+    pub movie: Movie,
+    start_frame: usize,
+}
+
+impl MovieRecording {
+    pub fn start(nes: &mut Nes, rom_filename: &str, from_power_on: bool) -> MovieRecording {
+        let movie = Movie::record(nes, rom_filename, from_power_on);
+        MovieRecording { movie, start_frame: nes.frame_count() }
+    }
+
+    /* Call before running each frame, after `commands` were run */
+    pub fn record_frame(&mut self, nes: &Nes, commands: u8) {
+        self.movie.push(nes, commands);
+    }
+
+    /* After loading a state or rewinding, drops the input past the new position */
+    pub fn rerecord(&mut self, nes: &Nes) {
+        let position = nes.frame_count().saturating_sub(self.start_frame);
+        self.movie.frames.truncate(position);
+        self.movie.rerecord_count += 1;
+    }
+}
+
+pub struct MoviePlayback {
+    pub movie: Movie,
+    position: usize,
+    start_frame: usize,
+}
+
+impl MoviePlayback {
+    /* Puts the console where the movie starts */
+    pub fn start(movie: Movie, nes: &mut Nes) -> Result<MoviePlayback, String> {
+        if movie.rom_checksum.is_some_and(|checksum| checksum != rom_checksum(nes.rom())) {
+            return Err("Movie was recorded with a different ROM".to_string());
+        }
+
+        nes.set_region(if movie.pal { Region::Pal } else { Region::Ntsc });
+        nes.set_four_score(movie.four_score);
+        match &movie.start_state {
+            Some(state) => nes.load_state(state)?,
+            None => nes.power_cycle(),
+        }
+
+        Ok(MoviePlayback { movie, position: 0, start_frame: nes.frame_count() })
+    }
+
+    /* Applies the next frame of input, None once the movie has ended */
+    pub fn next_frame(&mut self, nes: &mut Nes) -> Option<MovieFrame> {
+        let frame = *self.movie.frames.get(self.position)?;
+        frame.apply(nes);
+        self.position += 1;
+        Some(frame)
+    }
+
+    /* After loading a state or rewinding, continues with the input for the new position */
+    pub fn seek(&mut self, nes: &Nes) {
+        self.position = nes.frame_count().saturating_sub(self.start_frame);
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.movie.frames.len()
+    }
+}
+
+/* With a Four Score there are four gamepad fields before port2 instead of port0 and port1 */
+fn parse_frame(line: &str, ports: &[&str]) -> Result<MovieFrame, String> {
+    let fields: Vec<&str> = line.split('|').collect();
+    if fields.len() < 3 {
+        return Err("expected |commands|port0|port1|port2|".to_string());
+    }
+
+    let mut frame = MovieFrame {
+        commands: fields[1].trim().parse::<u8>().map_err(|_| format!("invalid commands '{}'", fields[1]))?,
+        ..Default::default()
+    };
+    for (port, kind) in ports.iter().enumerate() {
+        let field = fields.get(port + 2).copied().unwrap_or("");
+        if *kind == GAMEPAD {
+            frame.ports[port] = parse_buttons(field)?;
+        }
+    }
+
+    Ok(frame)
+}
+
+/* Anything but '.' or ' ' counts as pressed */
+fn parse_buttons(field: &str) -> Result<Buttons, String> {
+    if field.is_empty() {
+        return Ok(Buttons::empty());
+    }
+    if field.chars().count() != BUTTON_ORDER.len() {
+        return Err(format!("expected 8 buttons in '{}'", field));
+    }
+
+    Ok(field.chars()
+        .zip(BUTTON_ORDER.iter())
+        .filter(|(c, _)| *c != '.' && *c != ' ')
+        .fold(Buttons::empty(), |buttons, (_, (_, button))| buttons | *button))
+}
+
+fn format_buttons(buttons: Buttons) -> String {
+    BUTTON_ORDER.iter()
+        .map(|(name, button)| if buttons.contains(*button) { *name } else { '.' })
+        .collect()
+}
+
+/* FM2 binary fields are either base64:... or 0x... hex */
+fn parse_binary(value: &str) -> Result<Vec<u8>, String> {
+    if let Some(data) = value.strip_prefix("base64:") {
+        return base64_decode(data).ok_or(format!("invalid base64 '{}'", data));
+    }
+
+    let hex = value.strip_prefix("0x").ok_or(format!("invalid binary value '{}'", value))?;
+    if hex.len() % 2 != 0 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(format!("invalid hex '{}'", hex));
+    }
+    (0..hex.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid hex '{}'", hex)))
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(value >> (18 - 6 * i)) as usize & 0x3F] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let (mut value, mut bits) = (0u32, 0);
+
+    for c in text.trim_end_matches('=').bytes() {
+        value = (value << 6 | BASE64.iter().position(|&b| b == c)? as u32) & 0xFFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((value >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+/* Only has to be unique per recording, not cryptographically random */
+fn new_guid() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos());
+    let hash = md5(&nanos.to_le_bytes());
+    let hex: String = hash.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
@@ -0,0 +1,242 @@
+use crate::emulator::cpu::Cpu;
+use crate::emulator::bus::Bus;
+use crate::emulator::rom::Rom;
+use crate::emulator::region::Region;
+use crate::emulator::joypad::Buttons;
+use crate::emulator::input_device::InputDevice;
+use crate::emulator::interrupts::Interrupt;
+use crate::emulator::savestate::{ StateWriter, StateReader };
+use crate::emulator::cdl::{ CodeDataLog, PrgLog };
+use crate::emulator::frame::Frame;
+
+/*
+    Whole console behind one type so every frontend drives the same main loop:
+
+    let mut nes = Nes::new(Rom::load("game.nes")?);
+    loop {
+        nes.set_buttons(0, buttons);
+        nes.run_frame();
+        draw(nes.framebuffer());
+    }
+ */
+pub struct Nes {
+    pub cpu: Cpu,
+    rom: Rom,
+    frame: Frame,
+    frame_count: usize,
+    audio_frames: usize, // frame_count when audio_samples was last called
+    audio_remainder: f64, // Fraction of a sample owed from then
+}
+
+impl Nes {
+    pub const SAMPLE_RATE: u32 = 44_100;
+
+    pub fn new(rom: Rom) -> Self {
+        let mut cpu = Cpu::new(Bus::new(rom.clone()));
+        cpu.reset();
+
+        Nes {
+            cpu,
+            rom,
+            frame: Frame::new(),
+            frame_count: 0,
+            audio_frames: 0,
+            audio_remainder: 0.0,
+        }
+    }
+
+    pub fn load(rom_path: &str) -> Result<Self, String> {
+        Ok(Nes::new(Rom::load(rom_path)?))
+    }
+
+    /* Swaps the cartridge and powers the console back on, the region is kept */
+    pub fn load_rom(&mut self, rom: Rom) {
+        self.rom = rom;
+        self.stop_code_data_log(); // Logs belong to one ROM
+        self.power_cycle();
+    }
+
+    pub fn rom(&self) -> &Rom {
+        &self.rom
+    }
+
+    pub fn region(&self) -> Region {
+        self.cpu.bus.ppu.region
+    }
+
+    pub fn set_region(&mut self, region: Region) {
+        self.cpu.bus.set_region(region);
+    }
+
+    /* Reset button, RAM and PPU memory survive */
+    pub fn reset(&mut self) {
+        self.cpu.interrupt = None;
+        self.cpu.bus.ppu.interrupt = None;
+        self.cpu.reset();
+    }
+
+    /* Power switch, everything but the cartridge, region and peripherals starts over */
+    pub fn power_cycle(&mut self) {
+        let region = self.region();
+        let mut bus = Bus::new(self.rom.clone());
+        bus.set_region(region);
+        bus.plug_in_from(&self.cpu.bus);
+
+        let mut cpu = Cpu::new(bus);
+        self.keep_code_data_log(&mut cpu);
+        self.cpu = cpu;
+        self.cpu.reset();
+        self.frame = Frame::new();
+    }
+
+    /*
+        Ports 0 and 1 are the controllers at $4016 and $4017, 2 and 3 are
+        players 3 and 4 on a Four Score, others are ignored
+     */
+    pub fn set_buttons(&mut self, port: usize, buttons: Buttons) {
+        self.cpu.bus.set_buttons(port, buttons);
+    }
+
+    pub fn buttons(&self, port: usize) -> Buttons {
+        self.cpu.bus.buttons(port)
+    }
+
+    pub fn set_four_score(&mut self, connected: bool) {
+        self.cpu.bus.set_four_score(connected);
+    }
+
+    pub fn four_score(&self) -> bool {
+        self.cpu.bus.four_score()
+    }
+
+    /* Famicom controller 2 microphone, high while something loud is picked up */
+    pub fn set_microphone(&mut self, level: bool) {
+        self.cpu.bus.microphone = level;
+    }
+
+    /* Replaces whatever is in the port (0 or 1, 2 for the expansion port), see InputDevice */
+    pub fn plug_in(&mut self, port: usize, device: Box<dyn InputDevice>) {
+        self.cpu.bus.plug_in(port, device);
+    }
+
+    pub fn device<T: InputDevice>(&self, port: usize) -> Option<&T> {
+        self.cpu.bus.device(port)
+    }
+
+    pub fn device_mut<T: InputDevice>(&mut self, port: usize) -> Option<&mut T> {
+        self.cpu.bus.device_mut(port)
+    }
+
+    /* Runs a single instruction, returns true once it completed a frame */
+    pub fn step(&mut self) -> bool {
+        self.enter_nmi();
+        self.execute_instruction()
+    }
+
+    /*
+        First half of a step: takes the finished frame and jumps to the NMI
+        handler if the PPU asked for it, returns true if it did. Debuggers call
+        it on its own to stop on the first instruction of the handler, calling
+        it again before the instruction runs does not enter the handler twice.
+     */
+    pub fn enter_nmi(&mut self) -> bool {
+        if self.cpu.ppu_ready().is_some() {
+            self.frame.pixels.copy_from_slice(&self.cpu.bus.ppu.output);
+        }
+
+        if self.cpu.bus.ppu.interrupt.is_some() {
+            self.cpu.interrupt(Interrupt::new_nmi());
+            return true;
+        }
+        false
+    }
+
+    /* Second half of a step, returns true once it completed a frame */
+    pub fn execute_instruction(&mut self) -> bool {
+        self.cpu.step();
+
+        if self.cpu.bus.ppu.frame_ready() {
+            self.frame_count += 1;
+            return true;
+        }
+
+        false
+    }
+
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut writer = StateWriter::new(&self.rom);
+        self.cpu.save_state(&mut writer);
+        writer.usize(self.frame_count);
+        writer.data
+    }
+
+    /* Nothing changes unless the whole state loads */
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), String> {
+        let mut reader = StateReader::new(data, &self.rom)?;
+        let mut cpu = Cpu::new(Bus::new(self.rom.clone()));
+        cpu.bus.plug_in_from(&self.cpu.bus); // Devices stay, only their serial state is loaded
+        cpu.load_state(&mut reader)?;
+        let frame_count = reader.usize()?;
+        reader.finish()?;
+
+        self.keep_code_data_log(&mut cpu);
+        self.cpu = cpu;
+        self.frame_count = frame_count;
+        Ok(())
+    }
+
+    pub fn run_frame(&mut self) {
+        while !self.step() {}
+    }
+
+    /* Palette indices with emphasis bits, see Frame::apply_palette for RGB */
+    pub fn framebuffer(&self) -> &Frame {
+        &self.frame
+    }
+
+    /*
+        Mono samples at SAMPLE_RATE for the frames run since the last call.
+        There is no APU yet so they are silent, but they last as long as the frames.
+     */
+    pub fn audio_samples(&mut self) -> Vec<f32> {
+        let frames = self.frame_count.saturating_sub(self.audio_frames);
+        self.audio_frames = self.frame_count;
+
+        let samples = frames as f64 * Nes::SAMPLE_RATE as f64 / self.region().frame_rate() + self.audio_remainder;
+        self.audio_remainder = samples.fract();
+        vec![0.0; samples as usize]
+    }
+
+    /*
+        Marks the PRG and CHR bytes the game uses from now on, on top of the
+        flags already in `log`. Savestates and power cycles keep logging.
+     */
+    pub fn start_code_data_log(&mut self, log: CodeDataLog) {
+        self.cpu.prg_log = Some(PrgLog::new(log.prg));
+        self.cpu.bus.ppu.chr_log = Some(log.chr);
+    }
+
+    pub fn code_data_log(&self) -> Option<CodeDataLog> {
+        Some(CodeDataLog {
+            prg: self.cpu.prg_log.as_ref()?.flags.clone(),
+            chr: self.cpu.bus.ppu.chr_log.clone()?,
+        })
+    }
+
+    pub fn stop_code_data_log(&mut self) -> Option<CodeDataLog> {
+        let log = self.code_data_log();
+        self.cpu.prg_log = None;
+        self.cpu.bus.ppu.chr_log = None;
+        log
+    }
+
+    fn keep_code_data_log(&mut self, cpu: &mut Cpu) {
+        cpu.prg_log = self.cpu.prg_log.take();
+        cpu.bus.ppu.chr_log = self.cpu.bus.ppu.chr_log.take();
+    }
+
+    /* Frames run since the Nes was created, resets do not clear it */
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+}
@@ -0,0 +1,241 @@
+use crate::emulator::ppu::{ Ppu, Mask };
+use std::fs::File;
+use std::io::Read;
+
+const BG_PALETTE_START: usize = 0x03c0; // Update when scroll is implemented
+const META_TILE_WIDTH: usize = 4;
+const TILE_HEIGHT: usize = 2;
+const NAME_TABLE_WIDTH: usize = 32;
+const META_TILE_HEIGHT: usize = NAME_TABLE_WIDTH / META_TILE_WIDTH;
+
+const SPRITE_PALETTE_START: usize = 0x11;
+
+const PALETTE_LENGTH: usize = 4;
+
+const COLORS: usize = 64;
+const EMPHASIS_VARIANTS: usize = 8;
+const PAL_FILE_LEN: usize = COLORS * 3;
+const PAL_FILE_EMPHASIS_LEN: usize = COLORS * EMPHASIS_VARIANTS * 3;
+
+/* https://www.nesdev.org/wiki/NTSC_video#Emulating_in_C++_code */
+const SIGNAL_LEVELS: [f64; 8] = [
+    0.350, 0.518, 0.962, 1.550, // Signal low
+    1.094, 1.506, 1.962, 1.962, // Signal high
+];
+const SIGNAL_BLACK: f64 = 0.518;
+const SIGNAL_WHITE: f64 = 1.962;
+const EMPHASIS_ATTENUATION: f64 = 0.746;
+
+#[rustfmt::skip]
+pub static DEFAULT_PALETTE: [(u8,u8,u8); 64] = [
+   (0x80, 0x80, 0x80), (0x00, 0x3D, 0xA6), (0x00, 0x12, 0xB0), (0x44, 0x00, 0x96), (0xA1, 0x00, 0x5E),
+   (0xC7, 0x00, 0x28), (0xBA, 0x06, 0x00), (0x8C, 0x17, 0x00), (0x5C, 0x2F, 0x00), (0x10, 0x45, 0x00),
+   (0x05, 0x4A, 0x00), (0x00, 0x47, 0x2E), (0x00, 0x41, 0x66), (0x00, 0x00, 0x00), (0x05, 0x05, 0x05),
+   (0x05, 0x05, 0x05), (0xC7, 0xC7, 0xC7), (0x00, 0x77, 0xFF), (0x21, 0x55, 0xFF), (0x82, 0x37, 0xFA),
+   (0xEB, 0x2F, 0xB5), (0xFF, 0x29, 0x50), (0xFF, 0x22, 0x00), (0xD6, 0x32, 0x00), (0xC4, 0x62, 0x00),
+   (0x35, 0x80, 0x00), (0x05, 0x8F, 0x00), (0x00, 0x8A, 0x55), (0x00, 0x99, 0xCC), (0x21, 0x21, 0x21),
+   (0x09, 0x09, 0x09), (0x09, 0x09, 0x09), (0xFF, 0xFF, 0xFF), (0x0F, 0xD7, 0xFF), (0x69, 0xA2, 0xFF),
+   (0xD4, 0x80, 0xFF), (0xFF, 0x45, 0xF3), (0xFF, 0x61, 0x8B), (0xFF, 0x88, 0x33), (0xFF, 0x9C, 0x12),
+   (0xFA, 0xBC, 0x20), (0x9F, 0xE3, 0x0E), (0x2B, 0xF0, 0x35), (0x0C, 0xF0, 0xA4), (0x05, 0xFB, 0xFF),
+   (0x5E, 0x5E, 0x5E), (0x0D, 0x0D, 0x0D), (0x0D, 0x0D, 0x0D), (0xFF, 0xFF, 0xFF), (0xA6, 0xFC, 0xFF),
+   (0xB3, 0xEC, 0xFF), (0xDA, 0xAB, 0xEB), (0xFF, 0xA8, 0xF9), (0xFF, 0xAB, 0xB3), (0xFF, 0xD2, 0xB0),
+   (0xFF, 0xEF, 0xA6), (0xFF, 0xF7, 0x9C), (0xD7, 0xE8, 0x95), (0xA6, 0xED, 0xAF), (0xA2, 0xF2, 0xDA),
+   (0x99, 0xFF, 0xFC), (0xDD, 0xDD, 0xDD), (0x11, 0x11, 0x11), (0x11, 0x11, 0x11)
+];
+
+pub type Rgb = (u8, u8, u8);
+
+/* https://www.nesdev.org/wiki/NTSC_video */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NtscParams {
+    pub hue: f64, // Degrees
+    pub saturation: f64,
+    pub contrast: f64,
+    pub brightness: f64,
+    pub gamma: f64,
+}
+
+impl Default for NtscParams {
+    fn default() -> Self {
+        NtscParams {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            brightness: 0.0,
+            gamma: 2.2,
+        }
+    }
+}
+
+/*
+    64 colours for each of the 8 combinations of the emphasis bits in PPUMASK,
+    indexed by emphasis * 64 + colour like a 1536 byte .pal file
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: Vec<Rgb>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::from_colors(&DEFAULT_PALETTE)
+    }
+}
+
+impl Palette {
+    /* Emphasis variants are approximated by dimming the channels that aren't emphasised */
+    pub fn from_colors(colors: &[Rgb; COLORS]) -> Self {
+        let mut palette = Vec::with_capacity(COLORS * EMPHASIS_VARIANTS);
+        for emphasis in 0..EMPHASIS_VARIANTS {
+            for (i, &(r, g, b)) in colors.iter().enumerate() {
+                if i & 0x0F >= 0x0E || emphasis == 0 {
+                    palette.push((r, g, b));
+                    continue;
+                }
+
+                let dim = |value: u8, emphasised: bool| if emphasised {
+                    value
+                } else {
+                    (value as f64 * EMPHASIS_ATTENUATION).round() as u8
+                };
+                let all = emphasis == EMPHASIS_VARIANTS - 1; // All three bits dim every channel
+                palette.push((
+                    dim(r, emphasis & 0b001 != 0 && !all),
+                    dim(g, emphasis & 0b010 != 0 && !all),
+                    dim(b, emphasis & 0b100 != 0 && !all),
+                ));
+            }
+        }
+
+        Palette { colors: palette }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Palette, String> {
+        let colors: Vec<Rgb> = bytes.chunks_exact(3)
+            .map(|rgb| (rgb[0], rgb[1], rgb[2]))
+            .collect();
+
+        match bytes.len() {
+            PAL_FILE_LEN => {
+                let mut base = [(0, 0, 0); COLORS];
+                base.copy_from_slice(&colors);
+                Ok(Palette::from_colors(&base))
+            },
+            PAL_FILE_EMPHASIS_LEN => Ok(Palette { colors }),
+            len => Err(format!(
+                "Invalid palette size: {} bytes (expected {} or {})", 
+                len, PAL_FILE_LEN, PAL_FILE_EMPHASIS_LEN
+            )),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Palette, String> {
+        let mut file = File::open(path)
+            .map_err(|e| format!("Could not open palette {}: {}", path, e))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|e| format!("Could not read palette {}: {}", path, e))?;
+        Palette::from_bytes(&bytes)
+    }
+
+    /* https://www.nesdev.org/wiki/NTSC_video#Emulating_in_C++_code */
+    pub fn generate(params: &NtscParams) -> Self {
+        let mut colors = Vec::with_capacity(COLORS * EMPHASIS_VARIANTS);
+        for pixel in 0..(COLORS * EMPHASIS_VARIANTS) as u16 {
+            let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+            for phase in 0..12 {
+                let level = (composite_signal(pixel, phase) - SIGNAL_BLACK) / (SIGNAL_WHITE - SIGNAL_BLACK);
+                let angle = std::f64::consts::PI * (phase as f64 + 4.0) / 6.0 + params.hue.to_radians();
+
+                y += level;
+                i += level * angle.cos();
+                q += level * angle.sin();
+            }
+
+            y = y / 12.0 * params.contrast + params.brightness;
+            i = i / 12.0 * params.saturation;
+            q = q / 12.0 * params.saturation;
+
+            colors.push((
+                gamma_correct(y + 0.946882 * i + 0.623557 * q, params.gamma),
+                gamma_correct(y - 0.274788 * i - 0.635691 * q, params.gamma),
+                gamma_correct(y - 1.108545 * i + 1.709007 * q, params.gamma),
+            ));
+        }
+
+        Palette { colors }
+    }
+
+    pub fn color(&self, index: u8, emphasis: u8) -> Rgb {
+        self.colors[(emphasis as usize & 0b111) * COLORS + (index as usize & 0x3F)]
+    }
+
+    pub fn pixel(&self, pixel: u16) -> Rgb {
+        self.colors[pixel as usize & 0x1FF]
+    }
+}
+
+/* Composite voltage of a 9 bit pixel at one of the 12 colour carrier phases */
+pub fn composite_signal(pixel: u16, phase: usize) -> f64 {
+    let color = (pixel & 0x0F) as usize;
+    let level = if color < 0x0E { (pixel >> 4) as usize & 0b11 } else { 1 };
+
+    let low = SIGNAL_LEVELS[level + 4 * (color == 0x00) as usize];
+    let high = SIGNAL_LEVELS[level + 4 * (color < 0x0D) as usize];
+
+    let in_color_phase = |color: usize| (color + phase) % 12 < 6;
+    let mut signal = if in_color_phase(color) { high } else { low };
+
+    if (pixel & 0x040 != 0 && in_color_phase(0))
+        || (pixel & 0x080 != 0 && in_color_phase(4))
+        || (pixel & 0x100 != 0 && in_color_phase(8)) {
+        signal *= EMPHASIS_ATTENUATION;
+    }
+
+    signal
+}
+
+fn gamma_correct(value: f64, gamma: f64) -> u8 {
+    let corrected = if value <= 0.0 { 0.0 } else { value.powf(2.2 / gamma) };
+    (corrected * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+pub fn emphasis(ppu: &Ppu) -> u8 {
+    (ppu.mask.bits() & (Mask::EMPHASIZE_RED | Mask::EMPHASIZE_GREEN | Mask::EMPHASIZE_BLUE).bits()) >> 5
+}
+
+/* Colour index and emphasis combined the way the PPU outputs them (EEEPPPPPP) */
+fn pixel(ppu: &Ppu, index: u8) -> u16 {
+    let index = if ppu.mask.contains(Mask::GREYSCALE) { index & 0x30 } else { index & 0x3F };
+    (emphasis(ppu) as u16) << 6 | index as u16
+}
+
+pub fn palette_bg(ppu: &Ppu, x: usize, y: usize) -> [u16; 4] {
+    let attr_table_idx = x / META_TILE_WIDTH + y / META_TILE_WIDTH * META_TILE_HEIGHT;
+    let attr_byte = ppu.vram[BG_PALETTE_START + attr_table_idx];
+ 
+    let pallete_idx = match (x % META_TILE_WIDTH / TILE_HEIGHT == 1, y % META_TILE_WIDTH / TILE_HEIGHT == 1) {
+        (false, false) => attr_byte & 0b11,
+        (true, false) => (attr_byte >> 2) & 0b11,
+        (false, true) => (attr_byte >> 4) & 0b11,
+        (true, true) => (attr_byte >> 6) & 0b11,
+    };
+ 
+    let pallete_start: usize = 1 + (pallete_idx as usize) * PALETTE_LENGTH;
+    [
+        pixel(ppu, ppu.palette_table[0]), 
+        pixel(ppu, ppu.palette_table[pallete_start]),
+        pixel(ppu, ppu.palette_table[pallete_start + 1]), 
+        pixel(ppu, ppu.palette_table[pallete_start + 2]),
+    ]
+}
+
+pub fn palette_sprite(ppu: &Ppu, palette_idx: u8) -> [u16; 4] {
+    let pallete_start: usize = SPRITE_PALETTE_START + (palette_idx as usize) * PALETTE_LENGTH;
+    [
+        0,
+        pixel(ppu, ppu.palette_table[pallete_start]),
+        pixel(ppu, ppu.palette_table[pallete_start + 1]), 
+        pixel(ppu, ppu.palette_table[pallete_start + 2]),
+    ]
+}
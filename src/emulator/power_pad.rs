@@ -0,0 +1,111 @@
+use crate::emulator::ppu::Ppu;
+use crate::emulator::input_device::{ InputDevice, DeviceKind };
+use crate::emulator::savestate::{ StateWriter, StateReader };
+
+pub const BUTTONS: usize = 12;
+
+/* Order the buttons are shifted out in, the D4 line only carries 4 of them */
+const D3_ORDER: [usize; 8] = [2, 1, 5, 9, 6, 10, 11, 7];
+const D4_ORDER: [usize; 4] = [4, 3, 12, 8];
+
+const D3: u8 = 0b0000_1000;
+const D4: u8 = 0b0001_0000;
+
+/*
+    https://www.nesdev.org/wiki/Power_Pad
+    A mat with 12 buttons read through two shift registers at once, one on D3
+    and one on D4. Pressed buttons read as 1 and both lines read 1 once empty.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PowerPad {
+    pub buttons: u16, // Bit n - 1 is button n
+    pub strobe: bool,
+    pub shift_d3: u8,
+    pub shift_d4: u8,
+}
+
+impl PowerPad {
+    pub fn new() -> Self {
+        PowerPad::default()
+    }
+
+    /* Buttons are numbered 1 to 12 like on side B of the mat */
+    pub fn set_button(&mut self, button: usize, pressed: bool) {
+        if !(1..=BUTTONS).contains(&button) {
+            return;
+        }
+        if pressed {
+            self.buttons |= 1 << (button - 1);
+        } else {
+            self.buttons &= !(1 << (button - 1));
+        }
+    }
+
+    pub fn is_pressed(&self, button: usize) -> bool {
+        (1..=BUTTONS).contains(&button) && self.buttons & (1 << (button - 1)) != 0
+    }
+
+    fn latch(&mut self) {
+        self.shift_d3 = self.serialize(&D3_ORDER, 0);
+        self.shift_d4 = self.serialize(&D4_ORDER, 0xFF);
+    }
+
+    fn serialize(&self, order: &[usize], fill: u8) -> u8 {
+        order.iter().enumerate().fold(fill, |shift, (bit, button)| {
+            if self.is_pressed(*button) { shift | (1 << bit) } else { shift & !(1 << bit) }
+        })
+    }
+}
+
+impl InputDevice for PowerPad {
+    fn kind(&self) -> DeviceKind {
+        DeviceKind::PowerPad
+    }
+
+    fn write(&mut self, value: u8) {
+        self.strobe = value & 1 == 1;
+        if self.strobe {
+            self.latch();
+        }
+    }
+
+    fn read(&mut self, ppu: &Ppu) -> u8 {
+        if self.strobe {
+            self.latch();
+        }
+        let value = self.read_debugging(ppu);
+        if !self.strobe {
+            self.shift_d3 = (self.shift_d3 >> 1) | 0x80;
+            self.shift_d4 = (self.shift_d4 >> 1) | 0x80;
+        }
+        value
+    }
+
+    fn read_debugging(&self, _ppu: &Ppu) -> u8 {
+        let mut value = 0;
+        if self.shift_d3 & 1 == 1 {
+            value |= D3;
+        }
+        if self.shift_d4 & 1 == 1 {
+            value |= D4;
+        }
+        value
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.strobe);
+        writer.u8(self.shift_d3);
+        writer.u8(self.shift_d4);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.strobe = reader.bool()?;
+        self.shift_d3 = reader.u8()?;
+        self.shift_d4 = reader.u8()?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn InputDevice> {
+        Box::new(*self)
+    }
+}
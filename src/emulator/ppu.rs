@@ -1,5 +1,9 @@
 use crate::emulator::rom::Mirroring;
 use crate::emulator::interrupts::Interrupt;
+use crate::emulator::region::Region;
+use crate::emulator::cdl::ChrFlags;
+use crate::emulator::frame::Frame;
+use crate::emulator::render;
 use bitflags::bitflags;
 
 bitflags! {
@@ -88,8 +92,11 @@ pub struct Ppu {
 
     pub cycles: usize,
     pub scanline: usize,
+    pub region: Region,
 
     pub interrupt: Option<Interrupt>,
+    pub output: Vec<u16>, // Visible scanlines drawn so far, palette indices like Frame::pixels
+    pub chr_log: Option<Vec<u8>>, // The CHR half of a code/data log, see cdl::CodeDataLog
 }
 
 impl Ppu {
@@ -111,8 +118,11 @@ impl Ppu {
 
             cycles: 0,
             scanline: 0,
+            region: Region::Ntsc,
 
             interrupt: None,
+            output: vec![0; Frame::WIDTH * Frame::HEIGHT],
+            chr_log: None,
         }
     }
 
@@ -138,6 +148,9 @@ impl Ppu {
             0 ..= 0x1FFF => {
                 value = self.buffer;
                 self.buffer = self.chr_rom[addr as usize];
+                if let Some(flags) = self.chr_log.as_mut().and_then(|log| log.get_mut(addr as usize)) {
+                    *flags |= ChrFlags::READ.bits();
+                }
             },
             0x2000 ..= 0x2FFF => {
                 value = self.buffer;
@@ -257,23 +270,29 @@ impl Ppu {
     }
 
     const SCANLINE_DURATION: usize = 341;
-    const VBLANK_SET: usize = 241;
-    const SCANLINES_FRAME_SIZE: usize = 262;
 
     pub fn tick(&mut self, cycles: usize) {
         self.cycles += cycles;
         if self.cycles >= Self::SCANLINE_DURATION {
             self.cycles -= Self::SCANLINE_DURATION;
+            if self.scanline < Frame::HEIGHT {
+                let mut line = [0; Frame::WIDTH];
+                let mut chr_log = self.chr_log.take();
+                render::render_scanline(self, self.scanline, &mut line, chr_log.as_deref_mut());
+                self.chr_log = chr_log;
+                let start = self.scanline * Frame::WIDTH;
+                self.output[start..start + Frame::WIDTH].copy_from_slice(&line);
+            }
             self.scanline += 1;
 
-            if self.scanline == Self::VBLANK_SET {
+            if self.scanline == self.region.vblank_scanline() {
                 self.status.insert(Status::VBLANK_STARTED);
                 self.status.remove(Status::SPRITE_0); /* https://forums.nesdev.org/viewtopic.php?t=8832 */
                 if self.controller.contains(Controller::NMI_INTERRUPT) {
                     self.interrupt = Some(Interrupt::new_nmi());
                 }
             } 
-            if self.scanline >= Self::SCANLINES_FRAME_SIZE {
+            if self.scanline >= self.region.scanlines_per_frame() {
                 self.scanline = 0;
                 self.status.remove(Status::SPRITE_0);
                 self.status.remove(Status::VBLANK_STARTED);
@@ -288,7 +307,12 @@ impl Ppu {
             return true;
         }
 
-        return false;
+        false
+    }
+
+    /* Pixels of a visible scanline, as of the last time the beam finished it */
+    pub fn scanline_output(&self, y: usize) -> &[u16] {
+        &self.output[y * Frame::WIDTH..(y + 1) * Frame::WIDTH]
     }
 
     pub fn handled_interrupt(&mut self) {
@@ -0,0 +1,56 @@
+/* https://www.nesdev.org/wiki/Cycle_reference_chart */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
+impl Region {
+    pub fn from_name(name: &str) -> Option<Region> {
+        match name.to_lowercase().as_str() {
+            "ntsc" => Some(Region::Ntsc),
+            "pal" => Some(Region::Pal),
+            "dendy" => Some(Region::Dendy),
+            _ => None,
+        }
+    }
+
+    pub fn scanlines_per_frame(&self) -> usize {
+        match self {
+            Region::Ntsc => 262,
+            Region::Pal | Region::Dendy => 312,
+        }
+    }
+
+    pub fn vblank_scanline(&self) -> usize {
+        match self {
+            Region::Ntsc | Region::Pal => 241,
+            Region::Dendy => 291,
+        }
+    }
+
+    /* PPU dots per CPU cycle as a fraction, PAL runs 3.2 dots per cycle */
+    pub fn ppu_clock_ratio(&self) -> (usize, usize) {
+        match self {
+            Region::Ntsc | Region::Dendy => (3, 1),
+            Region::Pal => (16, 5),
+        }
+    }
+    /* CPU cycles per second, the master clock divided by 12 (NTSC), 16 (PAL) or 15 (Dendy) */
+    pub fn cpu_clock_rate(&self) -> f64 {
+        match self {
+            Region::Ntsc => 1_789_773.0,
+            Region::Pal => 1_662_607.0,
+            Region::Dendy => 1_773_448.0,
+        }
+    }
+
+    /* Frames per second, ignoring the dot NTSC skips on odd frames */
+    pub fn frame_rate(&self) -> f64 {
+        let (dots, cycles) = self.ppu_clock_ratio();
+        let cycles_per_frame = (self.scanlines_per_frame() * 341 * cycles) as f64 / dots as f64;
+        self.cpu_clock_rate() / cycles_per_frame
+    }
+}
@@ -0,0 +1,98 @@
+use crate::emulator::ppu::{ Ppu, Controller };
+use crate::emulator::cdl::ChrFlags;
+use crate::emulator::palette;
+
+const LEFT_BANK_START: usize = 0x0000;
+const RIGHT_BANK_START: usize = 0x1000;
+const TILE_LEN: usize = 16;
+const TILE_SIZE: usize = 8;
+
+/*
+    One line of the picture, the PPU draws each as the beam finishes it.
+    The first nametable and then the sprites on top, no scrolling yet.
+    The pattern rows it fetches are marked in chr_log when there is one
+ */
+pub fn render_scanline(ppu: &Ppu, y: usize, line: &mut [u16], mut chr_log: Option<&mut [u8]>) {
+    let bank_bg = if !ppu.controller.contains(Controller::BACKGROUND) { 0 } else { 1 };
+    const NAME_TABLE_WIDTH: usize = 32;
+
+    for tile_x in 0..NAME_TABLE_WIDTH {
+        let tile_n = ppu.vram[y / TILE_SIZE * NAME_TABLE_WIDTH + tile_x];
+        render_tile_row(line, ppu, bank_bg, tile_n as usize, tile_x, y, chr_log.as_deref_mut());
+    }
+
+    let bank_sprite = if !ppu.controller.contains(Controller::SPRITES_ADDR) { 0 } else { 1 };
+    for i in (0..ppu.oam_data.len()).step_by(4).rev() {
+        let sprite_y = ppu.oam_data[i] as usize;
+        if y < sprite_y || y >= sprite_y + TILE_SIZE {
+            continue;
+        }
+        let x = ppu.oam_data[i + 3] as usize;
+        let tile_n = ppu.oam_data[i + 1] as usize;
+        let attributes = ppu.oam_data[i + 2];
+
+        let row = render_sprite_row(line, ppu, bank_sprite, tile_n, x, y - sprite_y, attributes);
+        mark_rendered(chr_log.as_deref_mut(), bank_sprite, tile_n, row);
+    }
+}
+
+fn tile_start(bank: usize, tile_n: usize) -> usize {
+    let bank_start = if bank == 0 { LEFT_BANK_START } else { RIGHT_BANK_START };
+    bank_start + tile_n * TILE_LEN
+}
+
+fn tile(ppu: &Ppu, bank: usize, tile_n: usize) -> &[u8] {
+    let tile_start = tile_start(bank, tile_n);
+    &ppu.chr_rom[tile_start .. tile_start + TILE_LEN]
+}
+
+/* Both bit planes of the row, CHR RAM is longer than the log and is not marked */
+fn mark_rendered(chr_log: Option<&mut [u8]>, bank: usize, tile_n: usize, row: usize) {
+    let Some(chr_log) = chr_log else { return };
+    let start = tile_start(bank, tile_n) + row;
+    for offset in [start, start + 8] {
+        if let Some(flags) = chr_log.get_mut(offset) {
+            *flags |= ChrFlags::RENDERED.bits();
+        }
+    }
+}
+
+/* Returns the row of the tile it drew, flipping can make it another than asked for */
+fn render_sprite_row(line: &mut [u16], ppu: &Ppu, bank: usize, tile_n: usize, x: usize, row: usize, attributes: u8) -> usize {
+    let flip_vertical = attributes & 0b1000_0000 != 0;
+    let flip_horizontal = attributes & 0b0100_0000 != 0;
+    let palette_idx = attributes & 0b0000_0011;
+
+    let tile = tile(ppu, bank, tile_n);
+    let palette = palette::palette_sprite(ppu, palette_idx);
+
+    let i = if flip_vertical { 7 - row } else { row };
+    let (upper, lower) = (tile[i], tile[i + 8]);
+
+    for column in 0..TILE_SIZE {
+        let bit = if flip_horizontal { column } else { 7 - column };
+        let value = (lower >> bit & 1) << 1 | (upper >> bit & 1);
+        if value == 0 {
+            continue; // Transparent
+        }
+        if let Some(pixel) = line.get_mut(x + column) {
+            *pixel = palette[value as usize];
+        }
+    }
+    i
+}
+
+fn render_tile_row(line: &mut [u16], ppu: &Ppu, bank: usize, tile_n: usize, tile_x: usize, y: usize, chr_log: Option<&mut [u8]>) {
+    let tile = tile(ppu, bank, tile_n);
+    let palette = palette::palette_bg(ppu, tile_x, y / TILE_SIZE);
+
+    let i = y % TILE_SIZE;
+    let (upper, lower) = (tile[i], tile[i + 8]);
+    mark_rendered(chr_log, bank, tile_n, i);
+
+    for column in 0..TILE_SIZE {
+        let bit = 7 - column; // Leftmost pixel is the high bit
+        let value = (lower >> bit & 1) << 1 | (upper >> bit & 1);
+        line[tile_x * TILE_SIZE + column] = palette[value as usize];
+    }
+}
@@ -0,0 +1,175 @@
+use crate::emulator::nes::Nes;
+use std::collections::VecDeque;
+
+pub const DEFAULT_MEMORY: usize = 16 * 1024 * 1024;
+pub const DEFAULT_INTERVAL: usize = 2;
+
+/*
+    Snapshots are save states taken every `interval` frames. Only the newest one
+    is kept whole, each older one is stored as the XOR with the snapshot after it,
+    run length encoded since most of the machine does not change between frames.
+    The oldest deltas are dropped once `capacity` bytes are used.
+ */
+pub struct Rewind {
+    capacity: usize,
+    interval: usize,
+    frames: usize,
+    latest: Option<Vec<u8>>,
+    deltas: VecDeque<Vec<u8>>,
+    used: usize,
+}
+
+impl Rewind {
+    pub fn new(capacity: usize, interval: usize) -> Self {
+        Rewind {
+            capacity,
+            interval: interval.max(1),
+            frames: 0,
+            latest: None,
+            deltas: VecDeque::new(),
+            used: 0,
+        }
+    }
+
+    /* Call once per emulated frame */
+    pub fn record(&mut self, nes: &Nes) {
+        self.frames += 1;
+        if self.frames >= self.interval {
+            self.frames = 0;
+            self.push(nes.save_state());
+        }
+    }
+
+    pub fn push(&mut self, state: Vec<u8>) {
+        if let Some(latest) = self.latest.take() {
+            self.used -= latest.len();
+            if latest.len() == state.len() {
+                let delta = compress(&xor(&latest, &state));
+                self.used += delta.len();
+                self.deltas.push_back(delta);
+            } else {
+                self.deltas.clear();
+                self.used = 0;
+            }
+        }
+
+        self.used += state.len();
+        self.latest = Some(state);
+
+        while self.used > self.capacity && !self.deltas.is_empty() {
+            let oldest = self.deltas.pop_front().unwrap();
+            self.used -= oldest.len();
+        }
+    }
+
+    /* Newest snapshot, the one before it becomes the newest */
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        let latest = self.latest.take()?;
+        self.used -= latest.len();
+
+        if let Some(delta) = self.deltas.pop_back() {
+            self.used -= delta.len();
+            let previous = xor(&latest, &decompress(&delta, latest.len()));
+            self.used += previous.len();
+            self.latest = Some(previous);
+        }
+
+        Some(latest)
+    }
+
+    /* Restores the newest snapshot, false once the buffer is empty or it does not load, which keeps it */
+    pub fn step_back(&mut self, nes: &mut Nes) -> bool {
+        let Some(state) = self.latest.as_ref() else { return false };
+        if nes.load_state(state).is_err() {
+            return false;
+        }
+
+        self.pop();
+        self.frames = 0;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.latest = None;
+        self.deltas.clear();
+        self.used = 0;
+        self.frames = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.latest.as_ref().map_or(0, |_| 1 + self.deltas.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_none()
+    }
+
+    /* Bytes held by snapshots and deltas */
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+/*
+    Pairs of (zero run, literal run) as LEB128 lengths, each followed by its
+    literal bytes
+ */
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        let zeros = data[i..].iter().take_while(|&&byte| byte == 0).count();
+        i += zeros;
+        let literals = data[i..].iter().take_while(|&&byte| byte != 0).count();
+
+        write_length(&mut out, zeros);
+        write_length(&mut out, literals);
+        out.extend_from_slice(&data[i..i + literals]);
+        i += literals;
+    }
+
+    out
+}
+
+pub fn decompress(data: &[u8], len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+
+    while i < data.len() {
+        let zeros = read_length(data, &mut i);
+        let literals = read_length(data, &mut i);
+        out.resize(out.len() + zeros, 0);
+        out.extend_from_slice(&data[i..i + literals]);
+        i += literals;
+    }
+
+    out.resize(len, 0);
+    out
+}
+
+fn write_length(out: &mut Vec<u8>, mut length: usize) {
+    while length >= 0x80 {
+        out.push(length as u8 | 0x80);
+        length >>= 7;
+    }
+    out.push(length as u8);
+}
+
+fn read_length(data: &[u8], i: &mut usize) -> usize {
+    let mut length = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*i];
+        *i += 1;
+        length |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return length;
+        }
+        shift += 7;
+    }
+}
@@ -8,7 +8,7 @@ pub enum Mirroring {
     FOURSCREEN,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Rom {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
@@ -22,7 +22,7 @@ pub const CHR_ROM_PAGE_SIZE: usize = 0x2000;
 
 impl Rom {
     pub fn new(cartridge: Vec<u8>) -> Result<Rom, String> {
-        if &cartridge[0..4] != NES_HEADER {
+        if cartridge.len() < 16 || cartridge[0..4] != NES_HEADER {
             return Err("Invalid NES Header".to_string())
         }
 
@@ -51,6 +51,10 @@ impl Rom {
             return Err("Unsupported NES Version".to_string())
         }
 
+        if cartridge.len() < chr_rom_start + chr_rom_size {
+            return Err("Truncated NES ROM".to_string())
+        }
+
         Ok(Rom {
             prg_rom: cartridge[prg_rom_start..prg_rom_start + prg_rom_size].to_vec(),
             chr_rom: cartridge[chr_rom_start..chr_rom_start + chr_rom_size].to_vec(),
@@ -60,9 +64,11 @@ impl Rom {
     }
 
     pub fn load(rom_path: &str) -> Result<Rom, String> {
-        let mut file = File::open(rom_path).unwrap();
+        let mut file = File::open(rom_path)
+            .map_err(|e| format!("Could not open ROM {}: {}", rom_path, e))?;
         let mut cartridge = Vec::new();
-        file.read_to_end(&mut cartridge).unwrap();
+        file.read_to_end(&mut cartridge)
+            .map_err(|e| format!("Could not read ROM {}: {}", rom_path, e))?;
         Rom::new(cartridge)
     }
 }
\ No newline at end of file
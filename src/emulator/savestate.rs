@@ -0,0 +1,294 @@
+use crate::emulator::cpu::{ Cpu, Status as CpuStatus };
+use crate::emulator::bus::Bus;
+use crate::emulator::ppu::{ Ppu, Controller, Mask, Status as PpuStatus };
+use crate::emulator::interrupts::{ Interrupt, INTERRUPTS };
+use crate::emulator::region::Region;
+use crate::emulator::rom::Rom;
+use crate::emulator::input_device::DeviceKind;
+use crate::helpers::checksum::crc32_update;
+use std::path::Path;
+
+/*
+    Save state layout, all numbers little endian:
+
+    "NESS"      magic
+    u16         format version
+    u32         crc32 of PRG + CHR ROM
+    ...         Cpu, Bus (RAM, Ppu, kind and state of the port and expansion devices) and Nes fields in declaration order
+
+    Bump VERSION whenever the body changes, older states are then rejected
+ */
+pub const MAGIC: [u8; 4] = *b"NESS";
+pub const VERSION: u16 = 7;
+pub const SLOTS: usize = 10;
+
+pub fn rom_hash(rom: &Rom) -> u32 {
+    crc32_update(crc32_update(0, &rom.prg_rom), &rom.chr_rom)
+}
+
+/* game.nes -> game.ss1 next to the ROM */
+pub fn slot_path(rom_path: &str, slot: usize) -> String {
+    Path::new(rom_path).with_extension(format!("ss{}", slot)).to_string_lossy().into_owned()
+}
+
+#[derive(Default)]
+pub struct StateWriter {
+    pub data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new(rom: &Rom) -> Self {
+        let mut writer = StateWriter::default();
+        writer.bytes(&MAGIC);
+        writer.u16(VERSION);
+        writer.u32(rom_hash(rom));
+        writer
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn usize(&mut self, value: usize) {
+        self.bytes(&(value as u64).to_le_bytes());
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /* Checks the header before anything is read into the machine */
+    pub fn new(data: &'a [u8], rom: &Rom) -> Result<Self, String> {
+        let mut reader = StateReader { data, position: 0 };
+
+        if reader.bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err("Not a save state".to_string());
+        }
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(format!("Unsupported save state version {} (expected {})", version, VERSION));
+        }
+        if reader.u32()? != rom_hash(rom) {
+            return Err("Save state was made with a different ROM".to_string());
+        }
+
+        Ok(reader)
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self.data.get(self.position..self.position + len)
+            .ok_or("Save state is truncated")?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, String> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn usize(&mut self) -> Result<usize, String> {
+        Ok(u64::from_le_bytes(self.array()?) as usize)
+    }
+
+    pub fn finish(&self) -> Result<(), String> {
+        if self.position != self.data.len() {
+            return Err("Save state has trailing data".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn save_interrupt(writer: &mut StateWriter, interrupt: &Option<Interrupt>) {
+    writer.u8(match interrupt.as_ref().map(|i| &i.interrupt) {
+        None => 0,
+        Some(INTERRUPTS::NMI) => 1,
+        Some(INTERRUPTS::IRQ) => 2,
+        Some(INTERRUPTS::FRMFIN) => 3,
+    });
+}
+
+fn load_interrupt(reader: &mut StateReader) -> Result<Option<Interrupt>, String> {
+    match reader.u8()? {
+        0 => Ok(None),
+        1 => Ok(Some(Interrupt::new_nmi())),
+        2 => Ok(Some(Interrupt::new_irq())),
+        3 => Ok(Some(Interrupt::new_frmfin())),
+        kind => Err(format!("Invalid interrupt in save state: {}", kind)),
+    }
+}
+
+impl Cpu {
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.u8(self.accumulator);
+        writer.u8(self.register_x);
+        writer.u8(self.register_y);
+        writer.u8(self.stack_pointer);
+        writer.u16(self.program_counter);
+        writer.u8(self.status.bits());
+        writer.usize(self.cycles);
+        save_interrupt(writer, &self.interrupt);
+
+        self.bus.save_state(writer);
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.accumulator = reader.u8()?;
+        self.register_x = reader.u8()?;
+        self.register_y = reader.u8()?;
+        self.stack_pointer = reader.u8()?;
+        self.program_counter = reader.u16()?;
+        self.status = CpuStatus::from_bits_truncate(reader.u8()?);
+        self.cycles = reader.usize()?;
+        self.interrupt = load_interrupt(reader)?;
+
+        self.bus.load_state(reader)
+    }
+}
+
+impl Bus {
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.bytes(&self.cpu_vram);
+        writer.usize(self.ppu_clock_remainder);
+        self.ppu.save_state(writer);
+        for device in [Some(&self.port_1), Some(&self.port_2), self.expansion.as_ref()] {
+            writer.u8(device.map_or(NO_DEVICE, |device| device.kind() as u8));
+            if let Some(device) = device {
+                device.save_state(writer);
+            }
+        }
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.cpu_vram = reader.array()?;
+        self.ppu_clock_remainder = reader.usize()?;
+        self.ppu.load_state(reader)?;
+        let devices = [Some(&mut self.port_1), Some(&mut self.port_2), self.expansion.as_mut()];
+        for (port, device) in PORT_NAMES.iter().zip(devices) {
+            let kind = reader.u8()?;
+            let expected = device.as_ref().map_or(NO_DEVICE, |device| device.kind() as u8);
+            if kind != expected {
+                return Err(format!("Save state was made with {} in {}, not {}", device_name(kind), port, device_name(expected)));
+            }
+            if let Some(device) = device {
+                device.load_state(reader)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+const NO_DEVICE: u8 = 0xFF;
+const PORT_NAMES: [&str; 3] = ["port 1", "port 2", "the expansion port"];
+
+fn device_name(kind: u8) -> String {
+    match DeviceKind::from_index(kind) {
+        Some(kind) => format!("a {}", kind.name()),
+        None if kind == NO_DEVICE => "nothing".to_string(),
+        None => "an unknown device".to_string(),
+    }
+}
+
+/* chr_rom and mirroring come from the cartridge so they are not saved */
+impl Ppu {
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.bytes(&self.palette_table);
+        writer.bytes(&self.vram);
+        writer.u8(self.buffer);
+
+        writer.u8(self.controller.bits());
+        writer.u8(self.mask.bits());
+        writer.u8(self.status.bits());
+        writer.u8(self.oam_addr);
+        writer.bytes(&self.oam_data);
+        writer.u8(self.scroll.x);
+        writer.u8(self.scroll.y);
+        writer.bool(self.scroll.latch);
+        writer.u8(self.address.h);
+        writer.u8(self.address.l);
+        writer.bool(self.address.latch);
+
+        writer.usize(self.cycles);
+        writer.usize(self.scanline);
+        writer.u8(match self.region {
+            Region::Ntsc => 0,
+            Region::Pal => 1,
+            Region::Dendy => 2,
+        });
+        save_interrupt(writer, &self.interrupt);
+
+        /* The frame being drawn, so a state taken mid-frame finishes it with the same pixels */
+        for pixel in &self.output {
+            writer.u16(*pixel);
+        }
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.palette_table = reader.array()?;
+        self.vram = reader.array()?;
+        self.buffer = reader.u8()?;
+
+        self.controller = Controller::from_bits_truncate(reader.u8()?);
+        self.mask = Mask::from_bits_truncate(reader.u8()?);
+        self.status = PpuStatus::from_bits_truncate(reader.u8()?);
+        self.oam_addr = reader.u8()?;
+        self.oam_data = reader.array()?;
+        self.scroll.x = reader.u8()?;
+        self.scroll.y = reader.u8()?;
+        self.scroll.latch = reader.bool()?;
+        self.address.h = reader.u8()?;
+        self.address.l = reader.u8()?;
+        self.address.latch = reader.bool()?;
+
+        self.cycles = reader.usize()?;
+        self.scanline = reader.usize()?;
+        self.region = match reader.u8()? {
+            0 => Region::Ntsc,
+            1 => Region::Pal,
+            2 => Region::Dendy,
+            region => return Err(format!("Invalid region in save state: {}", region)),
+        };
+        self.interrupt = load_interrupt(reader)?;
+
+        for pixel in self.output.iter_mut() {
+            *pixel = reader.u16()?;
+        }
+
+        Ok(())
+    }
+}
+
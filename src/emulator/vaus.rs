@@ -0,0 +1,101 @@
+use crate::emulator::ppu::Ppu;
+use crate::emulator::input_device::{ InputDevice, DeviceKind };
+use crate::emulator::savestate::{ StateWriter, StateReader };
+
+/* Range of the knob, Arkanoid maps it to the width of the play field */
+pub const POSITION_MIN: u16 = 0x0C4;
+pub const POSITION_MAX: u16 = 0x1E4;
+
+const POSITION_BITS: u16 = 9;
+const FIRE_PRESSED: u8 = 0b0000_1000;
+const SERIAL_DATA: u8 = 0b0001_0000;
+
+/*
+    https://www.nesdev.org/wiki/Arkanoid_controller
+    A strobe latches the potentiometer, each read then shifts out one of its
+    9 bits in D4, highest first and inverted. D3 is the fire button.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vaus {
+    pub position: u16,
+    pub fire: bool,
+    pub strobe: bool,
+    pub shift: u16, // Shift register
+}
+
+impl Vaus {
+    pub fn new() -> Self {
+        Vaus {
+            position: (POSITION_MIN + POSITION_MAX) / 2,
+            fire: false,
+            strobe: false,
+            shift: 0,
+        }
+    }
+
+    /* Turns the knob to match an X coordinate on the NES screen */
+    pub fn aim(&mut self, x: usize) {
+        let range = (POSITION_MAX - POSITION_MIN) as usize;
+        self.position = POSITION_MIN + (x.min(255) * range / 255) as u16;
+    }
+
+    fn latch(&mut self) {
+        self.shift = !self.position & ((1 << POSITION_BITS) - 1);
+    }
+}
+
+impl Default for Vaus {
+    fn default() -> Self {
+        Vaus::new()
+    }
+}
+
+impl InputDevice for Vaus {
+    fn kind(&self) -> DeviceKind {
+        DeviceKind::Vaus
+    }
+
+    fn write(&mut self, value: u8) {
+        self.strobe = value & 1 == 1;
+        if self.strobe {
+            self.latch();
+        }
+    }
+
+    fn read(&mut self, ppu: &Ppu) -> u8 {
+        if self.strobe {
+            self.latch();
+        }
+        let value = self.read_debugging(ppu);
+        if !self.strobe {
+            self.shift = (self.shift << 1) & ((1 << POSITION_BITS) - 1);
+        }
+        value
+    }
+
+    fn read_debugging(&self, _ppu: &Ppu) -> u8 {
+        let mut value = 0;
+        if self.shift >> (POSITION_BITS - 1) & 1 == 1 {
+            value |= SERIAL_DATA;
+        }
+        if self.fire {
+            value |= FIRE_PRESSED;
+        }
+        value
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.strobe);
+        writer.u16(self.shift);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), String> {
+        self.strobe = reader.bool()?;
+        self.shift = reader.u16()?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn InputDevice> {
+        Box::new(*self)
+    }
+}
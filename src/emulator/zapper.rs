@@ -0,0 +1,83 @@
+use crate::emulator::ppu::Ppu;
+use crate::emulator::input_device::{ InputDevice, DeviceKind };
+use crate::emulator::savestate::{ StateWriter, StateReader };
+use crate::emulator::frame::Frame;
+use crate::emulator::palette::DEFAULT_PALETTE;
+
+const LIGHT_NOT_DETECTED: u8 = 0b0000_1000;
+const TRIGGER_PULLED: u8 = 0b0001_0000;
+
+const SENSOR_RADIUS: usize = 2; // Pixels around the aim point the photodiode sees
+const SENSOR_SCANLINES: usize = 20; // How long the photodiode keeps reporting light after the beam passes
+const BRIGHTNESS_THRESHOLD: u32 = 85 * 3; // Sum of R, G and B
+
+/*
+    https://www.nesdev.org/wiki/Zapper
+    Reads from its port return the trigger in bit 4 and the light sensor in
+    bit 3, which is 0 while the photodiode sees a bright pixel. The sensor
+    only sees light for a short while after the beam draws near the aim point,
+    so games blank the screen and flash targets white for a frame to check hits.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Zapper {
+    pub aim: Option<(usize, usize)>, // NES pixel coordinates, None when pointed off screen
+    pub trigger: bool,
+}
+
+impl Zapper {
+    pub fn new() -> Self {
+        Zapper::default()
+    }
+
+    /* Only lines the beam has finished this frame count, the current one is still being drawn */
+    pub fn light_detected(&self, ppu: &Ppu) -> bool {
+        let Some((x, y)) = self.aim else { return false };
+        let top = y.saturating_sub(SENSOR_RADIUS);
+        let bottom = (y + SENSOR_RADIUS).min(Frame::HEIGHT - 1);
+        let left = x.saturating_sub(SENSOR_RADIUS);
+        let right = (x + SENSOR_RADIUS).min(Frame::WIDTH - 1);
+
+        (top..=bottom)
+            .filter(|line| *line < ppu.scanline && ppu.scanline - line <= SENSOR_SCANLINES)
+            .any(|line| ppu.scanline_output(line)[left..=right].iter().any(|pixel| is_bright(*pixel)))
+    }
+}
+
+fn is_bright(pixel: u16) -> bool {
+    let (r, g, b) = DEFAULT_PALETTE[pixel as usize & 0x3F]; // Emphasis bits dim a little, not enough to matter
+    r as u32 + g as u32 + b as u32 >= BRIGHTNESS_THRESHOLD
+}
+
+/* Nothing is latched, the sensor and trigger are read as they are */
+impl InputDevice for Zapper {
+    fn kind(&self) -> DeviceKind {
+        DeviceKind::Zapper
+    }
+
+    fn write(&mut self, _value: u8) {}
+
+    fn read(&mut self, ppu: &Ppu) -> u8 {
+        self.read_debugging(ppu)
+    }
+
+    fn read_debugging(&self, ppu: &Ppu) -> u8 {
+        let mut value = 0;
+        if !self.light_detected(ppu) {
+            value |= LIGHT_NOT_DETECTED;
+        }
+        if self.trigger {
+            value |= TRIGGER_PULLED;
+        }
+        value
+    }
+
+    fn save_state(&self, _writer: &mut StateWriter) {}
+
+    fn load_state(&mut self, _reader: &mut StateReader) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn InputDevice> {
+        Box::new(*self)
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicU32, Ordering };
+
+use sdl2::keyboard::{ Keycode, Mod };
+use sdl2::controller::Button;
+use sdl2::audio::{ AudioCallback, AudioDevice, AudioSpecDesired };
+use sdl2::AudioSubsystem;
+use nes::emulator::joypad::Buttons;
+use nes::emulator::family_keyboard::KEYS;
+use nes::player::microphone::peak_level;
+use nes::player::bindings::{ Bindings, Hotkey, KeyBinding };
+use nes::player::gamepad::GamepadButton;
+
+/* Bindings with their key names looked up as SDL keycodes */
+pub struct KeyMap {
+    buttons: HashMap<Keycode, Vec<(usize, Buttons)>>,
+    turbo: HashMap<Keycode, Vec<(usize, Buttons)>>,
+    power_pad: HashMap<Keycode, Vec<usize>>,
+    hotkeys: Vec<(Keycode, KeyBinding, Hotkey)>,
+}
+
+impl KeyMap {
+    pub fn new(bindings: &Bindings) -> Result<KeyMap, String> {
+        let mut buttons: HashMap<Keycode, Vec<(usize, Buttons)>> = HashMap::new();
+        for (key, port, button) in bindings.keys.iter() {
+            buttons.entry(keycode(key)?).or_default().push((*port, *button));
+        }
+
+        let mut turbo: HashMap<Keycode, Vec<(usize, Buttons)>> = HashMap::new();
+        for (key, port, button) in bindings.turbo_keys.iter() {
+            turbo.entry(keycode(key)?).or_default().push((*port, *button));
+        }
+
+        let mut power_pad: HashMap<Keycode, Vec<usize>> = HashMap::new();
+        for (key, button) in bindings.power_pad.iter() {
+            power_pad.entry(keycode(key)?).or_default().push(*button);
+        }
+
+        let hotkeys = bindings.hotkeys.iter()
+            .map(|(key, hotkey)| Ok((keycode(key)?, key.clone(), *hotkey)))
+            .collect::<Result<_, String>>()?;
+
+        Ok(KeyMap { buttons, turbo, power_pad, hotkeys })
+    }
+
+    /* Hotkeys need exactly their modifiers, so F1 and Shift+F1 can differ */
+    pub fn hotkey(&self, keycode: Keycode, keymod: Mod) -> Option<Hotkey> {
+        let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+        let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+        let alt = keymod.intersects(Mod::LALTMOD | Mod::RALTMOD);
+
+        self.hotkeys.iter()
+            .find(|(k, key, _)| *k == keycode && key.ctrl == ctrl && key.shift == shift && key.alt == alt)
+            .map(|(_, _, hotkey)| *hotkey)
+    }
+
+    /* Held hotkeys stop on key up whatever modifiers are still down */
+    pub fn held_hotkeys(&self, keycode: Keycode) -> impl Iterator<Item = Hotkey> + '_ {
+        self.hotkeys.iter()
+            .filter(move |(k, _, hotkey)| *k == keycode && hotkey.is_held())
+            .map(|(_, _, hotkey)| *hotkey)
+    }
+
+    /* Controller buttons ignore modifiers so Shift+Left still moves */
+    pub fn buttons(&self, keycode: Keycode) -> &[(usize, Buttons)] {
+        self.buttons.get(&keycode).map_or(&[], |buttons| buttons.as_slice())
+    }
+
+    pub fn turbo_buttons(&self, keycode: Keycode) -> &[(usize, Buttons)] {
+        self.turbo.get(&keycode).map_or(&[], |buttons| buttons.as_slice())
+    }
+
+    pub fn power_pad_buttons(&self, keycode: Keycode) -> &[usize] {
+        self.power_pad.get(&keycode).map_or(&[], |buttons| buttons.as_slice())
+    }
+}
+
+fn keycode(key: &KeyBinding) -> Result<Keycode, String> {
+    Keycode::from_name(&key.key).ok_or(format!("Unknown key '{}'", key.key))
+}
+
+/* Family BASIC key for a PC key, keys with the same name map directly */
+pub fn family_key(keycode: Keycode) -> Option<&'static str> {
+    let key = match keycode {
+        Keycode::Return | Keycode::KpEnter => "RETURN",
+        Keycode::Escape => "ESC",
+        Keycode::LCtrl => "CTR",
+        Keycode::RCtrl => "_",
+        Keycode::LShift => "LSHIFT",
+        Keycode::RShift => "RSHIFT",
+        Keycode::LAlt => "GRPH",
+        Keycode::RAlt => "KANA",
+        Keycode::Backspace | Keycode::Delete => "DEL",
+        Keycode::Insert => "INS",
+        Keycode::Home => "CLR",
+        Keycode::End => "STOP",
+        Keycode::Backslash => "¥",
+        Keycode::Equals => "^",
+        Keycode::Quote => ":",
+        Keycode::Backquote => "@",
+        _ => {
+            let name = keycode.name();
+            return KEYS.iter().flatten().flatten().find(|key| key.eq_ignore_ascii_case(&name)).copied();
+        },
+    };
+    Some(key)
+}
+
+/* Keeps the loudest sample of the last buffer the recording device delivered */
+struct LevelMeter {
+    level: Arc<AtomicU32>,
+}
+
+impl AudioCallback for LevelMeter {
+    type Channel = i16;
+
+    fn callback(&mut self, samples: &mut [i16]) {
+        self.level.store(peak_level(samples).to_bits(), Ordering::Relaxed);
+    }
+}
+
+pub struct MicrophoneCapture {
+    _device: AudioDevice<LevelMeter>,
+    level: Arc<AtomicU32>,
+}
+
+impl MicrophoneCapture {
+    pub fn open(audio: &AudioSubsystem) -> Result<MicrophoneCapture, String> {
+        let level = Arc::new(AtomicU32::new(0));
+        let spec = AudioSpecDesired { freq: Some(22050), channels: Some(1), samples: Some(512) };
+        let meter = LevelMeter { level: level.clone() };
+        let device = audio.open_capture(None, &spec, |_| meter)
+            .map_err(|e| format!("Could not open the recording device: {}", e))?;
+        device.resume();
+        Ok(MicrophoneCapture { _device: device, level })
+    }
+
+    /* 0 to 1 */
+    pub fn level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+}
+
+pub fn gamepad_button(button: Button) -> Option<GamepadButton> {
+    match button {
+        Button::A => Some(GamepadButton::A),
+        Button::B => Some(GamepadButton::B),
+        Button::X => Some(GamepadButton::X),
+        Button::Y => Some(GamepadButton::Y),
+        Button::Back => Some(GamepadButton::Back),
+        Button::Guide => Some(GamepadButton::Guide),
+        Button::Start => Some(GamepadButton::Start),
+        Button::LeftStick => Some(GamepadButton::LeftStick),
+        Button::RightStick => Some(GamepadButton::RightStick),
+        Button::LeftShoulder => Some(GamepadButton::LeftShoulder),
+        Button::RightShoulder => Some(GamepadButton::RightShoulder),
+        Button::DPadUp => Some(GamepadButton::DPadUp),
+        Button::DPadDown => Some(GamepadButton::DPadDown),
+        Button::DPadLeft => Some(GamepadButton::DPadLeft),
+        Button::DPadRight => Some(GamepadButton::DPadRight),
+        _ => None,
+    }
+}
@@ -0,0 +1,113 @@
+mod player;
+mod controls;
+
+use nes::emulator::nes::Nes;
+use nes::emulator::rewind::Rewind;
+use nes::emulator::movie::Movie;
+use nes::emulator::cdl::CodeDataLog;
+use nes::emulator::input_device::DeviceKind;
+use nes::emulator::palette::Palette;
+use nes::player::input::Input;
+use nes::cli::options::{ self, Options, USAGE };
+use nes::debugger::core::Debugger;
+use nes::debugger::gdb::GdbStub;
+use nes::helpers::symbols::Symbols;
+use std::env;
+use std::path::Path;
+use std::process::exit;
+use player::Player;
+
+fn main() {
+    let options = match options::parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            exit(0);
+        },
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            exit(2);
+        },
+    };
+
+    let mut player = match create_player(&options) {
+        Ok(player) => player,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            exit(1);
+        },
+    };
+
+    let mut main_loop = move || {
+        player.run();
+    };
+
+    #[cfg(target_os = "emscripten")]
+    use nes::emscripten::{emscripten};
+
+    #[cfg(target_os = "emscripten")]
+    emscripten::set_main_loop_callback(main_loop);
+
+    #[cfg(not(target_os = "emscripten"))]
+    loop { main_loop(); }
+}
+
+fn create_player(options: &Options) -> Result<Player, String> {
+    let mut nes = Nes::load(&options.rom_path)?;
+    nes.set_region(options.region);
+    nes.set_four_score(options.four_score);
+    if options.port_2 != DeviceKind::Joypad {
+        nes.plug_in(1, options.port_2.create());
+    }
+    if let Some(expansion) = options.expansion {
+        nes.plug_in(2, expansion.create());
+        println!("The {} takes every key, Scroll Lock switches back to the controllers and hotkeys", expansion.name());
+    }
+
+    if let Some(path) = &options.cdl {
+        nes.start_code_data_log(CodeDataLog::load_or_new(path, nes.rom())?);
+    }
+
+    let prg_size = nes.cpu.bus.prg_rom.len();
+    let mut player = Player::new(nes, options.display);
+    player.set_rom_path(&options.rom_path);
+    if let Some(path) = &options.cdl {
+        player.set_code_data_log_path(path);
+    }
+    if let Some(path) = &options.palette {
+        player.set_palette(Palette::load(path)?);
+    }
+    if let Some(path) = &options.trace {
+        player.set_trace(path)?;
+    }
+    if !options.symbols.is_empty() {
+        player.set_symbols(Symbols::load_all(&options.symbols, prg_size)?);
+    }
+    player.set_filter(options.filter);
+    player.set_bindings(options.bindings.clone())?;
+    player.set_input(Input::new(options.turbo_rate, options.socd));
+    player.set_microphone(options.microphone, options.mic_threshold)?;
+    player.set_paused(options.paused);
+    if options.mute {
+        eprintln!("warning: --mute has no effect, there is no sound output yet");
+    }
+    if options.debug {
+        player.set_debugger(Debugger::new());
+    }
+    if let Some(port) = options.gdb {
+        player.set_gdb(GdbStub::listen(("127.0.0.1", port))?);
+    }
+    player.set_frame_limit(options.frames);
+    if let Some(path) = &options.play_movie {
+        player.play_movie(Movie::load(path)?)?;
+    }
+    if let Some(path) = &options.record_movie {
+        let rom_name = Path::new(&options.rom_path).file_stem().unwrap_or_default().to_string_lossy();
+        player.record_movie(path, &rom_name);
+    }
+    if options.rewind_memory > 0 {
+        player.set_rewind(Some(Rewind::new(options.rewind_memory, options.rewind_interval)));
+    }
+
+    Ok(player)
+}
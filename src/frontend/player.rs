@@ -0,0 +1,739 @@
+use nes::emulator::nes::Nes;
+use nes::emulator::zapper::Zapper;
+use nes::emulator::vaus::Vaus;
+use nes::emulator::power_pad::PowerPad;
+use nes::emulator::family_keyboard::FamilyKeyboard;
+use nes::emulator::savestate;
+use nes::emulator::rewind::Rewind;
+use nes::emulator::movie::{ self, Movie, MoviePlayback, MovieRecording };
+use nes::helpers::{ png, trace::trace_with_symbols };
+use nes::helpers::symbols::Symbols;
+use nes::debugger::core::Debugger;
+use nes::debugger::commands::{ run_command, describe_stop };
+use nes::debugger::console::Console;
+use nes::debugger::gdb::GdbStub;
+use nes::emulator::palette::Palette;
+use nes::emulator::frame::Frame;
+use nes::player::filters::{ VideoFilter, FilterKind };
+use nes::player::display::{ DisplaySettings, Rect as DisplayRect };
+use crate::controls::{ KeyMap, MicrophoneCapture, family_key, gamepad_button };
+use nes::player::gamepad::{ self, GamepadSettings, GamepadState };
+use nes::player::bindings::{ Bindings, Hotkey, KeyBinding, Rebinding };
+use nes::player::input::Input;
+use nes::player::microphone::{ self, MicSource };
+use std::process::exit;
+use std::fs::{ self, File };
+use std::io::{ BufWriter, Write };
+use std::path::Path;
+use std::time::Duration;
+use std::thread;
+use std::rc::Rc;
+use sdl2::render::{ Texture, TextureCreator };
+use sdl2::{
+    event::{ Event, WindowEvent },
+    EventPump,
+    AudioSubsystem,
+    GameControllerSubsystem,
+    controller::{ Axis, GameController },
+    keyboard::{ Keycode, Mod },
+    mouse::MouseButton,
+    pixels::PixelFormatEnum,
+    rect::Rect,
+    render::Canvas,
+    video::{ Window, WindowContext, FullscreenType },
+};
+
+const TITLE: &str = "NES Emulator";
+const FAST_FORWARD_SPEED: usize = 4;
+
+pub struct Player {
+    event_pump: EventPump,
+    canvas: Canvas<Window>,
+    texture_creator: TextureCreator<WindowContext>,
+    texture: Option<(Texture, (usize, usize))>, // Remade when the filter's output size changes
+    nes: Nes,
+    frame: Frame,
+    palette: Palette,
+    filter_kind: FilterKind,
+    filter: Box<dyn VideoFilter>,
+    output: Vec<u8>,
+    display: DisplaySettings,
+    viewport: Rect,
+    paused: bool,
+    frame_limit: Option<usize>,
+    trace: Option<BufWriter<File>>,
+    symbols: Rc<Symbols>,
+    rom_path: Option<String>,
+    cdl_path: Option<String>,
+    rewind: Option<Rewind>,
+    rewinding: bool,
+    recording: Option<(MovieRecording, String)>,
+    playback: Option<MoviePlayback>,
+    commands: u8,
+    controller_subsystem: GameControllerSubsystem,
+    gamepads: Vec<(GameController, GamepadState)>,
+    bindings: Bindings,
+    key_map: KeyMap,
+    rebinding: Option<Rebinding>,
+    fast_forward: bool,
+    input: Input,
+    audio_subsystem: Option<AudioSubsystem>,
+    microphone: Option<MicrophoneCapture>,
+    mic_threshold: f32,
+    mic_key: bool,
+    keyboard_captured: bool, // Keys go to the Family BASIC keyboard when one is plugged in
+    debugger: Option<Debugger>,
+    console: Option<Console>,
+    gdb: Option<GdbStub>,
+    frame_in_progress: bool, // The debugger stopped in the middle of a frame
+}
+
+impl Player {
+    pub fn new(nes: Nes, display: DisplaySettings) -> Player {
+        let sdl_context = sdl2::init().unwrap();
+
+        let (width, height) = display.window_size();
+        let video_subsystem = sdl_context.video().unwrap();
+        let window = video_subsystem
+            .window(TITLE, width, height)
+            .position_centered()
+            .resizable()
+            .build()
+            .unwrap();
+        
+        let canvas = window.into_canvas().present_vsync().build().unwrap();
+        let (output_width, output_height) = canvas.output_size().unwrap();
+
+        let texture_creator = canvas.texture_creator();
+        let event_pump = sdl_context.event_pump().unwrap();
+        let controller_subsystem = sdl_context.game_controller().unwrap(); // Already connected controllers arrive as added events
+        let audio_subsystem = sdl_context.audio().ok(); // Only needed for the microphone
+
+        let frame = Frame::new();
+
+        Player {
+            event_pump,
+            canvas,
+            texture_creator,
+            texture: None,
+            nes,
+            frame,
+            palette: Palette::default(),
+            filter_kind: FilterKind::None,
+            filter: FilterKind::None.create(),
+            output: Vec::new(),
+            display,
+            viewport: to_sdl_rect(display.viewport(output_width, output_height)),
+            paused: false,
+            frame_limit: None,
+            trace: None,
+            symbols: Rc::default(),
+            rom_path: None,
+            cdl_path: None,
+            rewind: None,
+            rewinding: false,
+            recording: None,
+            playback: None,
+            commands: 0,
+            controller_subsystem,
+            gamepads: Vec::new(),
+            bindings: Bindings::default(),
+            key_map: KeyMap::new(&Bindings::default()).unwrap(),
+            rebinding: None,
+            fast_forward: false,
+            input: Input::default(),
+            audio_subsystem,
+            microphone: None,
+            mic_threshold: microphone::DEFAULT_THRESHOLD,
+            mic_key: false,
+            keyboard_captured: true,
+            debugger: None,
+            console: None,
+            gdb: None,
+            frame_in_progress: false,
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn set_filter(&mut self, kind: FilterKind) {
+        self.filter_kind = kind;
+        self.filter = kind.create();
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn set_frame_limit(&mut self, frames: Option<usize>) {
+        self.frame_limit = frames;
+    }
+
+    pub fn set_trace(&mut self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("Could not create trace file {}: {}", path, e))?;
+        self.trace = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    /* Save state slots are stored next to this path */
+    pub fn set_rom_path(&mut self, path: &str) {
+        self.rom_path = Some(path.to_string());
+    }
+
+    /* Where the Nes's code/data log is written when the player quits */
+    pub fn set_code_data_log_path(&mut self, path: &str) {
+        self.cdl_path = Some(path.to_string());
+    }
+
+    /* Fails if a key name is not known to SDL */
+    pub fn set_bindings(&mut self, bindings: Bindings) -> Result<(), String> {
+        self.key_map = KeyMap::new(&bindings)?;
+        self.bindings = bindings;
+        Ok(())
+    }
+
+    pub fn set_input(&mut self, input: Input) {
+        self.input = input;
+    }
+
+    /* Names for the trace and the debugger */
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = Rc::new(symbols);
+        if let Some(debugger) = self.debugger.as_mut() {
+            debugger.symbols = self.symbols.clone();
+        }
+    }
+
+    /* Commands are read from the terminal, the game waits for the first continue */
+    pub fn set_debugger(&mut self, mut debugger: Debugger) {
+        debugger.symbols = self.symbols.clone();
+        let console = Console::spawn();
+        println!("Debugger attached, type help for commands");
+        console.prompt();
+        self.debugger = Some(debugger);
+        self.console = Some(console);
+    }
+
+    /* gdb shares the console's debugger, or gets one of its own that starts halted */
+    pub fn set_gdb(&mut self, gdb: GdbStub) {
+        println!("Waiting for gdb on {}", gdb.local_addr());
+        self.debugger.get_or_insert_with(Debugger::new).symbols = self.symbols.clone();
+        self.gdb = Some(gdb);
+    }
+
+    /* The hotkey drives the microphone either way, audio adds the recording device */
+    pub fn set_microphone(&mut self, source: MicSource, threshold: f32) -> Result<(), String> {
+        self.mic_threshold = threshold;
+        self.microphone = match source {
+            MicSource::Key => None,
+            MicSource::Audio => {
+                let audio = self.audio_subsystem.as_ref().ok_or("No audio support, the microphone can only use its key")?;
+                Some(MicrophoneCapture::open(audio)?)
+            },
+        };
+        Ok(())
+    }
+
+    pub fn set_rewind(&mut self, rewind: Option<Rewind>) {
+        self.rewind = rewind;
+    }
+
+    /* Records from power on, the movie is written when the player quits */
+    pub fn record_movie(&mut self, path: &str, rom_filename: &str) {
+        self.recording = Some((MovieRecording::start(&mut self.nes, rom_filename, true), path.to_string()));
+    }
+
+    pub fn play_movie(&mut self, movie: Movie) -> Result<(), String> {
+        self.playback = Some(MoviePlayback::start(movie, &mut self.nes)?);
+        Ok(())
+    }
+
+    /* Puts the movies on the frame the console went back to */
+    fn rerecord(&mut self) {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.seek(&self.nes);
+        }
+        if let Some((recording, _)) = self.recording.as_mut() {
+            recording.rerecord(&self.nes);
+        }
+    }
+
+    /* Movie input replaces the keyboard until the movie ends */
+    fn start_frame(&mut self) {
+        let commands = std::mem::take(&mut self.commands);
+        for (port, buttons) in self.input.next_frame().into_iter().enumerate() {
+            self.nes.set_buttons(port, buttons);
+        }
+        let loud = self.microphone.as_ref().is_some_and(|microphone| microphone.level() >= self.mic_threshold);
+        self.nes.set_microphone(self.mic_key || loud);
+
+        let frame = self.playback.as_mut().and_then(|playback| playback.next_frame(&mut self.nes));
+        let commands = match frame {
+            Some(frame) => frame.commands,
+            None => {
+                if self.playback.take().is_some() {
+                    println!("Movie finished");
+                }
+                movie::run_commands(&mut self.nes, commands);
+                commands
+            },
+        };
+
+        if let Some((recording, _)) = self.recording.as_mut() {
+            recording.record_frame(&self.nes, commands);
+        }
+    }
+
+    fn connect_gamepad(&mut self, joystick_index: u32) {
+        let controller = match self.controller_subsystem.open(joystick_index) {
+            Ok(controller) => controller,
+            Err(e) => {
+                eprintln!("Could not open controller {}: {}", joystick_index, e);
+                return;
+            },
+        };
+        let port = gamepad::free_port(self.gamepads.iter().map(|(_, state)| state.port));
+
+        match port {
+            Some(port) => println!("{} connected to port {}", controller.name(), port + 1),
+            None => println!("{} connected, all ports are taken", controller.name()),
+        }
+        self.gamepads.push((controller, GamepadState::new(port)));
+    }
+
+    fn disconnect_gamepad(&mut self, id: u32) {
+        self.update_gamepad(id, |state, _| *state = GamepadState::new(state.port));
+        if let Some(i) = self.gamepads.iter().position(|(controller, _)| controller.instance_id() == id) {
+            let (controller, _) = self.gamepads.remove(i);
+            println!("{} disconnected", controller.name());
+        }
+    }
+
+    /* Only the buttons this controller changed are touched, the keyboard keeps its own */
+    fn update_gamepad(&mut self, id: u32, update: impl FnOnce(&mut GamepadState, &GamepadSettings)) {
+        let Some((_, state)) = self.gamepads.iter_mut().find(|(controller, _)| controller.instance_id() == id) else { return };
+        let settings = self.bindings.gamepads[state.port.unwrap_or(0)];
+
+        let (before, turbo_before) = (state.buttons(&settings), state.turbo_buttons());
+        update(state, &settings);
+        let (after, turbo_after) = (state.buttons(&settings), state.turbo_buttons());
+
+        if let Some(port) = state.port {
+            self.input.release(port, before - after);
+            self.input.press(port, after - before);
+            self.input.release_turbo(port, turbo_before - turbo_after);
+            self.input.press_turbo(port, turbo_after - turbo_before);
+        }
+    }
+
+    /* Mouse coordinates are in window points, the viewport is in pixels which differ on high DPI screens */
+    fn aim(&mut self, x: i32, y: i32) {
+        let (window_width, window_height) = self.canvas.window().size();
+        let (width, height) = self.canvas.output_size().unwrap();
+        let x = x as i64 * width as i64 / window_width.max(1) as i64;
+        let y = y as i64 * height as i64 / window_height.max(1) as i64;
+        let position = self.display.frame_position(self.display.viewport(width, height), x as i32, y as i32);
+
+        if let Some(zapper) = self.nes.device_mut::<Zapper>(1) {
+            zapper.aim = position;
+        }
+        if let (Some(vaus), Some((x, _))) = (self.nes.device_mut::<Vaus>(1), position) {
+            vaus.aim(x);
+        }
+    }
+
+    fn pull_trigger(&mut self, pulled: bool) {
+        if let Some(zapper) = self.nes.device_mut::<Zapper>(1) {
+            zapper.trigger = pulled;
+        }
+        if let Some(vaus) = self.nes.device_mut::<Vaus>(1) {
+            vaus.fire = pulled;
+        }
+    }
+
+    fn save_slot(&mut self, slot: usize) {
+        let Some(rom_path) = &self.rom_path else { return };
+        let path = savestate::slot_path(rom_path, slot);
+
+        match fs::write(&path, self.nes.save_state()) {
+            Ok(()) => println!("Saved state {} to {}", slot, path),
+            Err(e) => eprintln!("Could not write save state {}: {}", path, e),
+        }
+    }
+
+    fn load_slot(&mut self, slot: usize) {
+        let Some(rom_path) = &self.rom_path else { return };
+        let path = savestate::slot_path(rom_path, slot);
+
+        let result = fs::read(&path)
+            .map_err(|e| format!("Could not read save state {}: {}", path, e))
+            .and_then(|data| self.nes.load_state(&data));
+        match result {
+            Ok(()) => {
+                println!("Loaded state {} from {}", slot, path);
+                self.rerecord();
+            },
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    fn quit(&mut self) -> ! {
+        if let Some((recording, path)) = self.recording.as_ref() {
+            match recording.movie.save(path) {
+                Ok(()) => println!("Saved movie to {}", path),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if let (Some(log), Some(path)) = (self.nes.code_data_log(), self.cdl_path.as_ref()) {
+            match log.save(path) {
+                Ok(()) => println!("Saved code/data log to {}", path),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            trace.flush().unwrap();
+        }
+        exit(0);
+    }
+
+    fn output_size(&self) -> (usize, usize) {
+        self.filter.output_size(Frame::WIDTH, Frame::HEIGHT)
+    }
+
+    pub fn render(&mut self) {
+        self.frame.pixels.copy_from_slice(&self.nes.framebuffer().pixels);
+
+        self.frame.apply_palette(&self.palette);
+        self.filter.apply(&self.frame, &mut self.output);
+
+        let (width, height) = self.output_size();
+        if self.texture.as_ref().is_none_or(|(_, size)| *size != (width, height)) {
+            if let Some((texture, _)) = self.texture.take() {
+                /* Nothing else holds it and the canvas it was made for is still alive */
+                unsafe { texture.destroy() };
+            }
+            let texture = self.texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, width as u32, height as u32).unwrap();
+            self.texture = Some((texture, (width, height)));
+        }
+        let (texture, _) = self.texture.as_mut().unwrap();
+        texture.update(None, &self.output, width * Frame::RGB_DATA_LEN).unwrap();
+
+        let source = to_sdl_rect(self.display.source_rect(width, height));
+
+        self.canvas.clear();
+        self.canvas.copy(texture, source, self.viewport).unwrap();
+        self.canvas.present();
+    }
+
+    fn handle_user_input(&mut self) {
+        let events: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in events {
+            match event {
+                Event::Quit { .. } => self.quit(),
+                Event::KeyDown { keycode: Some(keycode), keymod, repeat, .. } => self.key_down(keycode, keymod, repeat),
+                Event::KeyUp { keycode: Some(keycode), .. } => self.key_up(keycode),
+                Event::Window { win_event: WindowEvent::Resized(..) | WindowEvent::SizeChanged(..), .. } => {
+                    let (width, height) = self.canvas.output_size().unwrap();
+                    self.viewport = to_sdl_rect(self.display.viewport(width, height));
+                },
+                Event::MouseMotion { x, y, .. } => self.aim(x, y),
+                Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                    self.aim(x, y);
+                    self.pull_trigger(true);
+                },
+                Event::MouseButtonUp { mouse_btn: MouseButton::Left, .. } => self.pull_trigger(false),
+                Event::ControllerDeviceAdded { which, .. } => self.connect_gamepad(which),
+                Event::ControllerDeviceRemoved { which, .. } => self.disconnect_gamepad(which),
+                Event::ControllerButtonDown { which, button, .. } => {
+                    if let Some(button) = gamepad_button(button) {
+                        self.update_gamepad(which, |state, settings| state.press(button, settings));
+                    }
+                },
+                Event::ControllerButtonUp { which, button, .. } => {
+                    if let Some(button) = gamepad_button(button) {
+                        self.update_gamepad(which, |state, settings| state.release(button, settings));
+                    }
+                },
+                Event::ControllerAxisMotion { which, axis: Axis::LeftX, value, .. } => {
+                    self.update_gamepad(which, |state, _| state.move_stick(Some(value), None));
+                },
+                Event::ControllerAxisMotion { which, axis: Axis::LeftY, value, .. } => {
+                    self.update_gamepad(which, |state, _| state.move_stick(None, Some(value)));
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn key_down(&mut self, keycode: Keycode, keymod: Mod, repeat: bool) {
+        if self.rebinding.is_some() {
+            if !repeat {
+                self.rebind_key(keycode);
+            }
+            return;
+        }
+
+        let hotkey = self.key_map.hotkey(keycode, keymod);
+        if self.typing() && hotkey != Some(Hotkey::KeyboardCapture) {
+            self.type_key(keycode, true);
+            return;
+        }
+
+        match hotkey {
+            Some(hotkey) if !repeat => self.run_hotkey(hotkey),
+            Some(_) => {},
+            None => {
+                for (port, button) in self.key_map.buttons(keycode) {
+                    self.input.press(*port, *button);
+                }
+                for (port, button) in self.key_map.turbo_buttons(keycode) {
+                    self.input.press_turbo(*port, *button);
+                }
+                self.press_power_pad(keycode, true);
+            },
+        }
+    }
+
+    fn key_up(&mut self, keycode: Keycode) {
+        for hotkey in self.key_map.held_hotkeys(keycode) {
+            match hotkey {
+                Hotkey::Rewind => self.rewinding = false,
+                Hotkey::FastForward => self.fast_forward = false,
+                Hotkey::Microphone => self.mic_key = false,
+                _ => {},
+            }
+        }
+        self.type_key(keycode, false);
+        for (port, button) in self.key_map.buttons(keycode) {
+            self.input.release(*port, *button);
+        }
+        for (port, button) in self.key_map.turbo_buttons(keycode) {
+            self.input.release_turbo(*port, *button);
+        }
+        self.press_power_pad(keycode, false);
+    }
+
+    fn typing(&self) -> bool {
+        self.keyboard_captured && self.nes.device::<FamilyKeyboard>(2).is_some()
+    }
+
+    fn type_key(&mut self, keycode: Keycode, pressed: bool) {
+        let (Some(keyboard), Some(key)) = (self.nes.device_mut::<FamilyKeyboard>(2), family_key(keycode)) else { return };
+        keyboard.set_key(key, pressed);
+    }
+
+    fn press_power_pad(&mut self, keycode: Keycode, pressed: bool) {
+        let Some(power_pad) = self.nes.device_mut::<PowerPad>(1) else { return };
+        for button in self.key_map.power_pad_buttons(keycode) {
+            power_pad.set_button(*button, pressed);
+        }
+    }
+
+    fn run_hotkey(&mut self, hotkey: Hotkey) {
+        match hotkey {
+            Hotkey::Quit => self.quit(),
+            Hotkey::Pause => self.paused = !self.paused,
+            Hotkey::Rewind => self.rewinding = true,
+            Hotkey::FastForward => self.fast_forward = true,
+            Hotkey::Microphone => self.mic_key = true,
+            Hotkey::KeyboardCapture => {
+                self.keyboard_captured = !self.keyboard_captured;
+                if let Some(keyboard) = self.nes.device_mut::<FamilyKeyboard>(2) {
+                    keyboard.release_all();
+                    println!("Keys go to {}", if self.keyboard_captured { "the Family BASIC keyboard" } else { "the controllers and hotkeys" });
+                }
+            },
+            Hotkey::SoftReset => self.commands |= movie::SOFT_RESET,
+            Hotkey::HardReset => self.commands |= movie::HARD_RESET,
+            Hotkey::Screenshot => self.screenshot(),
+            Hotkey::Filter => {
+                self.filter_kind = self.filter_kind.next();
+                self.filter = self.filter_kind.create();
+            },
+            Hotkey::Fullscreen => toggle_fullscreen(self.canvas.window_mut()),
+            Hotkey::SaveState(slot) => self.save_slot(slot),
+            Hotkey::LoadState(slot) => self.load_slot(slot),
+            Hotkey::Rebind(port) => {
+                let rebinding = Rebinding::new(port);
+                self.show_prompt(&rebinding.prompt());
+                self.rebinding = Some(rebinding);
+            },
+            Hotkey::RecordMacro(port) => {
+                if self.input.toggle_macro_recording(port) {
+                    println!("Recording macro for player {}", port + 1);
+                } else {
+                    println!("Recorded {} frame macro for player {}", self.input.macro_frames(port).len(), port + 1);
+                }
+            },
+            Hotkey::PlayMacro(port) => {
+                if !self.input.play_macro(port) {
+                    println!("No macro recorded for player {}", port + 1);
+                }
+            },
+        }
+    }
+
+    /* Escape cancels, the keys bound so far are kept */
+    fn rebind_key(&mut self, keycode: Keycode) {
+        let Some(rebinding) = self.rebinding.as_mut() else { return };
+
+        let finished = keycode == Keycode::Escape
+            || rebinding.bind(&mut self.bindings, KeyBinding::new(&keycode.name()));
+        if !finished {
+            let prompt = rebinding.prompt();
+            self.show_prompt(&prompt);
+            return;
+        }
+
+        let port = rebinding.port;
+        self.rebinding = None;
+        self.key_map = KeyMap::new(&self.bindings).unwrap();
+        self.canvas.window_mut().set_title(TITLE).unwrap();
+        println!("Controls for player {}, add them to the config file to keep them:\n{}", port + 1, self.bindings.keys_section(port));
+    }
+
+    fn show_prompt(&mut self, prompt: &str) {
+        println!("{}", prompt);
+        self.canvas.window_mut().set_title(&format!("{} - {}", TITLE, prompt)).unwrap();
+    }
+
+    /* The frame as shown without the filter, game-1.png next to the ROM */
+    fn screenshot(&mut self) {
+        let Some(rom_path) = &self.rom_path else { return };
+        let path = (1..).map(|n| screenshot_path(rom_path, n)).find(|path| !Path::new(path).exists()).unwrap();
+
+        match png::save(&path, Frame::WIDTH, Frame::HEIGHT, &self.frame.data) {
+            Ok(()) => println!("Saved screenshot to {}", path),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    /* Goes back one snapshot and shows the frame that follows it */
+    fn rewind_frame(&mut self) {
+        let Some(rewind) = self.rewind.as_mut() else { return };
+
+        if rewind.step_back(&mut self.nes) {
+            self.rerecord();
+            self.start_frame();
+            self.nes.run_frame();
+            self.render();
+        } else {
+            thread::sleep(Duration::from_millis(16));
+        }
+    }
+
+    fn run_frame(&mut self) {
+        if !self.frame_in_progress {
+            self.start_frame();
+        }
+
+        match (self.debugger.as_mut(), self.trace.as_mut()) {
+            (Some(debugger), mut trace_file) => {
+                let symbols = &self.symbols;
+                let (frame_done, stop) = debugger.run_frame(&mut self.nes, |nes| {
+                    if let Some(file) = trace_file.as_mut() {
+                        writeln!(file, "{}", trace_with_symbols(&mut nes.cpu, symbols)).unwrap();
+                    }
+                });
+                if let Some(stop) = stop {
+                    if let Some(console) = self.console.as_ref() {
+                        println!("{}", describe_stop(&stop, &mut self.nes, &self.symbols));
+                        console.prompt();
+                    }
+                    if let Some(gdb) = self.gdb.as_mut() {
+                        gdb.report_stop(&stop);
+                    }
+                }
+                if !frame_done {
+                    self.frame_in_progress = true;
+                    return;
+                }
+            },
+            (None, Some(file)) => loop {
+                writeln!(file, "{}", trace_with_symbols(&mut self.nes.cpu, &self.symbols)).unwrap();
+                if self.nes.step() {
+                    break;
+                }
+            },
+            (None, None) => self.nes.run_frame(),
+        }
+        self.frame_in_progress = false;
+
+        if let Some(rewind) = self.rewind.as_mut() {
+            rewind.record(&self.nes);
+        }
+
+        if Some(self.nes.frame_count()) == self.frame_limit {
+            self.quit();
+        }
+    }
+
+    fn run_debugger_commands(&mut self) {
+        let Some(debugger) = self.debugger.as_mut() else { return };
+        if let Some(gdb) = self.gdb.as_mut() {
+            gdb.poll(debugger, &mut self.nes);
+        }
+        let Some(console) = self.console.as_mut() else { return };
+        while let Some(line) = console.next_command() {
+            match run_command(debugger, &mut self.nes, &line) {
+                Ok(output) if output.is_empty() => {},
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("{}", e),
+            }
+            if debugger.is_halted() {
+                console.prompt();
+            }
+        }
+    }
+
+    fn debugger_halted(&self) -> bool {
+        self.debugger.as_ref().is_some_and(|debugger| debugger.is_halted())
+    }
+
+    pub fn run(&mut self) {
+        self.run_debugger_commands();
+        if self.paused || self.rebinding.is_some() || self.debugger_halted() {
+            self.handle_user_input();
+            thread::sleep(Duration::from_millis(16));
+            return;
+        }
+
+        self.handle_user_input();
+
+        if self.rewinding {
+            self.rewind_frame();
+            return;
+        }
+
+        let frames = if self.fast_forward { FAST_FORWARD_SPEED } else { 1 };
+        for _ in 0..frames {
+            self.run_frame();
+            if self.debugger_halted() {
+                break;
+            }
+        }
+
+        self.render();
+    }
+}
+
+fn to_sdl_rect(rect: DisplayRect) -> Rect {
+    Rect::new(rect.x, rect.y, rect.width, rect.height)
+}
+
+/* game.nes -> game-1.png next to the ROM */
+fn screenshot_path(rom_path: &str, n: usize) -> String {
+    let path = Path::new(rom_path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}-{}.png", stem, n)).to_string_lossy().into_owned()
+}
+
+fn toggle_fullscreen(window: &mut Window) {
+    let fullscreen = match window.fullscreen_state() {
+        FullscreenType::Off => FullscreenType::Desktop,
+        _ => FullscreenType::Off,
+    };
+    window.set_fullscreen(fullscreen).unwrap();
+}
@@ -1,10 +1,9 @@
 use crate::emulator::cpu::Cpu;
 use crate::emulator::ppu::Ppu;
 use crate::emulator::bus::Bus;
-use crate::emulator::joypad::Joypad;
 use crate::emulator::memory::{ RAM, RAM_MIRRORS_END, 
     PPU_REGISTERS_MIRRORS_START, PPU_REGISTERS_MIRRORS_END, ROM, 
-    ROM_MIRRORS_END, PPU_STATUS, PPU_OAM_DATA, PPU_DATA, JOYPAD_1 };
+    ROM_MIRRORS_END, PPU_STATUS, PPU_OAM_DATA, PPU_DATA, JOYPAD_1, JOYPAD_2, JOYPAD_OPEN_BUS, MICROPHONE };
 
 impl Cpu {
     pub fn mem_read_debugging(&self, addr: u16) -> u8 {
@@ -25,7 +24,14 @@ impl Bus {
             RAM ..= RAM_MIRRORS_END => {
                 self.cpu_vram[(addr & 0b111_11111111) as usize]
             },
-            JOYPAD_1 => self.joypad.read_debugging(),
+            JOYPAD_1 => {
+                let microphone = if self.microphone { MICROPHONE } else { 0 };
+                self.port_1.read_debugging(&self.ppu) | microphone | JOYPAD_OPEN_BUS
+            },
+            JOYPAD_2 => {
+                let expansion = self.expansion.as_ref().map_or(0, |device| device.read_debugging(&self.ppu));
+                self.port_2.read_debugging(&self.ppu) | expansion | JOYPAD_OPEN_BUS
+            },
             
             PPU_STATUS => self.ppu.read_status_debugging(),
             PPU_OAM_DATA => self.ppu.read_oam_data(),
@@ -68,9 +74,3 @@ impl Ppu {
         }
     }
 }
-
-impl Joypad {
-    pub fn read_debugging(&self) -> u8 {
-        self.buffer
-    }
-}
\ No newline at end of file
@@ -0,0 +1,219 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use crate::emulator::opcodes::{ OPCODES, Code };
+use crate::emulator::addressing_modes::AddressingMode;
+use crate::helpers::symbols::Symbols;
+use crate::emulator::cdl;
+
+/* One decoded instruction, operand holds the raw byte or little endian word after the opcode */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub address: u16,
+    pub opcode: u8,
+    pub code: Code,
+    pub mode: AddressingMode,
+    pub operand: u16,
+    pub length: u16,
+}
+
+impl Instruction {
+    /* None when the slice ends before the operand does */
+    pub fn decode(bytes: &[u8], address: u16) -> Option<Instruction> {
+        let opcode = *bytes.first()?;
+        let op = OPCODES.get(&opcode)?;
+        let operand = match op.bytes {
+            1 => 0,
+            2 => *bytes.get(1)? as u16,
+            _ => u16::from_le_bytes([*bytes.get(1)?, *bytes.get(2)?]),
+        };
+
+        Some(Instruction { address, opcode, code: op.code, mode: op.mode, operand, length: op.bytes })
+    }
+
+    /* As named in Code's Display, unofficial opcodes start with a * */
+    pub fn mnemonic(&self) -> String {
+        self.code.to_string().trim_start().to_string()
+    }
+
+    pub fn is_unofficial(&self) -> bool {
+        self.mnemonic().starts_with('*')
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        let [lsb, msb] = self.operand.to_le_bytes();
+        [self.opcode, lsb, msb][..self.length as usize].to_vec()
+    }
+
+    /* Where a branch, JMP or JSR goes, indirect jumps are only known at run time */
+    pub fn target(&self) -> Option<u16> {
+        match self.mode {
+            AddressingMode::Relative => Some(self.address.wrapping_add(2).wrapping_add(self.operand as i8 as u16)),
+            AddressingMode::Jump => Some(self.operand),
+            _ => None,
+        }
+    }
+
+    pub fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.length)
+    }
+
+    /* The operand as written in assembly, e.g. #$10, ($20),Y or A */
+    pub fn operand_text(&self) -> String {
+        self.operand_text_with(&Symbols::default())
+    }
+
+    /* Like operand_text with names in place of the addresses that have one */
+    pub fn operand_text_with(&self, symbols: &Symbols) -> String {
+        self.operand_with(|address, zero_page| symbols.format_address(address, zero_page))
+    }
+
+    /* `address` formats the addresses, told whether they are zero page ones, so names and labels can go in */
+    fn operand_with(&self, address: impl Fn(u16, bool) -> String) -> String {
+        let operand = self.operand;
+        match self.mode {
+            AddressingMode::Implied | AddressingMode::NoneAddressing => String::new(),
+            AddressingMode::Accumulator => "A".to_string(),
+            AddressingMode::Immediate => format!("#${:02X}", operand),
+            AddressingMode::ZeroPage => address(operand, true),
+            AddressingMode::ZeroPage_X => format!("{},X", address(operand, true)),
+            AddressingMode::ZeroPage_Y => format!("{},Y", address(operand, true)),
+            AddressingMode::Absolute | AddressingMode::Jump => address(operand, false),
+            AddressingMode::Absolute_X => format!("{},X", address(operand, false)),
+            AddressingMode::Absolute_Y => format!("{},Y", address(operand, false)),
+            AddressingMode::Indirect_X => format!("({},X)", address(operand, true)),
+            AddressingMode::Indirect_Y => format!("({}),Y", address(operand, true)),
+            AddressingMode::JumpIndirect => format!("({})", address(operand, false)),
+            AddressingMode::Relative => address(self.target().unwrap(), false),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operand = self.operand_text();
+        if operand.is_empty() {
+            write!(f, "{}", self.mnemonic())
+        } else {
+            write!(f, "{} {}", self.mnemonic(), operand)
+        }
+    }
+}
+
+/* Decodes back to back from `base`, stops at the first instruction cut off by the end of the slice */
+pub fn disassemble(bytes: &[u8], base: u16) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while let Some(instruction) = Instruction::decode(&bytes[offset..], base.wrapping_add(offset as u16)) {
+        offset += instruction.length as usize;
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+fn label(address: u16) -> String {
+    format!("L{:04X}", address)
+}
+
+fn byte_list(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("${:02X}", byte)).collect::<Vec<_>>().join(",")
+}
+
+/* An instruction, or bytes the listing leaves as .byte */
+enum Chunk<'a> {
+    Code(Instruction),
+    Data(&'a [u8]),
+}
+
+/*
+    Source ca65 assembles back into the same bytes. Branch and jump targets
+    that start an instruction get an Lxxxx label. Absolute operands below
+    $100 are forced with a: so they are not shortened to zero page, and
+    unofficial opcodes are written as .byte since assemblers disagree on
+    their names.
+ */
+pub fn ca65_listing(bytes: &[u8], base: u16) -> String {
+    listing(bytes, base, |_| false)
+}
+
+/*
+    Like ca65_listing with the PRG flags of a code/data log for the bytes,
+    the ones the game only ever read are kept as .byte rather than decoded
+    as instructions. Bytes the log never saw are decoded like before.
+ */
+pub fn ca65_listing_logged(bytes: &[u8], base: u16, log: &[u8]) -> String {
+    listing(bytes, base, |offset| log.get(offset).is_some_and(|flags| cdl::is_data(*flags)))
+}
+
+fn chunks(bytes: &[u8], base: u16, is_data: impl Fn(usize) -> bool) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        if !is_data(offset) {
+            match Instruction::decode(&bytes[offset..], base.wrapping_add(offset as u16)) {
+                Some(instruction) if !(offset + 1..offset + instruction.length as usize).any(&is_data) => {
+                    offset += instruction.length as usize;
+                    chunks.push(Chunk::Code(instruction));
+                    continue;
+                },
+                Some(_) => {}, // The operand was read as data, so the opcode likely is too
+                None => {
+                    chunks.push(Chunk::Data(&bytes[offset..]));
+                    break;
+                },
+            }
+        }
+
+        let end = (offset + 1..bytes.len())
+            .find(|&i| !is_data(i) || i - offset == 8)
+            .unwrap_or(bytes.len());
+        chunks.push(Chunk::Data(&bytes[offset..end]));
+        offset = end;
+    }
+    chunks
+}
+
+fn listing(bytes: &[u8], base: u16, is_data: impl Fn(usize) -> bool) -> String {
+    let chunks = chunks(bytes, base, is_data);
+    let instructions: Vec<&Instruction> = chunks.iter()
+        .filter_map(|chunk| match chunk { Chunk::Code(instruction) => Some(instruction), Chunk::Data(_) => None })
+        .collect();
+    let starts: BTreeSet<u16> = instructions.iter().map(|instruction| instruction.address).collect();
+    let labels: BTreeSet<u16> = instructions.iter()
+        .filter_map(|instruction| instruction.target())
+        .filter(|target| starts.contains(target))
+        .collect();
+
+    let mut lines = vec![".setcpu \"6502\"".to_string(), format!(".org ${:04X}", base), String::new()];
+    for chunk in &chunks {
+        let instruction = match chunk {
+            Chunk::Code(instruction) => instruction,
+            Chunk::Data(bytes) => {
+                lines.push(format!("{:<8}.byte {}", "", byte_list(bytes)));
+                continue;
+            },
+        };
+
+        let text = if instruction.is_unofficial() {
+            format!(".byte {:<12} ; {}", byte_list(&instruction.bytes()), instruction)
+        } else {
+            let forced = matches!(instruction.mode, AddressingMode::Absolute | AddressingMode::Absolute_X | AddressingMode::Absolute_Y);
+            let operand = instruction.operand_with(|address, zero_page| {
+                if labels.contains(&address) {
+                    label(address)
+                } else if forced && address < 0x100 {
+                    format!("a:${:04X}", address)
+                } else if zero_page {
+                    format!("${:02X}", address)
+                } else {
+                    format!("${:04X}", address)
+                }
+            });
+            format!("{} {}", instruction.mnemonic(), operand).trim_end().to_string()
+        };
+
+        let name = if labels.contains(&instruction.address) { format!("{}:", label(instruction.address)) } else { String::new() };
+        lines.push(format!("{:<8}{}", name, text));
+    }
+
+    lines.join("\n") + "\n"
+}
@@ -1,3 +1,8 @@
 pub mod trace;
 pub mod debugging;
-pub mod cpu_helpers;
\ No newline at end of file
+pub mod cpu_helpers;
+pub mod checksum;
+pub mod png;
+pub mod disassembler;
+pub mod symbols;
+pub mod wav;
\ No newline at end of file
@@ -0,0 +1,61 @@
+use crate::helpers::checksum::{ crc32_update, adler32 };
+use std::fs;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const COLOR_TYPE_RGB: u8 = 2;
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+
+/*
+    Minimal 8 bit RGB PNG encoder, the image data is zlib wrapped but left
+    uncompressed (stored deflate blocks) so no compression library is needed
+    https://www.w3.org/TR/png/
+ */
+pub fn encode(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    assert_eq!(rgb.len(), width * height * 3, "RGB data does not match image size");
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, COLOR_TYPE_RGB, 0, 0, 0]);
+
+    /* Each scanline starts with its filter type, 0 is none */
+    let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+    for line in rgb.chunks(width * 3) {
+        raw.push(0);
+        raw.extend_from_slice(line);
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+pub fn save(path: &str, width: usize, height: usize, rgb: &[u8]) -> Result<(), String> {
+    fs::write(path, encode(width, height, rgb))
+        .map_err(|e| format!("Could not write PNG {}: {}", path, e))
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc32_update(crc32_update(0, kind), data).to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(MAX_STORED_BLOCK).collect() };
+
+    for (i, block) in blocks.iter().enumerate() {
+        let last = i == blocks.len() - 1;
+        out.push(last as u8);
+        out.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
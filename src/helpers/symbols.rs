@@ -0,0 +1,197 @@
+use std::collections::{ BTreeMap, HashMap };
+use std::fs;
+use std::path::Path;
+use crate::emulator::rom::PRG_ROM_PAGE_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolFormat {
+    Ca65Dbg,    // ld65 --dbgfile
+    FceuxNl,    // $C5F5#UpdatePlayer#comment
+    MesenMlb,   // P:05F5:UpdatePlayer:comment
+}
+
+impl SymbolFormat {
+    pub fn from_path(path: &str) -> Option<SymbolFormat> {
+        match Path::new(path).extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "dbg" => Some(SymbolFormat::Ca65Dbg),
+            "nl" => Some(SymbolFormat::FceuxNl),
+            "mlb" => Some(SymbolFormat::MesenMlb),
+            _ => None,
+        }
+    }
+}
+
+/*
+    Names for CPU addresses from the developer's own symbol files, used by
+    traces and the debugger. An address keeps the first name it is given,
+    except that cheap locals like @loop give way to a proper name.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Symbols {
+    names: BTreeMap<u16, String>,
+    addresses: HashMap<String, u16>,
+}
+
+impl Symbols {
+    /* prg_size places PRG ROM offsets, a 16 KiB PRG is mirrored at $8000 and $C000 */
+    pub fn parse(text: &str, format: SymbolFormat, prg_size: usize) -> Result<Symbols, String> {
+        let mut entries = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = match format {
+                SymbolFormat::Ca65Dbg => parse_dbg_line(line),
+                SymbolFormat::FceuxNl => parse_nl_line(line),
+                SymbolFormat::MesenMlb => parse_mlb_line(line, prg_size),
+            };
+            entries.extend(parsed.map_err(|e| format!("line {}: {}", i + 1, e))?);
+        }
+
+        let mut symbols = Symbols::default();
+        entries.sort_by_key(|(nested, _, _)| *nested);
+        for (_, address, name) in entries {
+            symbols.add(address, &name);
+        }
+        Ok(symbols)
+    }
+
+    pub fn load(path: &str, prg_size: usize) -> Result<Symbols, String> {
+        let format = SymbolFormat::from_path(path)
+            .ok_or(format!("Unknown symbol file {} (expected .dbg, .nl or .mlb)", path))?;
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read symbol file {}: {}", path, e))?;
+        Symbols::parse(&text, format, prg_size).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /* Later files only name the addresses earlier ones left unnamed */
+    pub fn load_all(paths: &[String], prg_size: usize) -> Result<Symbols, String> {
+        let mut symbols = Symbols::default();
+        for path in paths {
+            symbols.extend(Symbols::load(path, prg_size)?);
+        }
+        Ok(symbols)
+    }
+
+    pub fn add(&mut self, address: u16, name: &str) {
+        let replace = self.names.get(&address).is_none_or(|existing| existing.starts_with('@') && !name.starts_with('@'));
+        if replace {
+            self.names.insert(address, name.to_string());
+        }
+        self.addresses.entry(name.to_string()).or_insert(address);
+    }
+
+    pub fn extend(&mut self, other: Symbols) {
+        for (address, name) in other.names {
+            self.add(address, &name);
+        }
+    }
+
+    pub fn name(&self, address: u16) -> Option<&str> {
+        self.names.get(&address).map(String::as_str)
+    }
+
+    pub fn address(&self, name: &str) -> Option<u16> {
+        self.addresses.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /* The name, or $xx for zero page operands and $xxxx otherwise */
+    pub fn format_address(&self, address: u16, zero_page: bool) -> String {
+        match self.name(address) {
+            Some(name) => name.to_string(),
+            None if zero_page => format!("${:02X}", address),
+            None => format!("${:04X}", address),
+        }
+    }
+}
+
+/* Whether the name is local to a scope, the address and the name */
+type Entry = (bool, u16, String);
+
+fn parse_hex(text: &str) -> Result<u32, String> {
+    let hex = text.trim().trim_start_matches('$').trim_start_matches("0x");
+    u32::from_str_radix(hex, 16).map_err(|_| format!("invalid address '{}'", text.trim()))
+}
+
+/*
+    sym	id=3,name="UpdatePlayer",addrsize=absolute,scope=0,def=12,val=0xC5F5,seg=0,type=lab
+
+    Only labels have addresses, equates are often plain numbers. Symbols in
+    the outer scope come first so they win over a .proc's locals.
+ */
+fn parse_dbg_line(line: &str) -> Result<Vec<Entry>, String> {
+    let Some(fields) = line.strip_prefix("sym") else { return Ok(Vec::new()) };
+    let fields: HashMap<&str, &str> = fields.trim().split(',')
+        .filter_map(|field| field.split_once('='))
+        .collect();
+
+    if fields.get("type") != Some(&"lab") {
+        return Ok(Vec::new());
+    }
+    let (Some(name), Some(value)) = (fields.get("name"), fields.get("val")) else { return Ok(Vec::new()) };
+    let address = parse_hex(value)?;
+    if address > 0xFFFF {
+        return Ok(Vec::new());
+    }
+    let nested = fields.get("scope").is_some_and(|scope| *scope != "0");
+    Ok(vec![(nested, address as u16, name.trim_matches('"').to_string())])
+}
+
+/* $0300/10#Buffer#comment, the /size is optional */
+fn parse_nl_line(line: &str) -> Result<Vec<Entry>, String> {
+    if !line.starts_with('$') {
+        return Ok(Vec::new());
+    }
+    let mut fields = line.splitn(3, '#');
+    let address = fields.next().unwrap();
+    let address = parse_hex(address.split_once('/').map_or(address, |(address, _)| address))?;
+    let name = fields.next().unwrap_or("").trim();
+    if name.is_empty() || address > 0xFFFF {
+        return Ok(Vec::new());
+    }
+    Ok(vec![(false, address as u16, name.to_string())])
+}
+
+/*
+    Type:address[-end]:label[:comment], in Mesen 1 (P, R, ...) or Mesen 2 (NesPrgRom, ...) names.
+    Types the CPU can not see, like CHR ROM or palette RAM, are skipped.
+ */
+fn parse_mlb_line(line: &str, prg_size: usize) -> Result<Vec<Entry>, String> {
+    let mut fields = line.splitn(4, ':');
+    let kind = fields.next().unwrap();
+    let address = fields.next().ok_or("expected type:address:label")?;
+    let offset = parse_hex(address.split_once('-').map_or(address, |(start, _)| start))? as usize;
+    let name = fields.next().unwrap_or("").trim();
+    if name.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let addresses = match kind {
+        "P" | "NesPrgRom" => prg_addresses(offset, prg_size),
+        "R" | "NesInternalRam" => vec![(offset & 0x07FF) as u16],
+        "S" | "W" | "NesSaveRam" | "NesWorkRam" => vec![0x6000 + (offset & 0x1FFF) as u16],
+        "G" | "NesMemory" if offset <= 0xFFFF => vec![offset as u16],
+        _ => Vec::new(),
+    };
+    Ok(addresses.into_iter().map(|address| (false, address, name.to_string())).collect())
+}
+
+/* Where a PRG ROM offset shows up for the CPU, only NROM layouts are known */
+fn prg_addresses(offset: usize, prg_size: usize) -> Vec<u16> {
+    if prg_size <= PRG_ROM_PAGE_SIZE && offset < PRG_ROM_PAGE_SIZE {
+        vec![(0xC000 + offset) as u16, (0x8000 + offset) as u16]
+    } else if offset < 2 * PRG_ROM_PAGE_SIZE {
+        vec![(0x8000 + offset) as u16]
+    } else {
+        Vec::new()
+    }
+}
@@ -0,0 +1,35 @@
+use std::fs;
+
+/*
+    16 bit PCM mono WAV, samples are clamped to -1.0..=1.0
+    http://soundfile.sapp.org/doc/WaveFormat/
+ */
+pub fn encode(sample_rate: u32, samples: &[f32]) -> Vec<u8> {
+    let data_size = (samples.len() * 2) as u32;
+
+    let mut wav = Vec::with_capacity(44 + samples.len() * 2);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // Bytes per second
+    wav.extend_from_slice(&2u16.to_le_bytes()); // Bytes per sample
+    wav.extend_from_slice(&16u16.to_le_bytes());
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    wav
+}
+
+pub fn save(path: &str, sample_rate: u32, samples: &[f32]) -> Result<(), String> {
+    fs::write(path, encode(sample_rate, samples))
+        .map_err(|e| format!("Could not write WAV {}: {}", path, e))
+}
@@ -1,6 +1,8 @@
 pub mod emulator;
 pub mod player;
 pub mod helpers;
+pub mod cli;
+pub mod debugger;
 
 #[cfg(target_os = "emscripten")]
 pub mod emscripten;
\ No newline at end of file
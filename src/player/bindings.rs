@@ -0,0 +1,387 @@
+use std::fmt;
+use crate::cli::config::Config;
+use crate::emulator::joypad::Buttons;
+use crate::emulator::savestate;
+use crate::emulator::power_pad;
+use crate::player::gamepad::{ GamepadButton, GamepadSettings, PORTS };
+
+/* Key names are SDL key names, see https://wiki.libsdl.org/SDL2/SDL_Keycode */
+pub const DEFAULT_KEYS: [[(&str, Buttons); 8]; PORTS] = [
+    [
+        ("Up", Buttons::Up), ("Down", Buttons::Down), ("Left", Buttons::Left), ("Right", Buttons::Right),
+        ("Space", Buttons::Select), ("Return", Buttons::Start), ("B", Buttons::B), ("A", Buttons::A),
+    ],
+    [
+        ("I", Buttons::Up), ("K", Buttons::Down), ("J", Buttons::Left), ("L", Buttons::Right),
+        ("U", Buttons::Select), ("O", Buttons::Start), ("N", Buttons::B), ("M", Buttons::A),
+    ],
+    [
+        ("Keypad 8", Buttons::Up), ("Keypad 2", Buttons::Down), ("Keypad 4", Buttons::Left), ("Keypad 6", Buttons::Right),
+        ("Keypad 7", Buttons::Select), ("Keypad 9", Buttons::Start), ("Keypad 0", Buttons::B), ("Keypad .", Buttons::A),
+    ],
+    [
+        ("T", Buttons::Up), ("G", Buttons::Down), ("F", Buttons::Left), ("H", Buttons::Right),
+        ("R", Buttons::Select), ("Y", Buttons::Start), ("Z", Buttons::B), ("X", Buttons::A),
+    ],
+];
+
+/* Players 3 and 4 have no spare keys for turbo by default */
+pub const DEFAULT_TURBO_KEYS: [&[(&str, Buttons)]; PORTS] = [
+    &[("S", Buttons::A), ("V", Buttons::B)],
+    &[(".", Buttons::A), (",", Buttons::B)],
+    &[],
+    &[],
+];
+
+/*
+    Power Pad buttons 1 to 12, laid out like side B of the mat. They share keys
+    with player 2 and 4, which can not be plugged in at the same time.
+ */
+pub const DEFAULT_POWER_PAD_KEYS: [&str; power_pad::BUTTONS] = [
+    "5", "6", "7", "8",
+    "T", "Y", "U", "I",
+    "G", "H", "J", "K",
+];
+
+/* Config names, also the order buttons are asked for when rebinding */
+pub const BUTTON_NAMES: [(&str, Buttons); 8] = [
+    ("up", Buttons::Up), ("down", Buttons::Down), ("left", Buttons::Left), ("right", Buttons::Right),
+    ("select", Buttons::Select), ("start", Buttons::Start), ("b", Buttons::B), ("a", Buttons::A),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hotkey {
+    Quit,
+    Pause,
+    Rewind,
+    FastForward,
+    SoftReset,
+    HardReset,
+    Screenshot,
+    Filter,
+    Fullscreen,
+    SaveState(usize),
+    LoadState(usize),
+    Rebind(usize), // Port
+    RecordMacro(usize),
+    PlayMacro(usize),
+    Microphone,
+    KeyboardCapture,
+}
+
+impl Hotkey {
+    pub fn from_name(name: &str) -> Option<Hotkey> {
+        let numbered = |prefix: &str, max: usize| {
+            name.strip_prefix(prefix)
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|n| (1..=max).contains(n))
+        };
+
+        match name {
+            "quit" => Some(Hotkey::Quit),
+            "pause" => Some(Hotkey::Pause),
+            "rewind" => Some(Hotkey::Rewind),
+            "fast_forward" => Some(Hotkey::FastForward),
+            "soft_reset" => Some(Hotkey::SoftReset),
+            "hard_reset" => Some(Hotkey::HardReset),
+            "screenshot" => Some(Hotkey::Screenshot),
+            "filter" => Some(Hotkey::Filter),
+            "fullscreen" => Some(Hotkey::Fullscreen),
+            "microphone" => Some(Hotkey::Microphone),
+            "keyboard_capture" => Some(Hotkey::KeyboardCapture),
+            _ => numbered("save_state_", savestate::SLOTS).map(Hotkey::SaveState)
+                .or_else(|| numbered("load_state_", savestate::SLOTS).map(Hotkey::LoadState))
+                .or_else(|| numbered("rebind_", PORTS).map(|port| Hotkey::Rebind(port - 1)))
+                .or_else(|| numbered("record_macro_", PORTS).map(|port| Hotkey::RecordMacro(port - 1)))
+                .or_else(|| numbered("play_macro_", PORTS).map(|port| Hotkey::PlayMacro(port - 1))),
+        }
+    }
+
+    /* Held down rather than pressed once */
+    pub fn is_held(&self) -> bool {
+        matches!(self, Hotkey::Rewind | Hotkey::FastForward | Hotkey::Microphone)
+    }
+}
+
+/* A key and the modifiers that must be held with it, written like Ctrl+Shift+R */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub fn new(key: &str) -> Self {
+        KeyBinding { key: key.to_string(), ..Default::default() }
+    }
+
+    pub fn parse(text: &str) -> Result<KeyBinding, String> {
+        let mut binding = KeyBinding::default();
+        let mut rest = text.trim();
+
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            if lower.starts_with("ctrl+") {
+                binding.ctrl = true;
+                rest = &rest[5..];
+            } else if lower.starts_with("shift+") {
+                binding.shift = true;
+                rest = &rest[6..];
+            } else if lower.starts_with("alt+") {
+                binding.alt = true;
+                rest = &rest[4..];
+            } else {
+                break;
+            }
+        }
+
+        if rest.is_empty() {
+            return Err(format!("missing key in '{}'", text));
+        }
+        binding.key = rest.to_string();
+        Ok(binding)
+    }
+
+    /* Same key, SDL key names are not case sensitive */
+    pub fn same_key(&self, key: &str) -> bool {
+        self.key.eq_ignore_ascii_case(key)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/*
+    Keyboard and controller bindings, [keys.2] and [gamepad.2] are player 2.
+    In a config file:
+
+    [keys.1]
+    a = X, Z
+    turbo_a = S
+    [gamepad.1]
+    a = b
+    turbo_b = none
+    deadzone = 0.3
+    [power_pad]
+    1 = Q
+    [hotkeys]
+    fast_forward = Tab
+    screenshot = none
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    pub keys: Vec<(KeyBinding, usize, Buttons)>,
+    pub turbo_keys: Vec<(KeyBinding, usize, Buttons)>,
+    pub gamepads: [GamepadSettings; PORTS],
+    pub power_pad: Vec<(KeyBinding, usize)>, // Button 1 to 12
+    pub hotkeys: Vec<(KeyBinding, Hotkey)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let keys = DEFAULT_KEYS.iter().enumerate()
+            .flat_map(|(port, keys)| keys.iter().map(move |(key, button)| (KeyBinding::new(key), port, *button)))
+            .collect();
+        let turbo_keys = DEFAULT_TURBO_KEYS.iter().enumerate()
+            .flat_map(|(port, keys)| keys.iter().map(move |(key, button)| (KeyBinding::new(key), port, *button)))
+            .collect();
+        let power_pad = DEFAULT_POWER_PAD_KEYS.iter().enumerate()
+            .map(|(index, key)| (KeyBinding::new(key), index + 1))
+            .collect();
+
+        let mut hotkeys = vec![
+            ("Escape", Hotkey::Quit),
+            ("P", Hotkey::Pause),
+            ("Backspace", Hotkey::Rewind),
+            ("`", Hotkey::FastForward),
+            ("Ctrl+R", Hotkey::SoftReset),
+            ("Ctrl+Shift+R", Hotkey::HardReset),
+            ("F12", Hotkey::Screenshot),
+            ("Tab", Hotkey::Filter),
+            ("F11", Hotkey::Fullscreen),
+            ("Alt+Return", Hotkey::Fullscreen),
+            ("Ctrl+1", Hotkey::Rebind(0)),
+            ("Ctrl+2", Hotkey::Rebind(1)),
+            ("Ctrl+3", Hotkey::Rebind(2)),
+            ("Ctrl+4", Hotkey::Rebind(3)),
+            ("Ctrl+M", Hotkey::RecordMacro(0)),
+            ("Ctrl+Shift+M", Hotkey::PlayMacro(0)),
+            ("Home", Hotkey::Microphone),
+            ("ScrollLock", Hotkey::KeyboardCapture),
+        ].into_iter()
+            .map(|(key, hotkey)| (KeyBinding::parse(key).unwrap(), hotkey))
+            .collect::<Vec<_>>();
+        for slot in 1..=savestate::SLOTS {
+            let key = format!("F{}", slot);
+            hotkeys.push((KeyBinding::new(&key), Hotkey::SaveState(slot)));
+            hotkeys.push((KeyBinding { shift: true, ..KeyBinding::new(&key) }, Hotkey::LoadState(slot)));
+        }
+
+        Bindings {
+            keys,
+            turbo_keys,
+            gamepads: [GamepadSettings::default(); PORTS],
+            power_pad,
+            hotkeys,
+        }
+    }
+}
+
+impl Bindings {
+    /* Sections that are not in the file keep their defaults */
+    pub fn load(&mut self, config: &Config) -> Result<(), String> {
+        for port in 0..PORTS {
+            let section = format!("keys.{}", port + 1);
+            for (name, value) in config.entries(&section) {
+                let keys = parse_keys(value).map_err(|e| format!("[{}] {}", section, e))?;
+                if let Some(button) = turbo_button(name) {
+                    self.turbo_keys.retain(|(_, p, b)| !(*p == port && *b == button));
+                    self.turbo_keys.extend(keys.into_iter().map(|key| (key, port, button)));
+                    continue;
+                }
+                let button = button_from_name(name).ok_or(format!("[{}] unknown button '{}'", section, name))?;
+                self.set_keys(port, button, keys);
+            }
+
+            let section = format!("gamepad.{}", port + 1);
+            for (name, value) in config.entries(&section) {
+                self.set_gamepad(port, name, value).map_err(|e| format!("[{}] {}", section, e))?;
+            }
+        }
+
+        for (name, value) in config.entries("power_pad") {
+            let button = name.parse::<usize>().ok()
+                .filter(|button| (1..=power_pad::BUTTONS).contains(button))
+                .ok_or(format!("[power_pad] unknown button '{}'", name))?;
+            let keys = parse_keys(value).map_err(|e| format!("[power_pad] {}", e))?;
+            self.power_pad.retain(|(_, b)| *b != button);
+            self.power_pad.extend(keys.into_iter().map(|key| (key, button)));
+        }
+
+        for (name, value) in config.entries("hotkeys") {
+            let hotkey = Hotkey::from_name(name).ok_or(format!("[hotkeys] unknown action '{}'", name))?;
+            let keys = parse_keys(value).map_err(|e| format!("[hotkeys] {}", e))?;
+            self.hotkeys.retain(|(_, h)| *h != hotkey);
+            self.hotkeys.extend(keys.into_iter().map(|key| (key, hotkey)));
+        }
+
+        Ok(())
+    }
+
+    /* Replaces the keys for one button */
+    pub fn set_keys(&mut self, port: usize, button: Buttons, keys: Vec<KeyBinding>) {
+        self.keys.retain(|(_, p, b)| !(*p == port && *b == button));
+        self.keys.extend(keys.into_iter().map(|key| (key, port, button)));
+    }
+
+    pub fn keys_for(&self, port: usize, button: Buttons) -> Vec<&KeyBinding> {
+        self.keys.iter()
+            .filter(|(_, p, b)| *p == port && *b == button)
+            .map(|(key, _, _)| key)
+            .collect()
+    }
+
+    fn set_gamepad(&mut self, port: usize, name: &str, value: &str) -> Result<(), String> {
+        let settings = &mut self.gamepads[port];
+        if name == "deadzone" {
+            settings.deadzone = value.parse::<f32>().ok()
+                .filter(|deadzone| (0.0..=1.0).contains(deadzone))
+                .ok_or(format!("invalid deadzone '{}'", value))?;
+            return Ok(());
+        }
+
+        let button = match value {
+            "none" => None,
+            _ => Some(GamepadButton::from_name(value).ok_or(format!("unknown controller button '{}'", value))?),
+        };
+        let required = || button.ok_or(format!("'{}' can not be unbound", name));
+        match name {
+            "a" => settings.a = required()?,
+            "b" => settings.b = required()?,
+            "select" => settings.select = required()?,
+            "start" => settings.start = required()?,
+            "turbo_a" => settings.turbo_a = button,
+            "turbo_b" => settings.turbo_b = button,
+            _ => return Err(format!("unknown button '{}'", name)),
+        }
+        Ok(())
+    }
+
+    /* The [keys.N] section for a port, e.g. to paste into a config file after rebinding */
+    pub fn keys_section(&self, port: usize) -> String {
+        let mut lines = vec![format!("[keys.{}]", port + 1)];
+        for (name, button) in BUTTON_NAMES.iter() {
+            let keys: Vec<String> = self.keys_for(port, *button).iter().map(|key| key.to_string()).collect();
+            let keys = if keys.is_empty() { "none".to_string() } else { keys.join(", ") };
+            lines.push(format!("{} = {}", name, keys));
+        }
+        lines.join("\n")
+    }
+}
+
+pub fn button_from_name(name: &str) -> Option<Buttons> {
+    BUTTON_NAMES.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, button)| *button)
+}
+
+fn turbo_button(name: &str) -> Option<Buttons> {
+    match name {
+        "turbo_a" => Some(Buttons::A),
+        "turbo_b" => Some(Buttons::B),
+        _ => None,
+    }
+}
+
+/* Comma separated, 'none' unbinds */
+fn parse_keys(value: &str) -> Result<Vec<KeyBinding>, String> {
+    if value.trim().eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+    value.split(',').map(KeyBinding::parse).collect()
+}
+
+/*
+    In-app rebinding, asks for each button of a port in turn. The new key
+    replaces the old ones and is taken away from any other button.
+ */
+pub struct Rebinding {
+    pub port: usize,
+    index: usize,
+}
+
+impl Rebinding {
+    pub fn new(port: usize) -> Self {
+        Rebinding { port, index: 0 }
+    }
+
+    pub fn prompt(&self) -> String {
+        let (name, _) = BUTTON_NAMES[self.index];
+        format!("Press a key for player {} {} (Escape cancels)", self.port + 1, name)
+    }
+
+    /* Returns true once every button has a key */
+    pub fn bind(&mut self, bindings: &mut Bindings, key: KeyBinding) -> bool {
+        let (_, button) = BUTTON_NAMES[self.index];
+        bindings.keys.retain(|(k, _, _)| !k.same_key(&key.key));
+        bindings.turbo_keys.retain(|(k, _, _)| !k.same_key(&key.key));
+        bindings.set_keys(self.port, button, vec![key]);
+
+        self.index += 1;
+        self.index == BUTTON_NAMES.len()
+    }
+}
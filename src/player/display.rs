@@ -0,0 +1,123 @@
+use crate::emulator::frame::Frame;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Overscan {
+    pub top: usize,
+    pub bottom: usize,
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Overscan {
+    /* Roughly what a CRT television hides */
+    pub fn tv() -> Self {
+        Overscan { top: 8, bottom: 8, left: 0, right: 0 }
+    }
+}
+
+/* https://www.nesdev.org/wiki/Overscan#Pixel_aspect_ratio */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AspectRatio {
+    #[default]
+    Square,
+    PixelAspect8x7,
+    Display4x3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplaySettings {
+    pub overscan: Overscan,
+    pub aspect: AspectRatio,
+    pub integer_scaling: bool,
+    pub scale: f64,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings {
+            overscan: Overscan::default(),
+            aspect: AspectRatio::Square,
+            integer_scaling: false,
+            scale: 3.0,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /* Size of the frame in NES pixels after cropping the overscan */
+    pub fn visible_size(&self) -> (usize, usize) {
+        let overscan = self.overscan;
+        (
+            Frame::WIDTH.saturating_sub(overscan.left + overscan.right).max(1),
+            Frame::HEIGHT.saturating_sub(overscan.top + overscan.bottom).max(1),
+        )
+    }
+
+    /* Visible size at 1x after aspect ratio correction */
+    pub fn display_size(&self) -> (f64, f64) {
+        let (width, height) = self.visible_size();
+        let (width, height) = (width as f64, height as f64);
+
+        match self.aspect {
+            AspectRatio::Square => (width, height),
+            AspectRatio::PixelAspect8x7 => (width * 8.0 / 7.0, height),
+            AspectRatio::Display4x3 => (height * 4.0 / 3.0, height),
+        }
+    }
+
+    pub fn window_size(&self) -> (u32, u32) {
+        let (width, height) = self.display_size();
+        ((width * self.scale).round() as u32, (height * self.scale).round() as u32)
+    }
+
+    /* Part of a filter output of the given size that is left after cropping */
+    pub fn source_rect(&self, texture_width: usize, texture_height: usize) -> Rect {
+        let (width, height) = self.visible_size();
+        let scale_x = texture_width as f64 / Frame::WIDTH as f64;
+        let scale_y = texture_height as f64 / Frame::HEIGHT as f64;
+
+        Rect {
+            x: (self.overscan.left as f64 * scale_x).round() as i32,
+            y: (self.overscan.top as f64 * scale_y).round() as i32,
+            width: (width as f64 * scale_x).round() as u32,
+            height: (height as f64 * scale_y).round() as u32,
+        }
+    }
+
+    /* Largest area with the display aspect ratio that fits the window, centered */
+    pub fn viewport(&self, window_width: u32, window_height: u32) -> Rect {
+        let (width, height) = self.display_size();
+        let fit = (window_width as f64 / width).min(window_height as f64 / height);
+        let scale = if self.integer_scaling { fit.floor().max(1.0) } else { fit };
+
+        let (width, height) = ((width * scale).round() as u32, (height * scale).round() as u32);
+        Rect {
+            x: (window_width as i32 - width as i32) / 2,
+            y: (window_height as i32 - height as i32) / 2,
+            width,
+            height,
+        }
+    }
+
+    /* NES pixel under a point in the window, None outside the viewport */
+    pub fn frame_position(&self, viewport: Rect, x: i32, y: i32) -> Option<(usize, usize)> {
+        let (x, y) = (x - viewport.x, y - viewport.y);
+        if x < 0 || y < 0 || x >= viewport.width as i32 || y >= viewport.height as i32 {
+            return None;
+        }
+
+        let (width, height) = self.visible_size();
+        Some((
+            self.overscan.left + x as usize * width / viewport.width as usize,
+            self.overscan.top + y as usize * height / viewport.height as usize,
+        ))
+    }
+}
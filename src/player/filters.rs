@@ -0,0 +1,530 @@
+use crate::emulator::frame::Frame;
+use crate::player::ntsc::NtscFilter;
+use crate::emulator::palette::{ NtscParams, Rgb };
+
+const RGB_DATA_LEN: usize = Frame::RGB_DATA_LEN;
+
+pub trait VideoFilter {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize);
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterKind {
+    None,
+    Ntsc,
+    Scale2x,
+    Scale3x,
+    Hq2x,
+    Xbrz2x,
+    Xbrz3x,
+    Scanlines,
+    Crt,
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 9] = [
+        FilterKind::None, FilterKind::Ntsc,
+        FilterKind::Scale2x, FilterKind::Scale3x, FilterKind::Hq2x,
+        FilterKind::Xbrz2x, FilterKind::Xbrz3x,
+        FilterKind::Scanlines, FilterKind::Crt,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterKind::None => "none",
+            FilterKind::Ntsc => "ntsc",
+            FilterKind::Scale2x => "scale2x",
+            FilterKind::Scale3x => "scale3x",
+            FilterKind::Hq2x => "hq2x",
+            FilterKind::Xbrz2x => "xbrz2x",
+            FilterKind::Xbrz3x => "xbrz3x",
+            FilterKind::Scanlines => "scanlines",
+            FilterKind::Crt => "crt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FilterKind> {
+        FilterKind::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+
+    pub fn next(&self) -> FilterKind {
+        let idx = FilterKind::ALL.iter().position(|kind| kind == self).unwrap();
+        FilterKind::ALL[(idx + 1) % FilterKind::ALL.len()]
+    }
+
+    pub fn create(&self) -> Box<dyn VideoFilter> {
+        match self {
+            FilterKind::None => Box::new(Nearest),
+            FilterKind::Ntsc => Box::new(NtscFilter::new(NtscParams::default())),
+            FilterKind::Scale2x => Box::new(ScaleNx { scale: 2 }),
+            FilterKind::Scale3x => Box::new(ScaleNx { scale: 3 }),
+            FilterKind::Hq2x => Box::new(Hq2x),
+            FilterKind::Xbrz2x => Box::new(Xbrz { scale: 2 }),
+            FilterKind::Xbrz3x => Box::new(Xbrz { scale: 3 }),
+            FilterKind::Scanlines => Box::new(Scanlines { intensity: 0.5 }),
+            FilterKind::Crt => Box::new(CrtMask { intensity: 0.7 }),
+        }
+    }
+}
+
+/* Read only view of Frame::data with clamped edges */
+struct Source<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Source<'a> {
+    fn new(frame: &'a Frame) -> Self {
+        Source { data: &frame.data, width: Frame::WIDTH, height: Frame::HEIGHT }
+    }
+
+    fn get(&self, x: isize, y: isize) -> Rgb {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        let base = (y * self.width + x) * RGB_DATA_LEN;
+        (self.data[base], self.data[base + 1], self.data[base + 2])
+    }
+}
+
+struct Output<'a> {
+    data: &'a mut Vec<u8>,
+    width: usize,
+}
+
+impl<'a> Output<'a> {
+    fn new(data: &'a mut Vec<u8>, width: usize, height: usize) -> Self {
+        data.resize(width * height * RGB_DATA_LEN, 0);
+        Output { data, width }
+    }
+
+    fn get(&self, x: usize, y: usize) -> Rgb {
+        let base = (y * self.width + x) * RGB_DATA_LEN;
+        (self.data[base], self.data[base + 1], self.data[base + 2])
+    }
+
+    fn set(&mut self, x: usize, y: usize, rgb: Rgb) {
+        let base = (y * self.width + x) * RGB_DATA_LEN;
+        self.data[base] = rgb.0;
+        self.data[base + 1] = rgb.1;
+        self.data[base + 2] = rgb.2;
+    }
+}
+
+fn scale_rgb(rgb: Rgb, factor: f64) -> Rgb {
+    let scale = |value: u8| (value as f64 * factor).round().min(255.0) as u8;
+    (scale(rgb.0), scale(rgb.1), scale(rgb.2))
+}
+
+/* (e * we + a * wa + b * wb) / (we + wa + wb) on each channel */
+fn mix(e: Rgb, a: Rgb, b: Rgb, (we, wa, wb): (u32, u32, u32)) -> Rgb {
+    let mix = |e: u8, a: u8, b: u8| ((e as u32 * we + a as u32 * wa + b as u32 * wb) / (we + wa + wb)) as u8;
+    (mix(e.0, a.0, b.0), mix(e.1, a.1, b.1), mix(e.2, a.2, b.2))
+}
+
+/*
+    Filters below are written for one corner of a pixel, where a neighbour
+    offset or a block position comes from after turning a quarter clockwise
+    `turns` times covers the other corners.
+ */
+fn rotate(dx: isize, dy: isize, turns: usize) -> (isize, isize) {
+    (0..turns).fold((dx, dy), |(dx, dy), _| (dy, -dx))
+}
+
+/* Position inside a scale x scale block, rotated around its centre */
+fn rotate_block(u: usize, v: usize, scale: usize, turns: usize) -> (usize, usize) {
+    let offset = scale as isize - 1;
+    let (p, q) = rotate(2 * u as isize - offset, 2 * v as isize - offset, turns);
+    (((p + offset) / 2) as usize, ((q + offset) / 2) as usize)
+}
+
+pub struct Nearest;
+
+impl VideoFilter for Nearest {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width, height)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        output.clear();
+        output.extend_from_slice(&frame.data);
+    }
+}
+
+/* https://www.scale2x.it/algorithm */
+pub struct ScaleNx {
+    pub scale: usize,
+}
+
+impl VideoFilter for ScaleNx {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width * self.scale, height * self.scale)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        let source = Source::new(frame);
+        let (width, height) = self.output_size(source.width, source.height);
+        let mut out = Output::new(output, width, height);
+
+        for y in 0..source.height {
+            for x in 0..source.width {
+                let (x, y) = (x as isize, y as isize);
+                let [a, b, c, d, e, f, g, h, i] = [
+                    source.get(x - 1, y - 1), source.get(x, y - 1), source.get(x + 1, y - 1),
+                    source.get(x - 1, y), source.get(x, y), source.get(x + 1, y),
+                    source.get(x - 1, y + 1), source.get(x, y + 1), source.get(x + 1, y + 1),
+                ];
+
+                let mut block = [e; 9];
+                if b != h && d != f && self.scale == 2 {
+                    block[..4].copy_from_slice(&[
+                        if d == b { d } else { e }, if b == f { f } else { e },
+                        if d == h { d } else { e }, if h == f { f } else { e },
+                    ]);
+                } else if b != h && d != f {
+                    block = [
+                        if d == b { d } else { e },
+                        if (d == b && e != c) || (b == f && e != a) { b } else { e },
+                        if b == f { f } else { e },
+                        if (d == b && e != g) || (d == h && e != a) { d } else { e },
+                        e,
+                        if (b == f && e != i) || (h == f && e != c) { f } else { e },
+                        if d == h { d } else { e },
+                        if (d == h && e != i) || (h == f && e != g) { h } else { e },
+                        if h == f { f } else { e },
+                    ];
+                }
+
+                let (x, y) = (x as usize * self.scale, y as usize * self.scale);
+                for (n, &rgb) in block[..self.scale * self.scale].iter().enumerate() {
+                    out.set(x + n % self.scale, y + n / self.scale, rgb);
+                }
+            }
+        }
+    }
+}
+
+/*
+    https://en.wikipedia.org/wiki/Hqx, the 256 case switch of the reference hq2x
+    folded into a rule per pattern for the top left pixel of the block (byuu's
+    port does the same). Bits are set for the neighbours differing from E:
+
+        A B C      bit 0 1 2
+        D E F          3 . 4
+        G H I          5 6 7
+ */
+const HQ2X_RULES: [u8; 256] = [
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 15, 12, 5,  3, 17, 13,
+    4, 4, 6, 18, 4, 4, 6, 18, 5,  3, 12, 12, 5,  3,  1, 12,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 17, 13, 5,  3, 16, 14,
+    4, 4, 6, 18, 4, 4, 6, 18, 5,  3, 16, 12, 5,  3,  1, 14,
+    4, 4, 6,  2, 4, 4, 6,  2, 5, 19, 12, 12, 5, 19, 16, 12,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 16, 12, 5,  3, 16, 12,
+    4, 4, 6,  2, 4, 4, 6,  2, 5, 19,  1, 12, 5, 19,  1, 14,
+    4, 4, 6,  2, 4, 4, 6, 18, 5,  3, 16, 12, 5, 19,  1, 14,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 15, 12, 5,  3, 17, 13,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 16, 12, 5,  3, 16, 12,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 17, 13, 5,  3, 16, 14,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 16, 13, 5,  3,  1, 14,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 16, 12, 5,  3, 16, 13,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 16, 12, 5,  3,  1, 12,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3, 16, 12, 5,  3,  1, 14,
+    4, 4, 6,  2, 4, 4, 6,  2, 5,  3,  1, 12, 5,  3,  1, 14,
+];
+
+/* The YUV conversion and thresholds of the reference code */
+fn hqx_differs(a: Rgb, b: Rgb) -> bool {
+    let yuv = |(r, g, b): Rgb| {
+        let (r, g, b) = (r as i32, g as i32, b as i32);
+        ((r + g + b) >> 2, (r - b) >> 2, (2 * g - r - b) >> 3)
+    };
+    let ((y1, u1, v1), (y2, u2, v2)) = (yuv(a), yuv(b));
+    (y1 - y2).abs() > 0x30 || (u1 - u2).abs() > 0x07 || (v1 - v2).abs() > 0x06
+}
+
+/* The reference Interp1 to Interp10 blends picked by each rule */
+fn hq2x_pixel(rule: u8, [a, b, d, e, f, h]: [Rgb; 6]) -> Rgb {
+    let same = |x, y| !hqx_differs(x, y);
+    match rule {
+        1 => mix(e, a, a, (3, 1, 0)),
+        2 => mix(e, d, d, (3, 1, 0)),
+        3 => mix(e, b, b, (3, 1, 0)),
+        4 => mix(e, d, b, (2, 1, 1)),
+        5 => mix(e, a, b, (2, 1, 1)),
+        6 => mix(e, a, d, (2, 1, 1)),
+        12 | 15 if same(b, d) => mix(e, d, b, (2, 1, 1)),
+        13 | 17 if same(b, d) => mix(e, d, b, (2, 3, 3)),
+        14 if same(b, d) => mix(e, d, b, (14, 1, 1)),
+        16 if same(b, d) => mix(e, d, b, (6, 1, 1)),
+        15..=17 => mix(e, a, a, (3, 1, 0)),
+        18 if same(b, f) => mix(e, b, d, (5, 2, 1)),
+        18 => mix(e, d, d, (3, 1, 0)),
+        19 if same(d, h) => mix(e, d, b, (5, 2, 1)),
+        19 => mix(e, b, b, (3, 1, 0)),
+        _ => e,
+    }
+}
+
+pub struct Hq2x;
+
+impl VideoFilter for Hq2x {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width * 2, height * 2)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        let source = Source::new(frame);
+        let (width, height) = self.output_size(source.width, source.height);
+        let mut out = Output::new(output, width, height);
+
+        for y in 0..source.height {
+            for x in 0..source.width {
+                let e = source.get(x as isize, y as isize);
+
+                for turns in 0..4 {
+                    let at = |dx, dy| {
+                        let (dx, dy) = rotate(dx, dy, turns);
+                        source.get(x as isize + dx, y as isize + dy)
+                    };
+                    let neighbours = [at(-1, -1), at(0, -1), at(1, -1), at(-1, 0), at(1, 0), at(-1, 1), at(0, 1), at(1, 1)];
+                    let pattern = neighbours.iter().enumerate()
+                        .fold(0, |pattern, (bit, &rgb)| pattern | (hqx_differs(e, rgb) as usize) << bit);
+                    let [a, b, _, d, f, _, h, _] = neighbours;
+
+                    let (u, v) = rotate_block(0, 0, 2, turns);
+                    out.set(x * 2 + u, y * 2 + v, hq2x_pixel(HQ2X_RULES[pattern], [a, b, d, e, f, h]));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Blend {
+    None,
+    Normal,
+    Dominant,
+}
+
+/* xBRZ's default ScalerCfg */
+const XBRZ_EQUAL_COLOR_TOLERANCE: f64 = 30.0;
+const XBRZ_DOMINANT_DIRECTION_THRESHOLD: f64 = 3.6;
+const XBRZ_STEEP_DIRECTION_THRESHOLD: f64 = 2.2;
+
+/* YCbCr distance with the BT.2020 weights and a luminance weight of 1 */
+fn xbrz_distance(a: Rgb, b: Rgb) -> f64 {
+    let (r, g, b) = (a.0 as f64 - b.0 as f64, a.1 as f64 - b.1 as f64, a.2 as f64 - b.2 as f64);
+    let y = 0.2627 * r + 0.6780 * g + 0.0593 * b;
+    let cb = 0.5 / (1.0 - 0.0593) * (b - y);
+    let cr = 0.5 / (1.0 - 0.2627) * (r - y);
+    (y * y + cb * cb + cr * cr).sqrt()
+}
+
+/*
+    How the corner between F, G, J and K is blended into each of them,
+    F being the pixel at (x, y):
+
+        A B C D
+        E F G H
+        I J K L
+        M N O P
+ */
+fn xbrz_corner(source: &Source, x: isize, y: isize) -> [Blend; 4] {
+    let at = |dx, dy| source.get(x + dx, y + dy);
+    let (b, c) = (at(0, -1), at(1, -1));
+    let (e, f, g, h) = (at(-1, 0), at(0, 0), at(1, 0), at(2, 0));
+    let (i, j, k, l) = (at(-1, 1), at(0, 1), at(1, 1), at(2, 1));
+    let (n, o) = (at(0, 2), at(1, 2));
+
+    let mut result = [Blend::None; 4];
+    if (f == g && j == k) || (f == j && g == k) {
+        return result;
+    }
+
+    let dist = xbrz_distance;
+    let jg = dist(i, f) + dist(f, c) + dist(n, k) + dist(k, h) + 4.0 * dist(j, g);
+    let fk = dist(e, j) + dist(j, o) + dist(b, g) + dist(g, l) + 4.0 * dist(f, k);
+    let blend = |strong: f64, weak: f64| if XBRZ_DOMINANT_DIRECTION_THRESHOLD * strong < weak { Blend::Dominant } else { Blend::Normal };
+
+    if jg < fk {
+        if f != g && f != j {
+            result[0] = blend(jg, fk);
+        }
+        if k != j && k != g {
+            result[3] = blend(jg, fk);
+        }
+    } else if fk < jg {
+        if g != f && g != k {
+            result[1] = blend(fk, jg);
+        }
+        if j != f && j != k {
+            result[2] = blend(fk, jg);
+        }
+    }
+    result
+}
+
+/* https://sourceforge.net/projects/xbrz/, the 2x and 3x scalers */
+pub struct Xbrz {
+    pub scale: usize,
+}
+
+impl Xbrz {
+    /* Blends the bottom right corner of the block of (x, y) turned a quarter `turns` times */
+    fn blend_corner(&self, source: &Source, out: &mut Output, x: usize, y: usize, corners: [Blend; 4], turns: usize) {
+        /* Top left, top right, bottom right and bottom left corners after the turn */
+        let corner = |n: usize| corners[(n + 4 - turns) % 4];
+        if corner(2) == Blend::None {
+            return;
+        }
+
+        let at = |dx, dy| {
+            let (dx, dy) = rotate(dx, dy, turns);
+            source.get(x as isize + dx, y as isize + dy)
+        };
+        let (b, c) = (at(0, -1), at(1, -1));
+        let (d, e, f) = (at(-1, 0), at(0, 0), at(1, 0));
+        let (g, h, i) = (at(-1, 1), at(0, 1), at(1, 1));
+
+        let dist = xbrz_distance;
+        let eq = |p, q| dist(p, q) < XBRZ_EQUAL_COLOR_TOLERANCE;
+        let line = corner(2) == Blend::Dominant || !(
+            (corner(1) != Blend::None && !eq(e, g))
+            || (corner(3) != Blend::None && !eq(e, c))
+            || (!eq(e, i) && eq(g, h) && eq(h, i) && eq(i, f) && eq(f, c))
+        );
+        let shallow = line && XBRZ_STEEP_DIRECTION_THRESHOLD * dist(f, g) <= dist(h, c) && e != g && d != g;
+        let steep = line && XBRZ_STEEP_DIRECTION_THRESHOLD * dist(h, c) <= dist(f, g) && e != c && b != c;
+
+        /* (row, column, alpha) for each pixel of the block the closer of F and H is blended into */
+        let writes: &[(usize, usize, (u32, u32))] = match (self.scale, line, shallow, steep) {
+            (2, false, _, _) => &[(1, 1, (21, 100))],
+            (2, _, true, true) => &[(1, 0, (1, 4)), (0, 1, (1, 4)), (1, 1, (5, 6))],
+            (2, _, true, false) => &[(1, 0, (1, 4)), (1, 1, (3, 4))],
+            (2, _, false, true) => &[(0, 1, (1, 4)), (1, 1, (3, 4))],
+            (2, _, false, false) => &[(1, 1, (1, 2))],
+            (_, false, _, _) => &[(2, 2, (45, 100))],
+            (_, _, true, true) => &[(2, 0, (1, 4)), (0, 2, (1, 4)), (2, 1, (3, 4)), (1, 2, (3, 4)), (2, 2, (1, 1))],
+            (_, _, true, false) => &[(2, 0, (1, 4)), (1, 2, (1, 4)), (2, 1, (3, 4)), (2, 2, (1, 1))],
+            (_, _, false, true) => &[(0, 2, (1, 4)), (2, 1, (1, 4)), (1, 2, (3, 4)), (2, 2, (1, 1))],
+            (_, _, false, false) => &[(1, 2, (1, 8)), (2, 1, (1, 8)), (2, 2, (7, 8))],
+        };
+
+        let px = if dist(e, f) <= dist(e, h) { f } else { h };
+        for &(row, column, (alpha, total)) in writes {
+            let (u, v) = rotate_block(column, row, self.scale, turns);
+            let (x, y) = (x * self.scale + u, y * self.scale + v);
+            out.set(x, y, mix(out.get(x, y), px, px, (total - alpha, alpha, 0)));
+        }
+    }
+}
+
+impl VideoFilter for Xbrz {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width * self.scale, height * self.scale)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        let source = Source::new(frame);
+        let (width, height) = self.output_size(source.width, source.height);
+        let mut out = Output::new(output, width, height);
+        let (columns, rows) = (source.width, source.height);
+
+        /* Corners on the top and left edges of the frame are never blended, as in xBRZ */
+        let mut corners = vec![[Blend::None; 4]; columns * rows];
+        for y in 0..rows {
+            for x in 0..columns {
+                let [f, g, j, k] = xbrz_corner(&source, x as isize, y as isize);
+                corners[y * columns + x][2] = f;
+                if x + 1 < columns {
+                    corners[y * columns + x + 1][3] = g;
+                }
+                if y + 1 < rows {
+                    corners[(y + 1) * columns + x][1] = j;
+                }
+                if x + 1 < columns && y + 1 < rows {
+                    corners[(y + 1) * columns + x + 1][0] = k;
+                }
+            }
+        }
+
+        for y in 0..rows {
+            for x in 0..columns {
+                let e = source.get(x as isize, y as isize);
+                for u in 0..self.scale {
+                    for v in 0..self.scale {
+                        out.set(x * self.scale + u, y * self.scale + v, e);
+                    }
+                }
+
+                let blends = corners[y * columns + x];
+                if blends.iter().any(|&blend| blend != Blend::None) {
+                    for turns in 0..4 {
+                        self.blend_corner(&source, &mut out, x, y, blends, turns);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/* Doubles the frame and darkens every other line */
+pub struct Scanlines {
+    pub intensity: f64,
+}
+
+impl VideoFilter for Scanlines {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width * 2, height * 2)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        let source = Source::new(frame);
+        let (width, height) = self.output_size(source.width, source.height);
+        let mut out = Output::new(output, width, height);
+
+        for y in 0..source.height {
+            for x in 0..source.width {
+                let rgb = source.get(x as isize, y as isize);
+                let dark = scale_rgb(rgb, self.intensity);
+
+                out.set(x * 2, y * 2, rgb);
+                out.set(x * 2 + 1, y * 2, rgb);
+                out.set(x * 2, y * 2 + 1, dark);
+                out.set(x * 2 + 1, y * 2 + 1, dark);
+            }
+        }
+    }
+}
+
+/* Triples the frame with an aperture grille (one column per phosphor) and scanline gaps */
+pub struct CrtMask {
+    pub intensity: f64,
+}
+
+impl VideoFilter for CrtMask {
+    fn output_size(&self, width: usize, height: usize) -> (usize, usize) {
+        (width * 3, height * 3)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        let source = Source::new(frame);
+        let (width, height) = self.output_size(source.width, source.height);
+        let mut out = Output::new(output, width, height);
+        let dim = |value: u8, lit: bool| if lit { value } else { (value as f64 * self.intensity) as u8 };
+
+        for y in 0..source.height {
+            for x in 0..source.width {
+                let (r, g, b) = source.get(x as isize, y as isize);
+
+                for u in 0..3 {
+                    let column = (dim(r, u == 0), dim(g, u == 1), dim(b, u == 2));
+                    out.set(x * 3 + u, y * 3, column);
+                    out.set(x * 3 + u, y * 3 + 1, column);
+                    out.set(x * 3 + u, y * 3 + 2, scale_rgb(column, self.intensity));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::emulator::joypad::Buttons;
+
+pub const DEFAULT_DEADZONE: f32 = 0.25;
+pub const PORTS: usize = 4; // Players 3 and 4 need a Four Score
+
+/* Controller buttons, named like in SDL game controller mappings */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "a" => Some(GamepadButton::A),
+            "b" => Some(GamepadButton::B),
+            "x" => Some(GamepadButton::X),
+            "y" => Some(GamepadButton::Y),
+            "back" => Some(GamepadButton::Back),
+            "guide" => Some(GamepadButton::Guide),
+            "start" => Some(GamepadButton::Start),
+            "leftstick" => Some(GamepadButton::LeftStick),
+            "rightstick" => Some(GamepadButton::RightStick),
+            "leftshoulder" => Some(GamepadButton::LeftShoulder),
+            "rightshoulder" => Some(GamepadButton::RightShoulder),
+            "dpup" => Some(GamepadButton::DPadUp),
+            "dpdown" => Some(GamepadButton::DPadDown),
+            "dpleft" => Some(GamepadButton::DPadLeft),
+            "dpright" => Some(GamepadButton::DPadRight),
+            _ => None,
+        }
+    }
+}
+
+/*
+    The d-pad and left stick drive the NES d-pad. NES A and B default to the
+    east and south buttons so they sit like on the original controller.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GamepadSettings {
+    pub a: GamepadButton,
+    pub b: GamepadButton,
+    pub select: GamepadButton,
+    pub start: GamepadButton,
+    pub turbo_a: Option<GamepadButton>,
+    pub turbo_b: Option<GamepadButton>,
+    pub deadzone: f32, // Fraction of the stick range that is ignored
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        GamepadSettings {
+            a: GamepadButton::B,
+            b: GamepadButton::A,
+            select: GamepadButton::Back,
+            start: GamepadButton::Start,
+            turbo_a: Some(GamepadButton::Y),
+            turbo_b: Some(GamepadButton::X),
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
+}
+
+impl GamepadSettings {
+    pub fn map(&self, button: GamepadButton) -> Buttons {
+        let mut buttons = match button {
+            GamepadButton::DPadUp => Buttons::Up,
+            GamepadButton::DPadDown => Buttons::Down,
+            GamepadButton::DPadLeft => Buttons::Left,
+            GamepadButton::DPadRight => Buttons::Right,
+            _ => Buttons::empty(),
+        };
+        for (bound, nes_button) in [(self.a, Buttons::A), (self.b, Buttons::B), (self.select, Buttons::Select), (self.start, Buttons::Start)] {
+            if button == bound {
+                buttons |= nes_button;
+            }
+        }
+        buttons
+    }
+
+    pub fn map_turbo(&self, button: GamepadButton) -> Buttons {
+        let mut buttons = Buttons::empty();
+        if self.turbo_a == Some(button) {
+            buttons |= Buttons::A;
+        }
+        if self.turbo_b == Some(button) {
+            buttons |= Buttons::B;
+        }
+        buttons
+    }
+
+    pub fn stick_buttons(&self, x: i16, y: i16) -> Buttons {
+        let threshold = (self.deadzone.clamp(0.0, 1.0) * i16::MAX as f32) as i32;
+        let mut buttons = Buttons::empty();
+
+        match x as i32 {
+            x if x < -threshold => buttons |= Buttons::Left,
+            x if x > threshold => buttons |= Buttons::Right,
+            _ => {},
+        }
+        match y as i32 { // SDL axes point down
+            y if y < -threshold => buttons |= Buttons::Up,
+            y if y > threshold => buttons |= Buttons::Down,
+            _ => {},
+        }
+
+        buttons
+    }
+}
+
+/* One connected controller, `port` is None when all ports are taken */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    pub port: Option<usize>,
+    held: Buttons,
+    turbo: Buttons,
+    x: i16,
+    y: i16,
+}
+
+impl GamepadState {
+    pub fn new(port: Option<usize>) -> Self {
+        GamepadState { port, ..Default::default() }
+    }
+
+    pub fn press(&mut self, button: GamepadButton, settings: &GamepadSettings) {
+        self.held.insert(settings.map(button));
+        self.turbo.insert(settings.map_turbo(button));
+    }
+
+    pub fn release(&mut self, button: GamepadButton, settings: &GamepadSettings) {
+        self.held.remove(settings.map(button));
+        self.turbo.remove(settings.map_turbo(button));
+    }
+
+    pub fn move_stick(&mut self, x: Option<i16>, y: Option<i16>) {
+        self.x = x.unwrap_or(self.x);
+        self.y = y.unwrap_or(self.y);
+    }
+
+    pub fn buttons(&self, settings: &GamepadSettings) -> Buttons {
+        self.held | settings.stick_buttons(self.x, self.y)
+    }
+
+    pub fn turbo_buttons(&self) -> Buttons {
+        self.turbo
+    }
+}
+
+/* Controllers take the first free port in the order they are plugged in */
+pub fn free_port(used: impl Iterator<Item = Option<usize>>) -> Option<usize> {
+    let used: Vec<usize> = used.flatten().collect();
+    (0..PORTS).find(|port| !used.contains(port))
+}
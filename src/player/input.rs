@@ -0,0 +1,182 @@
+use crate::emulator::joypad::Buttons;
+use crate::player::gamepad::PORTS;
+
+pub const DEFAULT_TURBO_RATE: usize = 15;
+pub const MAX_TURBO_RATE: usize = 30;
+const FRAME_RATE: usize = 60;
+
+/* What to do when both opposite directions are held, some games crash on Left+Right */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Socd {
+    #[default]
+    Allow,
+    Neutral,   // Neither direction
+    LastWins,  // The direction pressed last
+}
+
+impl Socd {
+    pub fn from_name(name: &str) -> Option<Socd> {
+        match name {
+            "allow" => Some(Socd::Allow),
+            "neutral" => Some(Socd::Neutral),
+            "last-wins" => Some(Socd::LastWins),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct PortInput {
+    held: Buttons,
+    turbo: Buttons,
+    turbo_start: usize,
+    last_horizontal: Buttons,
+    last_vertical: Buttons,
+    recording: Option<Vec<Buttons>>,
+    macro_frames: Vec<Buttons>,
+    macro_position: Option<usize>,
+}
+
+/*
+    Buttons held on the keyboard and controllers, turned into what the console
+    sees each frame. Turbo buttons flip on and off `turbo_rate` times a second,
+    counted in 60 Hz frames, starting pressed. A macro is a recorded run of
+    frames that is pressed on top of the held buttons when played back.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Input {
+    pub turbo_rate: usize,
+    pub socd: Socd,
+    ports: [PortInput; PORTS],
+    frame: usize,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Input::new(DEFAULT_TURBO_RATE, Socd::Allow)
+    }
+}
+
+impl Input {
+    pub fn new(turbo_rate: usize, socd: Socd) -> Self {
+        Input {
+            turbo_rate: turbo_rate.clamp(1, MAX_TURBO_RATE),
+            socd,
+            ports: Default::default(),
+            frame: 0,
+        }
+    }
+
+    pub fn press(&mut self, port: usize, buttons: Buttons) {
+        let input = &mut self.ports[port];
+        input.held.insert(buttons);
+
+        for direction in [Buttons::Left, Buttons::Right] {
+            if buttons.contains(direction) {
+                input.last_horizontal = direction;
+            }
+        }
+        for direction in [Buttons::Up, Buttons::Down] {
+            if buttons.contains(direction) {
+                input.last_vertical = direction;
+            }
+        }
+    }
+
+    pub fn release(&mut self, port: usize, buttons: Buttons) {
+        self.ports[port].held.remove(buttons);
+    }
+
+    pub fn press_turbo(&mut self, port: usize, buttons: Buttons) {
+        let input = &mut self.ports[port];
+        if input.turbo.is_empty() {
+            input.turbo_start = self.frame;
+        }
+        input.turbo.insert(buttons);
+    }
+
+    pub fn release_turbo(&mut self, port: usize, buttons: Buttons) {
+        self.ports[port].turbo.remove(buttons);
+    }
+
+    pub fn held(&self, port: usize) -> Buttons {
+        self.ports[port].held
+    }
+
+    /* Starts recording a macro, or stops and keeps it. Returns true while recording */
+    pub fn toggle_macro_recording(&mut self, port: usize) -> bool {
+        let input = &mut self.ports[port];
+        match input.recording.take() {
+            Some(frames) => {
+                input.macro_frames = frames;
+                false
+            },
+            None => {
+                input.recording = Some(Vec::new());
+                true
+            },
+        }
+    }
+
+    /* Restarts the macro from its first frame, false if none was recorded */
+    pub fn play_macro(&mut self, port: usize) -> bool {
+        let input = &mut self.ports[port];
+        if input.macro_frames.is_empty() {
+            return false;
+        }
+        input.macro_position = Some(0);
+        true
+    }
+
+    pub fn macro_frames(&self, port: usize) -> &[Buttons] {
+        &self.ports[port].macro_frames
+    }
+
+    pub fn set_macro(&mut self, port: usize, frames: Vec<Buttons>) {
+        self.ports[port].macro_frames = frames;
+    }
+
+    /* Buttons to give the console for the next frame, call once per frame */
+    pub fn next_frame(&mut self) -> [Buttons; PORTS] {
+        let half_period = (FRAME_RATE / (2 * self.turbo_rate)).max(1);
+        let mut buttons = [Buttons::empty(); PORTS];
+
+        for (port, input) in self.ports.iter_mut().enumerate() {
+            let turbo_on = ((self.frame - input.turbo_start) / half_period).is_multiple_of(2);
+            let mut pressed = input.held;
+            if turbo_on {
+                pressed |= input.turbo;
+            }
+
+            if let Some(frames) = input.recording.as_mut() {
+                frames.push(pressed);
+            }
+            if let Some(position) = input.macro_position {
+                pressed |= input.macro_frames[position];
+                input.macro_position = Some(position + 1).filter(|next| *next < input.macro_frames.len());
+            }
+
+            buttons[port] = resolve_socd(pressed, self.socd, input);
+        }
+
+        self.frame += 1;
+        buttons
+    }
+}
+
+fn resolve_socd(mut buttons: Buttons, socd: Socd, input: &PortInput) -> Buttons {
+    let axes = [(Buttons::Left | Buttons::Right, input.last_horizontal), (Buttons::Up | Buttons::Down, input.last_vertical)];
+
+    for (axis, last) in axes {
+        if !buttons.contains(axis) {
+            continue;
+        }
+        match socd {
+            Socd::Allow => {},
+            Socd::Neutral => buttons.remove(axis),
+            Socd::LastWins => buttons.remove(axis - last),
+        }
+    }
+
+    buttons
+}
@@ -0,0 +1,24 @@
+pub const DEFAULT_THRESHOLD: f32 = 0.1;
+
+/* What drives the Famicom controller 2 microphone */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MicSource {
+    #[default]
+    Key,   // Held down with the microphone hotkey
+    Audio, // Loud enough on the default recording device
+}
+
+impl MicSource {
+    pub fn from_name(name: &str) -> Option<MicSource> {
+        match name {
+            "key" => Some(MicSource::Key),
+            "audio" => Some(MicSource::Audio),
+            _ => None,
+        }
+    }
+}
+
+/* Loudest sample as a fraction of full scale */
+pub fn peak_level(samples: &[i16]) -> f32 {
+    samples.iter().map(|sample| sample.unsigned_abs()).max().unwrap_or(0) as f32 / 32768.0
+}
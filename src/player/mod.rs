@@ -1,3 +1,7 @@
-pub mod player;
-pub mod palette;
-pub mod controls;
\ No newline at end of file
+pub mod gamepad;
+pub mod bindings;
+pub mod input;
+pub mod microphone;
+pub mod ntsc;
+pub mod filters;
+pub mod display;
\ No newline at end of file
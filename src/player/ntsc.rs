@@ -0,0 +1,126 @@
+use crate::emulator::palette::{ self, NtscParams };
+use crate::emulator::frame::Frame;
+use crate::player::filters::VideoFilter;
+use std::f64::consts::PI;
+
+const WIDTH: usize = Frame::WIDTH;
+const HEIGHT: usize = Frame::HEIGHT;
+const RGB_DATA_LEN: usize = Frame::RGB_DATA_LEN;
+
+/* https://www.nesdev.org/wiki/NTSC_video */
+const SAMPLES_PER_PIXEL: usize = 8;
+const SAMPLES_PER_LINE: usize = WIDTH * SAMPLES_PER_PIXEL;
+const CARRIER_PERIOD: usize = 12; // Samples per colour subcarrier cycle
+const LINE_PHASE_SHIFT: usize = 341 * SAMPLES_PER_PIXEL % CARRIER_PERIOD; // 341 dots per scanline
+const FRAME_PHASE_SHIFT: usize = 262 * LINE_PHASE_SHIFT % CARRIER_PERIOD; // 262 scanlines per frame
+
+const SIGNAL_BLACK: f64 = 0.518;
+const SIGNAL_WHITE: f64 = 1.962;
+
+pub const NTSC_WIDTH: usize = 602; // Same output width as blargg's nes_ntsc
+
+/*
+    Encodes each 9 bit pixel (palette index + emphasis) as 8 samples of composite
+    signal and decodes it back with a 12 sample window, reproducing colour bleeding,
+    artifact colours and dot crawl from the scanline and frame phase shifts
+ */
+pub struct NtscFilter {
+    params: NtscParams,
+    frame_count: usize,
+    signal: Vec<f64>,
+    carrier: [(f64, f64); CARRIER_PERIOD],
+    gamma: [u8; 256],
+    output: Vec<u8>,
+}
+
+impl NtscFilter {
+    pub fn new(params: NtscParams) -> Self {
+        let mut carrier = [(0.0, 0.0); CARRIER_PERIOD];
+        for (phase, wave) in carrier.iter_mut().enumerate() {
+            let angle = PI * (phase as f64 + 4.0) / 6.0 + params.hue.to_radians();
+            *wave = (angle.cos(), angle.sin());
+        }
+
+        let mut gamma = [0; 256];
+        for (i, value) in gamma.iter_mut().enumerate() {
+            *value = ((i as f64 / 255.0).powf(2.2 / params.gamma) * 255.0).round() as u8;
+        }
+
+        NtscFilter {
+            params,
+            frame_count: 0,
+            signal: vec![0.0; SAMPLES_PER_LINE + CARRIER_PERIOD],
+            carrier,
+            gamma,
+            output: vec![0; NTSC_WIDTH * HEIGHT * RGB_DATA_LEN],
+        }
+    }
+
+    pub fn filter(&mut self, pixels: &[u16]) -> &[u8] {
+        let frame_phase = self.frame_count * FRAME_PHASE_SHIFT;
+        self.frame_count = (self.frame_count + 1) % CARRIER_PERIOD;
+
+        for y in 0..HEIGHT {
+            let line_phase = frame_phase + y * LINE_PHASE_SHIFT;
+            self.encode_line(&pixels[y * WIDTH..(y + 1) * WIDTH], line_phase);
+            self.decode_line(y, line_phase);
+        }
+
+        &self.output
+    }
+
+    /* Signal is padded with half a carrier period of black on each side */
+    fn encode_line(&mut self, line: &[u16], line_phase: usize) {
+        const PADDING: usize = CARRIER_PERIOD / 2;
+
+        for (x, &pixel) in line.iter().enumerate() {
+            for sample in 0..SAMPLES_PER_PIXEL {
+                let position = x * SAMPLES_PER_PIXEL + sample;
+                let phase = (line_phase + position) % CARRIER_PERIOD;
+                let level = palette::composite_signal(pixel, phase);
+
+                self.signal[PADDING + position] = (level - SIGNAL_BLACK) / (SIGNAL_WHITE - SIGNAL_BLACK);
+            }
+        }
+    }
+
+    fn decode_line(&mut self, y: usize, line_phase: usize) {
+        for x in 0..NTSC_WIDTH {
+            let center = x * SAMPLES_PER_LINE / NTSC_WIDTH;
+            let (mut luma, mut i, mut q) = (0.0, 0.0, 0.0);
+
+            for (offset, &level) in self.signal[center..center + CARRIER_PERIOD].iter().enumerate() {
+                let phase = (line_phase + center + CARRIER_PERIOD * 2 + offset - CARRIER_PERIOD / 2) % CARRIER_PERIOD;
+                let (cos, sin) = self.carrier[phase];
+
+                luma += level;
+                i += level * cos;
+                q += level * sin;
+            }
+
+            let luma = luma / CARRIER_PERIOD as f64 * self.params.contrast + self.params.brightness;
+            let i = i / CARRIER_PERIOD as f64 * self.params.saturation;
+            let q = q / CARRIER_PERIOD as f64 * self.params.saturation;
+
+            let base = (y * NTSC_WIDTH + x) * RGB_DATA_LEN;
+            self.output[base] = self.to_u8(luma + 0.946882 * i + 0.623557 * q);
+            self.output[base + 1] = self.to_u8(luma - 0.274788 * i - 0.635691 * q);
+            self.output[base + 2] = self.to_u8(luma - 1.108545 * i + 1.709007 * q);
+        }
+    }
+
+    fn to_u8(&self, value: f64) -> u8 {
+        self.gamma[(value * 255.0).round().clamp(0.0, 255.0) as usize]
+    }
+}
+
+impl VideoFilter for NtscFilter {
+    fn output_size(&self, _width: usize, height: usize) -> (usize, usize) {
+        (NTSC_WIDTH, height)
+    }
+
+    fn apply(&mut self, frame: &Frame, output: &mut Vec<u8>) {
+        output.clear();
+        output.extend_from_slice(self.filter(&frame.pixels));
+    }
+}
@@ -0,0 +1 @@
+pub mod test_bindings;
\ No newline at end of file
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod test {
+    use nes::cli::config::Config;
+    use nes::emulator::joypad::Buttons;
+    use nes::player::bindings::{ Bindings, Hotkey, KeyBinding, Rebinding };
+    use nes::player::gamepad::GamepadButton;
+
+    #[test]
+    fn test_key_binding() {
+        let key = KeyBinding::parse("ctrl+Shift+R").unwrap();
+        assert_eq!(key, KeyBinding { key: "R".to_string(), ctrl: true, shift: true, alt: false });
+        assert_eq!(key.to_string(), "Ctrl+Shift+R");
+
+        assert_eq!(KeyBinding::parse("Keypad +").unwrap(), KeyBinding::new("Keypad +"));
+        assert_eq!(KeyBinding::parse("Alt++").unwrap(), KeyBinding { alt: true, ..KeyBinding::new("+") });
+        assert_eq!(KeyBinding::parse("Ctrl+"), Err("missing key in 'Ctrl+'".to_string()));
+    }
+
+    #[test]
+    fn test_hotkey_names() {
+        assert_eq!(Hotkey::from_name("fast_forward"), Some(Hotkey::FastForward));
+        assert_eq!(Hotkey::from_name("save_state_10"), Some(Hotkey::SaveState(10)));
+        assert_eq!(Hotkey::from_name("load_state_3"), Some(Hotkey::LoadState(3)));
+        assert_eq!(Hotkey::from_name("rebind_2"), Some(Hotkey::Rebind(1)));
+        assert_eq!(Hotkey::from_name("rebind_4"), Some(Hotkey::Rebind(3)));
+        assert_eq!(Hotkey::from_name("record_macro_1"), Some(Hotkey::RecordMacro(0)));
+        assert_eq!(Hotkey::from_name("play_macro_2"), Some(Hotkey::PlayMacro(1)));
+        assert_eq!(Hotkey::from_name("microphone"), Some(Hotkey::Microphone));
+        assert_eq!(Hotkey::from_name("keyboard_capture"), Some(Hotkey::KeyboardCapture));
+        assert_eq!(Hotkey::from_name("save_state_11"), None);
+        assert_eq!(Hotkey::from_name("rebind_0"), None);
+        assert_eq!(Hotkey::from_name("rebind_5"), None);
+    }
+
+    #[test]
+    fn test_defaults() {
+        let bindings = Bindings::default();
+
+        assert_eq!(bindings.keys_for(0, Buttons::A), vec![&KeyBinding::new("A")]);
+        assert_eq!(bindings.keys_for(1, Buttons::Up), vec![&KeyBinding::new("I")]);
+        assert_eq!(bindings.keys_for(2, Buttons::A), vec![&KeyBinding::new("Keypad .")]);
+        assert_eq!(bindings.keys_for(3, Buttons::Start), vec![&KeyBinding::new("Y")]);
+        assert!(bindings.power_pad.contains(&(KeyBinding::new("5"), 1)));
+        assert!(bindings.power_pad.contains(&(KeyBinding::new("K"), 12)));
+        assert!(bindings.hotkeys.contains(&(KeyBinding::parse("Shift+F1").unwrap(), Hotkey::LoadState(1))));
+        assert!(bindings.hotkeys.contains(&(KeyBinding::parse("Alt+Return").unwrap(), Hotkey::Fullscreen)));
+    }
+
+    #[test]
+    fn test_load_config() {
+        let config = Config::parse("
+            [keys.1]
+            a = X, Z
+            select = none
+            turbo_a = T
+            [keys.2]
+            up = W
+            turbo_b = none
+            [gamepad.2]
+            a = rightshoulder
+            turbo_a = none
+            turbo_b = leftshoulder
+            start = guide
+            deadzone = 0.5
+            [power_pad]
+            1 = Q, W
+            12 = none
+            [hotkeys]
+            fast_forward = Ctrl+F
+            screenshot = none
+        ").unwrap();
+
+        let mut bindings = Bindings::default();
+        bindings.load(&config).unwrap();
+
+        assert_eq!(bindings.keys_for(0, Buttons::A), vec![&KeyBinding::new("X"), &KeyBinding::new("Z")]);
+        assert!(bindings.keys_for(0, Buttons::Select).is_empty());
+        assert_eq!(bindings.keys_for(0, Buttons::B), vec![&KeyBinding::new("B")]);
+        assert_eq!(bindings.keys_for(1, Buttons::Up), vec![&KeyBinding::new("W")]);
+        assert!(bindings.turbo_keys.contains(&(KeyBinding::new("T"), 0, Buttons::A)));
+        assert!(!bindings.turbo_keys.contains(&(KeyBinding::new("S"), 0, Buttons::A)));
+        assert!(!bindings.turbo_keys.iter().any(|(_, port, button)| *port == 1 && *button == Buttons::B));
+
+        assert_eq!(bindings.gamepads[0].a, GamepadButton::B);
+        assert_eq!(bindings.gamepads[1].a, GamepadButton::RightShoulder);
+        assert_eq!(bindings.gamepads[1].start, GamepadButton::Guide);
+        assert_eq!(bindings.gamepads[1].deadzone, 0.5);
+        assert_eq!(bindings.gamepads[1].turbo_a, None);
+        assert_eq!(bindings.gamepads[1].turbo_b, Some(GamepadButton::LeftShoulder));
+
+        assert_eq!(bindings.power_pad.iter().filter(|(_, button)| *button == 1).count(), 2);
+        assert!(!bindings.power_pad.iter().any(|(_, button)| *button == 12));
+        assert!(bindings.power_pad.contains(&(KeyBinding::new("6"), 2)));
+
+        assert!(bindings.hotkeys.contains(&(KeyBinding::parse("Ctrl+F").unwrap(), Hotkey::FastForward)));
+        assert!(!bindings.hotkeys.contains(&(KeyBinding::new("`"), Hotkey::FastForward)));
+        assert!(!bindings.hotkeys.iter().any(|(_, hotkey)| *hotkey == Hotkey::Screenshot));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let errors: Vec<String> = [
+            "[keys.1]\njump = X",
+            "[keys.2]\na = Ctrl+",
+            "[gamepad.1]\na = trigger",
+            "[gamepad.1]\nturbo = a",
+            "[gamepad.1]\ndeadzone = 3",
+            "[gamepad.1]\nstart = none",
+            "[power_pad]\n13 = X",
+            "[hotkeys]\nexplode = X",
+        ].iter()
+            .map(|text| Bindings::default().load(&Config::parse(text).unwrap()).unwrap_err())
+            .collect();
+
+        expect_test::expect![[r#"
+            [keys.1] unknown button 'jump'
+            [keys.2] missing key in 'Ctrl+'
+            [gamepad.1] unknown controller button 'trigger'
+            [gamepad.1] unknown button 'turbo'
+            [gamepad.1] invalid deadzone '3'
+            [gamepad.1] 'start' can not be unbound
+            [power_pad] unknown button '13'
+            [hotkeys] unknown action 'explode'"#]].assert_eq(&errors.join("\n"));
+    }
+
+    #[test]
+    fn test_rebinding() {
+        let mut bindings = Bindings::default();
+        let mut rebinding = Rebinding::new(1);
+
+        assert_eq!(rebinding.prompt(), "Press a key for player 2 up (Escape cancels)");
+        let keys = ["W", "S", "A", "D", "Q", "E", "Z", "X"];
+        let finished: Vec<bool> = keys.iter().map(|key| rebinding.bind(&mut bindings, KeyBinding::new(key))).collect();
+
+        assert_eq!(finished, vec![false, false, false, false, false, false, false, true]);
+        assert_eq!(bindings.keys_for(1, Buttons::Left), vec![&KeyBinding::new("A")]);
+        assert!(bindings.keys_for(0, Buttons::A).is_empty()); // Taken by player 2 left
+        assert_eq!(bindings.keys_section(1), "\
+[keys.2]
+up = W
+down = S
+left = A
+right = D
+select = Q
+start = E
+b = Z
+a = X");
+    }
+}
@@ -0,0 +1,34 @@
+use nes::emulator::rom::{ Rom, PRG_ROM_PAGE_SIZE, CHR_ROM_PAGE_SIZE, Mirroring };
+
+pub mod test_cdl;
+
+/*
+    8000: LDA #$10, STA $10, LDA #$90, STA $11, LDY #$00
+    800A: LDA ($10),Y           ; $9010
+    800C: LDA $9000
+    800F: LDA #$00, STA $2006, STA $2006
+    8017: LDA $2007             ; CHR $0000
+    801A: JMP ($9020)           ; $8030
+    8030: JMP $8030
+ */
+pub fn logging_rom() -> Rom {
+    let mut prg_rom = vec![0xEA; 2 * PRG_ROM_PAGE_SIZE];
+    prg_rom[0x0000..0x001D].copy_from_slice(&[
+        0xA9, 0x10, 0x85, 0x10, 0xA9, 0x90, 0x85, 0x11, 0xA0, 0x00,
+        0xB1, 0x10,
+        0xAD, 0x00, 0x90,
+        0xA9, 0x00, 0x8D, 0x06, 0x20, 0x8D, 0x06, 0x20,
+        0xAD, 0x07, 0x20,
+        0x6C, 0x20, 0x90,
+    ]);
+    prg_rom[0x0030..0x0033].copy_from_slice(&[0x4C, 0x30, 0x80]);
+    prg_rom[0x1020..0x1022].copy_from_slice(&[0x30, 0x80]);
+    prg_rom[0x7FFA..].copy_from_slice(&[0x30, 0x80, 0x00, 0x80, 0x30, 0x80]);
+
+    Rom {
+        prg_rom,
+        chr_rom: vec![0; CHR_ROM_PAGE_SIZE],
+        mapper: 0,
+        screen_mirroring: Mirroring::VERTICAL,
+    }
+}
\ No newline at end of file
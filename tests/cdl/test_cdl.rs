@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::cdl::{ self, CodeDataLog, PrgFlags, ChrFlags };
+    use nes::emulator::memory::Mem;
+    use crate::cdl::logging_rom;
+    use crate::helpers::counter_rom;
+    use std::env;
+    use std::fs;
+
+    fn logged_frame() -> Nes {
+        let rom = logging_rom();
+        let mut nes = Nes::new(rom.clone());
+        nes.start_code_data_log(CodeDataLog::new(&rom));
+        nes.run_frame();
+        nes
+    }
+
+    #[test]
+    fn test_prg_flags() {
+        let log = logged_frame().code_data_log().unwrap();
+
+        assert!((0x0000..0x001D).all(|offset| log.prg_flags(offset) == PrgFlags::CODE));
+        assert_eq!(log.prg_flags(0x1010), PrgFlags::DATA | PrgFlags::INDIRECT_DATA);
+        assert_eq!(log.prg_flags(0x1000), PrgFlags::DATA);
+        assert_eq!(log.prg_flags(0x1020), PrgFlags::DATA);
+        assert_eq!(log.prg_flags(0x1021), PrgFlags::DATA);
+        assert_eq!(log.prg_flags(0x0030), PrgFlags::CODE | PrgFlags::INDIRECT_CODE);
+        assert_eq!(log.prg_flags(0x0032), PrgFlags::CODE);
+        assert_eq!(log.prg_flags(0x001D), PrgFlags::empty());
+        assert_eq!(log.counts(), (32, 4, 16));
+    }
+
+    #[test]
+    fn test_brk_ends_instruction() {
+        let mut rom = logging_rom();
+        rom.prg_rom[0x0000] = 0x00; // BRK
+        let mut nes = Nes::new(rom.clone());
+        nes.start_code_data_log(CodeDataLog::new(&rom));
+
+        assert!(!nes.cpu.step());
+        nes.cpu.mem_read(0x8000);
+        assert_eq!(nes.code_data_log().unwrap().prg_flags(0x0000), PrgFlags::CODE | PrgFlags::DATA);
+    }
+
+    #[test]
+    fn test_window_bits() {
+        let mut nes = Nes::new(counter_rom());
+        nes.start_code_data_log(CodeDataLog::new(nes.rom()));
+        nes.run_frame();
+        nes.run_frame();
+        let log = nes.code_data_log().unwrap();
+
+        assert_eq!(log.prg[0x0005], 0x01); // JMP $8005
+        assert_eq!(log.prg[0x7FFA], 0x0E); // NMI vector at $FFFA
+        assert_eq!(log.prg[0x1000], 0x01); // NMI handler at $9000
+    }
+
+    #[test]
+    fn test_chr_flags() {
+        let log = logged_frame().code_data_log().unwrap();
+
+        assert_eq!(log.chr_flags(0x0000), ChrFlags::RENDERED | ChrFlags::READ);
+        assert!((0x0001..0x0010).all(|offset| log.chr_flags(offset) == ChrFlags::RENDERED));
+        assert_eq!(log.chr_flags(0x0010), ChrFlags::empty());
+    }
+
+    #[test]
+    fn test_keeps_logging() {
+        let mut nes = logged_frame();
+        let state = nes.save_state();
+        nes.load_state(&state).unwrap();
+        nes.power_cycle();
+        assert_eq!(nes.code_data_log().unwrap().counts(), (32, 6, 16)); // The reset vector was read
+
+        nes.load_rom(counter_rom());
+        assert_eq!(nes.code_data_log(), None);
+    }
+
+    #[test]
+    fn test_file() {
+        let rom = logging_rom();
+        let log = logged_frame().code_data_log().unwrap();
+        let path = env::temp_dir().join("nes_test_cdl.cdl").to_string_lossy().to_string();
+
+        log.save(&path).unwrap();
+        assert_eq!(fs::read(&path).unwrap().len(), 0x8000 + 0x2000);
+        assert_eq!(CodeDataLog::load(&path, &rom), Ok(log.clone()));
+        assert_eq!(CodeDataLog::load_or_new(&path, &rom), Ok(log));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(CodeDataLog::load_or_new(&path, &rom), Ok(CodeDataLog::new(&rom)));
+        assert_eq!(
+            CodeDataLog::from_bytes(&[0; 16], &rom),
+            Err("expected 40960 bytes for this ROM, found 16".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_is_data() {
+        assert!(cdl::is_data(0x02));
+        assert!(cdl::is_data(0x22));
+        assert!(!cdl::is_data(0x03));
+        assert!(!cdl::is_data(0x12));
+        assert!(!cdl::is_data(0x00));
+    }
+}
@@ -0,0 +1 @@
+pub mod test_checksum;
\ No newline at end of file
@@ -0,0 +1,24 @@
+#[cfg(test)]
+mod test {
+    use nes::helpers::checksum::{ crc32, adler32, md5 };
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[0xFF; 100_000]), 0x149A_302C);
+    }
+
+    #[test]
+    fn test_md5() {
+        let hex = |digest: [u8; 16]| digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        assert_eq!(hex(md5(b"")), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hex(md5(b"abc")), "900150983cd24fb0d6963f7d28e17f72");
+        let bytes: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+        assert_eq!(hex(md5(&bytes)), "b2ea9f7fcea831a4a63b213f41a8855b");
+    }
+}
@@ -0,0 +1,4 @@
+pub mod test_options;
+pub mod test_config;
+pub mod test_headless;
+pub mod test_disasm;
\ No newline at end of file
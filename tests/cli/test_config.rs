@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod test {
+    use nes::cli::config::Config;
+
+    #[test]
+    fn test_parse_sections() {
+        let config = Config::parse("
+            # Top level options
+            scale = 3
+            filter = \"crt\"
+
+            [controls]
+            ; Player one
+            a = X
+            b = Z
+            a = K
+        ").unwrap();
+
+        assert_eq!(config.get("", "scale"), Some("3"));
+        assert_eq!(config.get("", "filter"), Some("crt"));
+        assert_eq!(config.get("controls", "a"), Some("K"));
+        assert_eq!(config.get("controls", "scale"), None);
+        assert_eq!(config.entries("controls").count(), 3);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Config::parse("scale 3"), Err("line 1: expected 'key = value'".to_string()));
+        assert_eq!(Config::parse("[controls\n"), Err("line 1: expected ']' after section name".to_string()));
+        assert!(Config::load("does/not/exist.ini").unwrap_err().starts_with("Could not read config does/not/exist.ini"));
+    }
+}
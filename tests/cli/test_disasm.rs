@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod test {
+    use nes::cli::disasm::{ self, DisasmOptions };
+    use nes::emulator::rom::{ Rom, PRG_ROM_PAGE_SIZE };
+    use crate::helpers::TestRom;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn rom(banks: usize) -> Rom {
+        let mut rom = TestRom::default_rom();
+        rom.prg_rom = (0..banks * PRG_ROM_PAGE_SIZE).map(|i| (i / PRG_ROM_PAGE_SIZE) as u8).collect();
+        rom
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = disasm::parse(args(&["--bank", "2", "--org", "$A000", "--cdl", "game.cdl", "--output", "bank2.s", "game.nes"])).unwrap().unwrap();
+
+        assert_eq!(options, DisasmOptions {
+            rom_path: "game.nes".to_string(),
+            bank: 2,
+            org: Some(0xA000),
+            cdl: Some("game.cdl".to_string()),
+            output: Some("bank2.s".to_string()),
+        });
+        assert_eq!(disasm::parse(args(&["-h"])), Ok(None));
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors: Vec<String> = [
+            &[][..],
+            &["--bank", "last", "game.nes"],
+            &["--org", "C0000", "game.nes"],
+            &["--org"],
+            &["--labels", "x", "game.nes"],
+        ].iter()
+            .map(|a| disasm::parse(args(a)).unwrap_err())
+            .collect();
+
+        expect_test::expect![[r#"
+            Missing ROM path
+            Invalid value for --bank: 'last' (expected a bank number)
+            Invalid value for --org: 'C0000' (expected a hex address)
+            Option --org requires a value
+            Unknown option: --labels"#]].assert_eq(&errors.join("\n"));
+    }
+
+    #[test]
+    fn test_banks() {
+        let rom = rom(4);
+        let options = |bank| DisasmOptions { bank, ..Default::default() };
+
+        assert_eq!(disasm::prg_bank(&rom, 2).unwrap()[0], 2);
+        assert_eq!(disasm::prg_bank(&rom, 4), Err("The ROM has 4 PRG banks, there is no bank 4".to_string()));
+        assert_eq!(options(0).org(&rom), 0x8000);
+        assert_eq!(options(3).org(&rom), 0xC000);
+        assert_eq!(DisasmOptions { org: Some(0xA000), ..options(3) }.org(&rom), 0xA000);
+    }
+}
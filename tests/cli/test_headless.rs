@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod test {
+    use nes::cli::headless::{ self, Condition, InputScript };
+    use nes::emulator::bus::Bus;
+    use nes::emulator::cpu::Cpu;
+    use nes::emulator::joypad::Buttons;
+    use crate::helpers::TestRom;
+    use expect_test::expect;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = headless::parse(args(&["--until", "pc=C000", "--png", "out.png", "--wav", "out.wav", "--symbols", "game.mlb", "--cdl", "game.cdl", "game.nes"]))
+            .unwrap().unwrap();
+
+        assert_eq!(options.rom_path, "game.nes");
+        assert_eq!(options.until, Some(Condition::ProgramCounter(0xC000)));
+        assert_eq!(options.png.as_deref(), Some("out.png"));
+        assert_eq!(options.wav.as_deref(), Some("out.wav"));
+        assert_eq!(options.symbols, vec!["game.mlb"]);
+        assert_eq!(options.cdl.as_deref(), Some("game.cdl"));
+        assert_eq!(options.frame_limit(None), None);
+
+        let options = headless::parse(args(&["game.nes"])).unwrap().unwrap();
+        assert_eq!(options.frame_limit(None), Some(headless::DEFAULT_FRAMES));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let bus = Bus::new(TestRom::default_rom());
+        let mut cpu = Cpu::new(bus);
+        cpu.program_counter = 0x8000;
+        cpu.bus.cpu_vram[0x10] = 0xFF;
+
+        assert!(Condition::parse("pc=8000").unwrap().holds(&cpu));
+        assert!(Condition::parse("$0010=ff").unwrap().holds(&cpu));
+        assert!(Condition::parse("0810=FF").unwrap().holds(&cpu)); // Mirrored RAM
+        assert!(!Condition::parse("0011=FF").unwrap().holds(&cpu));
+        assert!(Condition::parse("2002=80").is_err());
+        assert!(Condition::parse("0010=100").is_err());
+    }
+
+    #[test]
+    fn test_input_script() {
+        let script = InputScript::parse("
+            # frame [port] buttons
+            10 start
+            11 2 left
+            12 none
+            20 1 right+A
+            30 2
+        ").unwrap();
+
+        assert_eq!(script.buttons(0, 0), Buttons::empty());
+        assert_eq!(script.buttons(0, 11), Buttons::Start);
+        assert_eq!(script.buttons(0, 12), Buttons::empty());
+        assert_eq!(script.buttons(0, 500), Buttons::Right | Buttons::A);
+        assert_eq!(script.buttons(1, 10), Buttons::empty());
+        assert_eq!(script.buttons(1, 20), Buttons::Left);
+        assert_eq!(script.buttons(1, 30), Buttons::empty());
+
+        assert_eq!(InputScript::parse("10 jump"), Err("line 1: unknown button 'jump'".to_string()));
+        assert_eq!(InputScript::parse("10 3 a"), Err("line 1: port 3 is not 1 or 2".to_string()));
+        assert_eq!(InputScript::parse("10 a\n5 b"), Err("line 2: frames must be in order".to_string()));
+    }
+
+    #[test]
+    fn test_cpu_state_and_ram_dump() {
+        let bus = Bus::new(TestRom::default_rom());
+        let mut cpu = Cpu::new(bus);
+        cpu.accumulator = 0x42;
+        cpu.bus.cpu_vram[0x11] = 0xAB;
+
+        assert_eq!(headless::cpu_state(&cpu), "PC:0000 A:42 X:00 Y:00 P:24 SP:FD CYC:0");
+        expect![[r#"
+            0000: 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+            0010: 00 AB 00 00 00 00 00 00 00 00 00 00 00 00 00 00"#]]
+            .assert_eq(&headless::ram_dump(&cpu.bus.cpu_vram[..0x20]));
+    }
+}
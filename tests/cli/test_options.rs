@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod test {
+    use nes::cli::options::{ self, Options };
+    use nes::emulator::region::Region;
+    use nes::emulator::input_device::DeviceKind;
+    use nes::player::display::{ AspectRatio, Overscan };
+    use nes::player::filters::FilterKind;
+    use nes::player::gamepad::GamepadButton;
+    use nes::player::input::Socd;
+    use nes::player::microphone::MicSource;
+    use std::env;
+    use std::fs;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rom_path_only() {
+        let options = options::parse(args(&["game.nes"])).unwrap().unwrap();
+
+        assert_eq!(options, Options { rom_path: "game.nes".to_string(), ..Default::default() });
+    }
+
+    #[test]
+    fn test_all_options() {
+        let options = options::parse(args(&[
+            "--scale", "2", "--region", "pal", "--palette", "smooth.pal", "--filter", "ntsc",
+            "--aspect", "8:7", "--overscan", "8,8,0,0", "--integer-scale", "--paused", "--mute", "--four-score",
+            "--frames", "600", "--rewind-memory", "0.5", "--rewind-interval", "4",
+            "--gamepad-a", "x", "--gamepad-b", "Y", "--deadzone", "0.5",
+            "--turbo-rate", "30", "--socd", "last-wins", "--trace", "trace.log", "--debug", "--gdb", "2345", "--symbols", "game.dbg", "--symbols", "ram.nl", "--cdl", "game.cdl", "game.nes",
+        ])).unwrap().unwrap();
+
+        assert_eq!(options.rom_path, "game.nes");
+        assert_eq!(options.display.scale, 2.0);
+        assert_eq!(options.display.aspect, AspectRatio::PixelAspect8x7);
+        assert_eq!(options.display.overscan, Overscan::tv());
+        assert!(options.display.integer_scaling);
+        assert_eq!(options.region, Region::Pal);
+        assert_eq!(options.palette.as_deref(), Some("smooth.pal"));
+        assert_eq!(options.filter, FilterKind::Ntsc);
+        assert!(options.paused);
+        assert!(options.mute);
+        assert!(options.four_score);
+        assert_eq!(options.port_2, DeviceKind::Joypad);
+        assert_eq!(options.frames, Some(600));
+        assert_eq!(options.rewind_memory, 512 * 1024);
+        assert_eq!(options.rewind_interval, 4);
+        assert_eq!(options.bindings.gamepads[1].a, GamepadButton::X);
+        assert_eq!(options.bindings.gamepads[1].b, GamepadButton::Y);
+        assert_eq!(options.bindings.gamepads[1].deadzone, 0.5);
+        assert_eq!(options.turbo_rate, 30);
+        assert_eq!(options.socd, Socd::LastWins);
+        assert_eq!(options.trace.as_deref(), Some("trace.log"));
+        assert!(options.debug);
+        assert_eq!(options.gdb, Some(2345));
+        assert_eq!(options.symbols, vec!["game.dbg", "ram.nl"]);
+        assert_eq!(options.cdl.as_deref(), Some("game.cdl"));
+    }
+
+    #[test]
+    fn test_port_2() {
+        let port_2 = |a: &[&str]| options::parse(args(a)).unwrap().unwrap().port_2;
+
+        assert_eq!(port_2(&["--zapper", "game.nes"]), DeviceKind::Zapper);
+        assert_eq!(port_2(&["--port2", "arkanoid", "game.nes"]), DeviceKind::Vaus);
+        assert_eq!(port_2(&["--port2", "power-pad", "game.nes"]), DeviceKind::PowerPad);
+    }
+
+    #[test]
+    fn test_famicom_devices() {
+        let options = options::parse(args(&["--expansion", "keyboard", "--microphone", "audio", "--mic-threshold", "0.3", "game.nes"])).unwrap().unwrap();
+
+        assert_eq!(options.expansion, Some(DeviceKind::FamilyKeyboard));
+        assert_eq!(options.microphone, MicSource::Audio);
+        assert_eq!(options.mic_threshold, 0.3);
+        assert_eq!(Options::default().expansion, None);
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(options::parse(args(&["--help"])), Ok(None));
+        assert_eq!(options::parse(args(&["game.nes", "-h"])), Ok(None));
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors: Vec<String> = [
+            &[][..],
+            &["--scale", "big", "game.nes"],
+            &["--region", "secam", "game.nes"],
+            &["--overscan", "8,8", "game.nes"],
+            &["game.nes", "--frames"],
+            &["--gamepad-a", "z", "game.nes"],
+            &["--deadzone", "2", "game.nes"],
+            &["--turbo-rate", "60", "game.nes"],
+            &["--socd", "first-wins", "game.nes"],
+            &["--port2", "keyboard", "game.nes"],
+            &["--four-score", "--port2", "vaus", "game.nes"],
+            &["--port2", "keyboard", "game.nes"],
+            &["--expansion", "zapper", "game.nes"],
+            &["--microphone", "usb", "game.nes"],
+            &["-x", "game.nes"],
+            &["--volume", "5", "game.nes"],
+            &["--gdb", "65536", "game.nes"],
+            &["game.nes", "other.nes"],
+        ].iter()
+            .map(|a| options::parse(args(a)).unwrap_err())
+            .collect();
+
+        expect_test::expect![[r#"
+            Missing ROM path
+            Invalid value for --scale: 'big' (expected a positive number)
+            Invalid value for --region: 'secam' (expected ntsc, pal or dendy)
+            Invalid value for --overscan: '8,8' (expected four numbers like 8,8,0,0)
+            Option --frames requires a value
+            Invalid value for --gamepad-a: 'z' (expected a controller button name)
+            Invalid value for --deadzone: '2' (expected a number from 0 to 1)
+            Invalid value for --turbo-rate: '60' (expected a number from 1 to 30)
+            Invalid value for --socd: 'first-wins' (expected allow, neutral or last-wins)
+            Invalid value for --port2: 'keyboard' (expected controller, zapper, vaus or power-pad)
+            --four-score needs a controller in port 2, not a Vaus paddle
+            Invalid value for --port2: 'keyboard' (expected controller, zapper, vaus or power-pad)
+            Invalid value for --expansion: 'zapper' (expected none or keyboard)
+            Invalid value for --microphone: 'usb' (expected key or audio)
+            Unknown option: -x
+            Unknown option: --volume
+            Invalid value for --gdb: '65536' (expected a port number)
+            Unexpected argument: other.nes"#]].assert_eq(&errors.join("\n"));
+    }
+
+    #[test]
+    fn test_config_file() {
+        let path = env::temp_dir().join("nes_test_options.ini");
+        fs::write(&path, "scale = 4\nregion = dendy\npaused = yes\n[controls]\nup = W\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let options = options::parse(args(&["--config", path, "--scale", "2", "game.nes"])).unwrap().unwrap();
+
+        assert_eq!(options.display.scale, 2.0);
+        assert_eq!(options.region, Region::Dendy);
+        assert!(options.paused);
+        assert_eq!(options.config.as_deref(), Some(path));
+    }
+}
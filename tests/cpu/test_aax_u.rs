@@ -22,6 +22,6 @@ mod test {
 
         let val = cpu.mem_read(0x00a1);
         let expected = expect!["00011000"];
-        expected.assert_eq(&format!("{:08b}", val).as_str());
+        expected.assert_eq(&format!("{:08b}", val));
     }
 }
\ No newline at end of file
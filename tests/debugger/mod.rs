@@ -0,0 +1,31 @@
+use nes::emulator::rom::{ Rom, PRG_ROM_PAGE_SIZE, CHR_ROM_PAGE_SIZE, Mirroring };
+
+pub mod test_condition;
+pub mod test_debugger;
+pub mod test_commands;
+pub mod test_gdb;
+
+/*
+    8000: LDA #$80, STA $2000   ; Enable NMI
+    8005: JSR $8020
+    8008: INC $10
+    800A: JMP $8005
+    8020: LDX #$03, STX $0300
+    8025: DEX, BNE $8025
+    8028: RTS
+    9000: INC $00, RTI          ; NMI
+ */
+pub fn subroutine_rom() -> Rom {
+    let mut prg_rom = vec![0xEA; 2 * PRG_ROM_PAGE_SIZE];
+    prg_rom[0x0000..0x000D].copy_from_slice(&[0xA9, 0x80, 0x8D, 0x00, 0x20, 0x20, 0x20, 0x80, 0xE6, 0x10, 0x4C, 0x05, 0x80]);
+    prg_rom[0x0020..0x0029].copy_from_slice(&[0xA2, 0x03, 0x8E, 0x00, 0x03, 0xCA, 0xD0, 0xFD, 0x60]);
+    prg_rom[0x1000..0x1003].copy_from_slice(&[0xE6, 0x00, 0x40]);
+    prg_rom[0x7FFA..].copy_from_slice(&[0x00, 0x90, 0x00, 0x80, 0x00, 0x90]);
+
+    Rom {
+        prg_rom,
+        chr_rom: vec![0; CHR_ROM_PAGE_SIZE],
+        mapper: 0,
+        screen_mirroring: Mirroring::VERTICAL,
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::debugger::core::{ Debugger, RunMode, Stop };
+    use nes::debugger::commands::{ run_command, describe_stop };
+    use nes::helpers::symbols::{ Symbols, SymbolFormat };
+    use crate::debugger::subroutine_rom;
+    use std::rc::Rc;
+
+    fn run_commands(debugger: &mut Debugger, nes: &mut Nes, lines: &[&str]) -> String {
+        lines.iter()
+            .map(|line| match run_command(debugger, nes, line) {
+                Ok(output) => output,
+                Err(e) => format!("error: {}", e),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_breakpoint_commands() {
+        let mut nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+
+        let output = run_commands(&mut debugger, &mut nes, &[
+            "break $8020",
+            "w rw $0300-$02F0 if A != 0",
+            "watch x 0x8028",
+            "disable 3",
+            "list",
+            "delete 1",
+            "delete 9",
+            "break",
+            "watch q $10",
+            "break $8020 if A ==",
+            "fly",
+        ]);
+
+        expect_test::expect![[r#"
+            Breakpoint 1 at $8020
+            Watchpoint 2 on rw $02F0-$0300
+            Watchpoint 3 on x $8028-$8028
+
+            1: break $8020
+            2: watch rw $02F0-$0300 if A != 0
+            3: break $8028 (disabled)
+
+            error: No breakpoint 9
+            error: invalid address ''
+            error: 'q' is not r, w, x or a mix of them
+            error: unfinished condition
+            error: Unknown command 'fly', try help"#]].assert_eq(&output);
+    }
+
+    #[test]
+    fn test_run_commands() {
+        let mut nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+
+        run_commands(&mut debugger, &mut nes, &["step 2"]);
+        assert_eq!(debugger.mode, RunMode::StepInto(2));
+        run_commands(&mut debugger, &mut nes, &["c"]);
+        assert_eq!(debugger.mode, RunMode::Running);
+        run_commands(&mut debugger, &mut nes, &["scanline 241"]);
+        assert_eq!(debugger.mode, RunMode::Scanline(241));
+        assert_eq!(run_commands(&mut debugger, &mut nes, &["pause", "pause"]), "Paused\n8000  A9 80     LDA #$80                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7\nNot running");
+        assert_eq!(run_command(&mut debugger, &mut nes, "scanline 262"), Err("usage: scanline <0-261>".to_string()));
+    }
+
+    #[test]
+    fn test_inspect() {
+        let mut nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+        nes.cpu.bus.cpu_vram[0x301] = 0xAB;
+
+        let output = run_commands(&mut debugger, &mut nes, &["mem $0300 20", "x $8000 3", "regs"]);
+        expect_test::expect![[r#"
+            0300: 00 AB 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+            0310: 00 00 00 00
+            8000: A9 80 8D
+            8000  A9 80     LDA #$80                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7"#]].assert_eq(&output);
+        assert_eq!(describe_stop(&Stop::Nmi, &mut nes, &Symbols::default()).lines().next(), Some("NMI"));
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+        let text = "$8020#UpdatePlayer#\n$0300#Buffer#\n$0010#FrameCount#";
+        debugger.symbols = Rc::new(Symbols::parse(text, SymbolFormat::FceuxNl, 0x8000).unwrap());
+
+        let output = run_commands(&mut debugger, &mut nes, &[
+            "break UpdatePlayer",
+            "watch w Buffer-$030F if [FrameCount] > 1",
+            "list",
+            "break Missing",
+            "break $8000 if Missing == 1",
+        ]);
+
+        expect_test::expect![[r#"
+            Breakpoint 1 at $8020 <UpdatePlayer>
+            Watchpoint 2 on w $0300 <Buffer>-$030F
+            1: break $8020 <UpdatePlayer>
+            2: watch w $0300-$030F <Buffer> if [FrameCount] > 1
+            error: invalid address 'Missing'
+            error: unknown register or symbol 'Missing'"#]].assert_eq(&output);
+
+        debugger.resume(RunMode::Running, &nes);
+        let stop = (0..10).find_map(|_| debugger.run_frame(&mut nes, |_| {}).1).unwrap();
+        assert_eq!(describe_stop(&stop, &mut nes, &debugger.symbols).lines().next(), Some("Breakpoint 1"));
+        assert!(run_commands(&mut debugger, &mut nes, &["regs"]).starts_with("8020  A2 03     LDX #$03"));
+        debugger.mode = RunMode::Halted;
+        nes.cpu.program_counter = 0x8005;
+        assert!(run_commands(&mut debugger, &mut nes, &["regs"]).starts_with("8005  20 20 80  JSR UpdatePlayer  "));
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::debugger::condition::{ Condition, parse_number };
+    use crate::debugger::subroutine_rom;
+
+    fn nes() -> Nes {
+        let mut nes = Nes::new(subroutine_rom());
+        nes.cpu.accumulator = 0x10;
+        nes.cpu.register_x = 4;
+        nes.cpu.bus.cpu_vram[0x300] = 0x85;
+        nes
+    }
+
+    fn evaluate(text: &str) -> u32 {
+        Condition::parse(text).unwrap().evaluate(&nes())
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(parse_number("$C000"), Some(0xC000));
+        assert_eq!(parse_number("0x1f"), Some(0x1F));
+        assert_eq!(parse_number("%101"), Some(5));
+        assert_eq!(parse_number("42"), Some(42));
+        assert_eq!(parse_number("$G"), None);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("A == $10 && X > 3"), 1);
+        assert_eq!(evaluate("a == $10 && x > 4"), 0);
+        assert_eq!(evaluate("[$0300] & $80 != 0"), 1);
+        assert_eq!(evaluate("[$02FF + 1]"), 0x85);
+        assert_eq!(evaluate("!(X == 4) || PC == $8000"), 1);
+        assert_eq!(evaluate("X - 5"), u32::MAX);
+        assert_eq!(evaluate("I"), 1);
+        assert_eq!(evaluate("Z | C"), 0);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Condition::parse("  A==1 ").unwrap().to_string(), "A==1");
+    }
+
+    #[test]
+    fn test_errors() {
+        let errors: Vec<String> = ["A ==", "Q > 1", "(A == 1", "A == 1 1", "A # 2", "$ZZ == 1", "[A"].iter()
+            .map(|text| Condition::parse(text).unwrap_err())
+            .collect();
+
+        expect_test::expect![[r#"
+            unfinished condition
+            unknown register or symbol 'Q'
+            unfinished condition
+            unexpected '1' in condition
+            unexpected '#' in condition
+            invalid number '$ZZ'
+            unfinished condition"#]].assert_eq(&errors.join("\n"));
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::cpu::MemoryAccess;
+    use nes::debugger::core::{ Debugger, Access, RunMode, Stop };
+    use nes::debugger::condition::Condition;
+    use crate::debugger::subroutine_rom;
+
+    /* Runs frames until the debugger stops, panics if it never does */
+    fn run(debugger: &mut Debugger, nes: &mut Nes) -> Stop {
+        (0..10).find_map(|_| debugger.run_frame(nes, |_| {}).1).expect("debugger did not stop")
+    }
+
+    fn start() -> (Debugger, Nes) {
+        let nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+        debugger.resume(RunMode::Running, &nes);
+        (debugger, nes)
+    }
+
+    #[test]
+    fn test_halted_runs_nothing() {
+        let mut nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+
+        assert_eq!(debugger.run_frame(&mut nes, |_| {}), (false, None));
+        assert_eq!(nes.cpu.program_counter, 0x8000);
+    }
+
+    #[test]
+    fn test_breakpoint() {
+        let (mut debugger, mut nes) = start();
+        let id = debugger.add_breakpoint(0x8020, 0x8020, Access::EXECUTE, None);
+
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Breakpoint(id));
+        assert_eq!(nes.cpu.program_counter, 0x8020);
+        assert!(debugger.is_halted());
+
+        debugger.resume(RunMode::Running, &nes);
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Breakpoint(id));
+        assert_eq!(nes.cpu.bus.cpu_vram[0x10], 1); // Went round the loop once
+    }
+
+    #[test]
+    fn test_conditional_breakpoint() {
+        let (mut debugger, mut nes) = start();
+        debugger.add_breakpoint(0x8025, 0x8025, Access::EXECUTE, Some(Condition::parse("X == 1").unwrap()));
+
+        run(&mut debugger, &mut nes);
+        assert_eq!(nes.cpu.register_x, 1);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let (mut debugger, mut nes) = start();
+        let write = debugger.add_breakpoint(0x0300, 0x03FF, Access::WRITE, None);
+
+        let access = MemoryAccess { address: 0x0300, value: 3, write: true };
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Watchpoint { id: write, access });
+        assert_eq!(nes.cpu.program_counter, 0x8025); // After the STX
+
+        debugger.delete(write);
+        let read = debugger.add_breakpoint(0x0010, 0x0010, Access::READ, None);
+        debugger.resume(RunMode::Running, &nes);
+        let access = MemoryAccess { address: 0x0010, value: 0, write: false };
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Watchpoint { id: read, access });
+    }
+
+    #[test]
+    fn test_fetches_are_not_reads() {
+        let (mut debugger, mut nes) = start();
+        debugger.add_breakpoint(0x8000, 0x8FFF, Access::READ, None);
+        let execute = debugger.add_breakpoint(0x8028, 0x8028, Access::EXECUTE, None);
+
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Breakpoint(execute));
+    }
+
+    #[test]
+    fn test_step_over_and_out() {
+        let (mut debugger, mut nes) = start();
+        debugger.add_breakpoint(0x8005, 0x8005, Access::EXECUTE, None);
+        run(&mut debugger, &mut nes);
+
+        debugger.step_over(&nes);
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Step);
+        assert_eq!(nes.cpu.program_counter, 0x8008);
+        assert_eq!(nes.cpu.register_x, 0);
+
+        debugger.breakpoints.clear();
+        debugger.add_breakpoint(0x8025, 0x8025, Access::EXECUTE, None);
+        debugger.resume(RunMode::Running, &nes);
+        run(&mut debugger, &mut nes);
+        debugger.breakpoints.clear();
+        debugger.step_out(&nes);
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Step);
+        assert_eq!(nes.cpu.program_counter, 0x8008);
+    }
+
+    #[test]
+    fn test_step_out_wrapped_stack() {
+        let (mut debugger, mut nes) = start();
+        debugger.add_breakpoint(0x8005, 0x8005, Access::EXECUTE, None);
+        run(&mut debugger, &mut nes);
+        nes.cpu.stack_pointer = 0x01; // The JSR pushes the return address across $0100
+
+        debugger.breakpoints.clear();
+        debugger.resume(RunMode::StepInto(1), &nes);
+        run(&mut debugger, &mut nes);
+        assert_eq!(nes.cpu.stack_pointer, 0xFF);
+        debugger.step_out(&nes);
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Step);
+        assert_eq!(nes.cpu.program_counter, 0x8008);
+        assert_eq!(nes.cpu.stack_pointer, 0x01);
+    }
+
+    #[test]
+    fn test_stop_on_frame_end() {
+        let mut reference = Nes::new(subroutine_rom());
+        let instructions = (1..).find(|_| reference.step()).unwrap();
+
+        /* The frame still counts as finished when the same instruction stops the debugger */
+        let mut nes = Nes::new(subroutine_rom());
+        let mut debugger = Debugger::new();
+        debugger.resume(RunMode::StepInto(instructions), &nes);
+        assert_eq!(debugger.run_frame(&mut nes, |_| {}), (true, Some(Stop::Step)));
+        assert_eq!(nes.frame_count(), 1);
+
+        debugger.resume(RunMode::StepInto(1), &nes);
+        assert_eq!(debugger.run_frame(&mut nes, |_| {}), (false, Some(Stop::Step)));
+    }
+
+    #[test]
+    fn test_step_into() {
+        let (mut debugger, mut nes) = start();
+        debugger.resume(RunMode::StepInto(3), &nes);
+
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Step);
+        assert_eq!(nes.cpu.program_counter, 0x8020);
+    }
+
+    #[test]
+    fn test_run_to_nmi_and_scanline() {
+        let (mut debugger, mut nes) = start();
+        debugger.resume(RunMode::Nmi, &nes);
+
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Nmi);
+        assert_eq!(nes.cpu.program_counter, 0x9000);
+        assert_eq!(nes.cpu.bus.cpu_vram[0], 0);
+
+        debugger.resume(RunMode::Scanline(100), &nes);
+        assert_eq!(run(&mut debugger, &mut nes), Stop::Scanline(100));
+        assert_eq!(nes.cpu.bus.ppu.scanline, 100);
+        assert_eq!(nes.cpu.bus.cpu_vram[0], 1); // The handler ran once
+    }
+
+    #[test]
+    fn test_access_parse() {
+        assert_eq!(Access::parse("rw"), Some(Access::READ | Access::WRITE));
+        assert_eq!(Access::parse("x"), Some(Access::EXECUTE));
+        assert_eq!(Access::parse(""), None);
+        assert_eq!(Access::parse("rq"), None);
+        assert_eq!((Access::WRITE | Access::READ).to_string(), "rw");
+    }
+}
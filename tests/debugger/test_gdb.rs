@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::cpu::MemoryAccess;
+    use nes::debugger::core::{ Debugger, RunMode, Stop };
+    use nes::debugger::gdb::{ GdbStub, encode_packet, stop_reply };
+    use crate::debugger::subroutine_rom;
+    use std::io::{ Read, Write };
+    use std::net::TcpStream;
+    use std::time::{ Duration, Instant };
+
+    fn stub() -> (GdbStub, Debugger, Nes) {
+        (GdbStub::listen("127.0.0.1:0").unwrap(), Debugger::new(), Nes::new(subroutine_rom()))
+    }
+
+    fn replies(stub: &mut GdbStub, debugger: &mut Debugger, nes: &mut Nes, packets: &[&str]) -> String {
+        packets.iter()
+            .map(|packet| match stub.handle_packet(debugger, nes, packet) {
+                Some(reply) => format!("{} -> {}", packet, reply),
+                None => format!("{} -> (no reply)", packet),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_packets() {
+        assert_eq!(encode_packet("OK"), "$OK#9a");
+        assert_eq!(encode_packet(""), "$#00");
+        assert_eq!(stop_reply(&Stop::Breakpoint(1)), "S05");
+        assert_eq!(stop_reply(&Stop::Interrupted), "S02");
+        assert_eq!(stop_reply(&Stop::Watchpoint { id: 1, access: MemoryAccess { address: 0x300, value: 3, write: true } }), "T05watch:300;");
+    }
+
+    #[test]
+    fn test_registers_and_memory() {
+        let (mut stub, mut debugger, mut nes) = stub();
+        nes.cpu.bus.cpu_vram[0x10] = 0x42;
+
+        let output = replies(&mut stub, &mut debugger, &mut nes, &[
+            "g",
+            "G0102032efc3480",
+            "g",
+            "p5",
+            "P0=ff",
+            "p0",
+            "P5=00",
+            "p9",
+            "m10,2",
+            "m8000,4",
+            "M10,2:abcd",
+            "m10,3",
+            "M10,2:ab",
+            "?",
+            "qAttached",
+            "vMustReplyEmpty",
+        ]);
+
+        expect_test::expect![[r#"
+            g -> 00000024fd0080
+            G0102032efc3480 -> OK
+            g -> 0102032efc3480
+            p5 -> 3480
+            P0=ff -> OK
+            p0 -> ff
+            P5=00 -> E01
+            p9 -> E01
+            m10,2 -> 4200
+            m8000,4 -> a9808d00
+            M10,2:abcd -> OK
+            m10,3 -> abcd00
+            M10,2:ab -> E01
+            ? -> S05
+            qAttached -> 1
+            vMustReplyEmpty -> "#]].assert_eq(&output);
+        assert_eq!(nes.cpu.program_counter, 0x8034);
+        assert_eq!(nes.cpu.accumulator, 0xFF);
+    }
+
+    #[test]
+    fn test_rom_writes() {
+        let (mut stub, mut debugger, mut nes) = stub();
+
+        let output = replies(&mut stub, &mut debugger, &mut nes, &[
+            "M8000,1:ea",
+            "M7fff,2:eaea",
+            "Mffff,2:eaea",
+            "m7fff,2",
+        ]);
+
+        expect_test::expect![[r#"
+            M8000,1:ea -> E01
+            M7fff,2:eaea -> E01
+            Mffff,2:eaea -> E01
+            m7fff,2 -> 00a9"#]].assert_eq(&output);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let (mut stub, mut debugger, mut nes) = stub();
+
+        let output = replies(&mut stub, &mut debugger, &mut nes, &["Z0,8020,1", "Z2,300,2", "Z5,300,1", "z0,8028,1", "c"]);
+        assert_eq!(output, "Z0,8020,1 -> OK\nZ2,300,2 -> OK\nZ5,300,1 -> E01\nz0,8028,1 -> E01\nc -> (no reply)");
+        assert_eq!(debugger.mode, RunMode::Running);
+        assert_eq!(debugger.breakpoints.len(), 2);
+
+        assert_eq!(debugger.run_frame(&mut nes, |_| {}), (false, Some(Stop::Breakpoint(1))));
+        assert_eq!(nes.cpu.program_counter, 0x8020);
+
+        stub.handle_packet(&mut debugger, &mut nes, "z0,8020,1");
+        stub.handle_packet(&mut debugger, &mut nes, "c");
+        let stop = debugger.run_frame(&mut nes, |_| {}).1.unwrap();
+        assert_eq!(stop_reply(&stop), "T05watch:300;");
+
+        stub.handle_packet(&mut debugger, &mut nes, "s");
+        assert_eq!(debugger.mode, RunMode::StepInto(1));
+        stub.handle_packet(&mut debugger, &mut nes, "D");
+        assert!(debugger.breakpoints.is_empty());
+    }
+
+    /* Reads from the client until `expected` has arrived */
+    fn expect_reply(client: &mut TcpStream, stub: &mut GdbStub, debugger: &mut Debugger, nes: &mut Nes, expected: &str) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut received = String::new();
+        while !received.contains(expected) {
+            assert!(Instant::now() < deadline, "expected {:?}, got {:?}", expected, received);
+            stub.poll(debugger, nes);
+            let mut buffer = [0; 256];
+            if let Ok(len) = client.read(&mut buffer) {
+                received.push_str(&String::from_utf8_lossy(&buffer[..len]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tcp_session() {
+        let (mut stub, mut debugger, mut nes) = stub();
+        let mut client = TcpStream::connect(stub.local_addr()).unwrap();
+        client.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+
+        client.write_all(encode_packet("qSupported:swbreak+").as_bytes()).unwrap();
+        expect_reply(&mut client, &mut stub, &mut debugger, &mut nes, "+$PacketSize=4000;qXfer:features:read+#");
+        assert!(stub.is_connected());
+
+        client.write_all(b"$g#00").unwrap();
+        expect_reply(&mut client, &mut stub, &mut debugger, &mut nes, "-");
+
+        client.write_all(format!("+{}{}", encode_packet("Z0,8025,1"), encode_packet("c")).as_bytes()).unwrap();
+        expect_reply(&mut client, &mut stub, &mut debugger, &mut nes, &format!("+{}+", encode_packet("OK")));
+        let stop = debugger.run_frame(&mut nes, |_| {}).1.unwrap();
+        stub.report_stop(&stop);
+        expect_reply(&mut client, &mut stub, &mut debugger, &mut nes, &encode_packet("S05"));
+
+        client.write_all(encode_packet("c").as_bytes()).unwrap();
+        expect_reply(&mut client, &mut stub, &mut debugger, &mut nes, "+");
+        client.write_all(&[0x03]).unwrap();
+        expect_reply(&mut client, &mut stub, &mut debugger, &mut nes, &encode_packet("S02"));
+        assert!(debugger.is_halted());
+
+        drop(client);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while stub.is_connected() {
+            assert!(Instant::now() < deadline);
+            stub.poll(&mut debugger, &mut nes);
+        }
+    }
+
+    #[test]
+    fn test_target_xml() {
+        let (mut stub, mut debugger, mut nes) = stub();
+
+        let first = stub.handle_packet(&mut debugger, &mut nes, "qXfer:features:read:target.xml:0,1b").unwrap();
+        assert_eq!(first, "m<?xml version=\"1.0\"?>\n<!DOC");
+        let rest = stub.handle_packet(&mut debugger, &mut nes, "qXfer:features:read:target.xml:1b,1000").unwrap();
+        assert!(rest.starts_with("lTYPE target"));
+        assert!(rest.contains("<reg name=\"pc\" bitsize=\"16\" type=\"code_ptr\"/>"));
+    }
+}
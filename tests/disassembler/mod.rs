@@ -0,0 +1 @@
+pub mod test_disassembler;
\ No newline at end of file
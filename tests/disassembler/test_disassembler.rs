@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::opcodes::Code;
+    use nes::emulator::addressing_modes::AddressingMode;
+    use nes::helpers::disassembler::{ Instruction, disassemble, ca65_listing, ca65_listing_logged };
+
+    #[test]
+    fn test_decode() {
+        let instruction = Instruction::decode(&[0xBD, 0x34, 0x12], 0xC000).unwrap();
+
+        assert_eq!(instruction, Instruction {
+            address: 0xC000,
+            opcode: 0xBD,
+            code: Code::LDA,
+            mode: AddressingMode::Absolute_X,
+            operand: 0x1234,
+            length: 3,
+        });
+        assert_eq!(instruction.mnemonic(), "LDA");
+        assert_eq!(instruction.bytes(), vec![0xBD, 0x34, 0x12]);
+        assert_eq!(instruction.target(), None);
+        assert_eq!(instruction.next_address(), 0xC003);
+        assert_eq!(Instruction::decode(&[0xBD, 0x34], 0xC000), None);
+        assert_eq!(Instruction::decode(&[], 0xC000), None);
+    }
+
+    #[test]
+    fn test_targets() {
+        let target = |bytes: &[u8]| Instruction::decode(bytes, 0xC010).unwrap().target();
+
+        assert_eq!(target(&[0xD0, 0x05]), Some(0xC017));
+        assert_eq!(target(&[0xD0, 0xFE]), Some(0xC010));
+        assert_eq!(target(&[0x20, 0x00, 0x80]), Some(0x8000));
+        assert_eq!(target(&[0x4C, 0x00, 0x90]), Some(0x9000));
+        assert_eq!(target(&[0x6C, 0xFC, 0xFF]), None);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let bytes = [
+            0xA9, 0x10, 0x0A, 0xB1, 0x20, 0xA1, 0x20, 0x96, 0x30, 0x6C, 0xFC, 0xFF,
+            0xEB, 0x01, 0xA7, 0x10, 0x1C, 0x00, 0x02, 0x60, 0xAD,
+        ];
+        let listing: Vec<String> = disassemble(&bytes, 0x8000).iter()
+            .map(|instruction| format!("{:04X} {}", instruction.address, instruction))
+            .collect();
+
+        expect_test::expect![[r#"
+            8000 LDA #$10
+            8002 ASL A
+            8003 LDA ($20),Y
+            8005 LDA ($20,X)
+            8007 STX $30,Y
+            8009 JMP ($FFFC)
+            800C *SBC #$01
+            800E *LAX $10
+            8010 *NOP $0200,X
+            8013 RTS"#]].assert_eq(&listing.join("\n"));
+    }
+
+    #[test]
+    fn test_ca65_listing() {
+        let bytes = [
+            0xA2, 0x08,         // C000 LDX #$08
+            0xCA,               // C002 DEX
+            0xD0, 0xFD,         // C003 BNE $C002
+            0x20, 0x0E, 0xC0,   // C005 JSR $C00E
+            0x8D, 0x10, 0x00,   // C008 STA $0010 kept absolute
+            0x4C, 0x00, 0xC0,   // C00B JMP $C000
+            0x04, 0x10,         // C00E *NOP $10
+            0x60,               // C010 RTS
+            0x10, 0x7F,         // C011 BPL out of the bank
+            0x20,               // C013 cut off JSR
+        ];
+
+        expect_test::expect![[r#"
+            .setcpu "6502"
+            .org $C000
+
+            LC000:  LDX #$08
+            LC002:  DEX
+                    BNE LC002
+                    JSR LC00E
+                    STA a:$0010
+                    JMP LC000
+            LC00E:  .byte $04,$10      ; *NOP $10
+                    RTS
+                    BPL $C092
+                    .byte $20
+        "#]].assert_eq(&ca65_listing(&bytes, 0xC000));
+    }
+
+    #[test]
+    fn test_ca65_listing_logged() {
+        let bytes = [
+            0xAD, 0x0A, 0xC0,   // C000 LDA $C00A
+            0x4C, 0x00, 0xC0,   // C003 JMP $C000
+            0xA9, 0x10,         // C006 never run, decoded anyway
+            0x00, 0xBD,         // C008 read as data
+            0x20, 0x40, 0x60,   // C00A read as data
+            0xA5, 0x20,         // C00D run once, but the operand is read as data
+        ];
+        let log = [
+            0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+            0x00, 0x00,
+            0x02, 0x02,
+            0x22, 0x02, 0x02,
+            0x01, 0x02,
+        ];
+
+        expect_test::expect![[r#"
+            .setcpu "6502"
+            .org $C000
+
+            LC000:  LDA $C00A
+                    JMP LC000
+                    LDA #$10
+                    .byte $00,$BD,$20,$40,$60
+                    .byte $A5,$20
+        "#]].assert_eq(&ca65_listing_logged(&bytes, 0xC000, &log));
+    }
+}
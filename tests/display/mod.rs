@@ -0,0 +1 @@
+pub mod test_display;
\ No newline at end of file
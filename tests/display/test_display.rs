@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test {
+    use nes::player::display::{ DisplaySettings, Overscan, AspectRatio, Rect };
+    use expect_test::expect;
+
+    #[test]
+    fn test_default_window_size() {
+        let display = DisplaySettings::default();
+
+        assert_eq!(display.window_size(), (768, 720));
+        assert_eq!(display.viewport(768, 720), Rect { x: 0, y: 0, width: 768, height: 720 });
+    }
+
+    #[test]
+    fn test_overscan_crop() {
+        let display = DisplaySettings { overscan: Overscan::tv(), ..Default::default() };
+
+        assert_eq!(display.visible_size(), (256, 224));
+        assert_eq!(display.window_size(), (768, 672));
+        assert_eq!(display.source_rect(256, 240), Rect { x: 0, y: 8, width: 256, height: 224 });
+        assert_eq!(display.source_rect(512, 480), Rect { x: 0, y: 16, width: 512, height: 448 });
+    }
+
+    #[test]
+    fn test_aspect_ratios() {
+        let sizes: Vec<String> = [AspectRatio::Square, AspectRatio::PixelAspect8x7, AspectRatio::Display4x3].iter()
+            .map(|&aspect| {
+                let display = DisplaySettings { overscan: Overscan::tv(), aspect, scale: 2.0, ..Default::default() };
+                format!("{:?}", display.window_size())
+            })
+            .collect();
+
+        let expected = expect!["(512, 448) (585, 448) (597, 448)"];
+        expected.assert_eq(&sizes.join(" "));
+    }
+
+    #[test]
+    fn test_viewport_letterbox() {
+        let display = DisplaySettings::default();
+
+        assert_eq!(display.viewport(1000, 480), Rect { x: 244, y: 0, width: 512, height: 480 });
+        assert_eq!(display.viewport(512, 1000), Rect { x: 0, y: 260, width: 512, height: 480 });
+    }
+
+    #[test]
+    fn test_integer_scaling() {
+        let display = DisplaySettings { integer_scaling: true, ..Default::default() };
+
+        assert_eq!(display.viewport(1000, 1000), Rect { x: 116, y: 140, width: 768, height: 720 });
+        assert_eq!(display.viewport(100, 100), Rect { x: -78, y: -70, width: 256, height: 240 });
+    }
+
+    #[test]
+    fn test_frame_position() {
+        let display = DisplaySettings { overscan: Overscan::tv(), ..Default::default() };
+        let viewport = display.viewport(1000, 448);
+
+        assert_eq!(viewport, Rect { x: 244, y: 0, width: 512, height: 448 });
+        assert_eq!(display.frame_position(viewport, 244, 0), Some((0, 8)));
+        assert_eq!(display.frame_position(viewport, 755, 447), Some((255, 231)));
+        assert_eq!(display.frame_position(viewport, 500, 224), Some((128, 120)));
+        assert_eq!(display.frame_position(viewport, 243, 100), None);
+        assert_eq!(display.frame_position(viewport, 756, 100), None);
+    }
+}
@@ -0,0 +1 @@
+pub mod test_filters;
\ No newline at end of file
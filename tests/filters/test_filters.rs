@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod test {
+    use nes::player::filters::FilterKind;
+    use nes::emulator::frame::Frame;
+
+    fn frame_with(rgb: impl Fn(usize, usize) -> (u8, u8, u8)) -> Frame {
+        let mut frame = Frame::new();
+        for y in 0..Frame::HEIGHT {
+            for x in 0..Frame::WIDTH {
+                let (r, g, b) = rgb(x, y);
+                let base = (y * Frame::WIDTH + x) * 3;
+                frame.data[base..base + 3].copy_from_slice(&[r, g, b]);
+            }
+        }
+        frame
+    }
+
+    fn rgb_at(output: &[u8], width: usize, x: usize, y: usize) -> (u8, u8, u8) {
+        let base = (y * width + x) * 3;
+        (output[base], output[base + 1], output[base + 2])
+    }
+
+    /* White below the diagonal, black above */
+    fn diagonal() -> Frame {
+        frame_with(|x, y| if x <= y { (255, 255, 255) } else { (0, 0, 0) })
+    }
+
+    #[test]
+    fn test_filter_names() {
+        for kind in FilterKind::ALL {
+            assert_eq!(FilterKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(FilterKind::from_name("bilinear"), None);
+        assert_eq!(FilterKind::Crt.next(), FilterKind::None);
+    }
+
+    #[test]
+    fn test_output_sizes() {
+        let sizes: Vec<String> = FilterKind::ALL.iter()
+            .map(|kind| {
+                let (width, height) = kind.create().output_size(Frame::WIDTH, Frame::HEIGHT);
+                format!("{} {}x{}", kind.name(), width, height)
+            })
+            .collect();
+
+        assert_eq!(sizes.join(", "), "none 256x240, ntsc 602x240, scale2x 512x480, scale3x 768x720, \
+            hq2x 512x480, xbrz2x 512x480, xbrz3x 768x720, scanlines 512x480, crt 768x720");
+    }
+
+    #[test]
+    fn test_filters_fill_output() {
+        let frame = diagonal();
+        for kind in FilterKind::ALL {
+            let mut filter = kind.create();
+            let (width, height) = filter.output_size(Frame::WIDTH, Frame::HEIGHT);
+            let mut output = Vec::new();
+            filter.apply(&frame, &mut output);
+
+            assert_eq!(output.len(), width * height * 3, "{}", kind.name());
+        }
+    }
+
+    #[test]
+    fn test_flat_colour_is_preserved() {
+        let frame = frame_with(|_, _| (10, 120, 200));
+        for kind in [FilterKind::Scale2x, FilterKind::Scale3x, FilterKind::Hq2x, FilterKind::Xbrz2x, FilterKind::Xbrz3x] {
+            let mut output = Vec::new();
+            kind.create().apply(&frame, &mut output);
+
+            assert!(output.chunks(3).all(|rgb| rgb == [10, 120, 200]), "{}", kind.name());
+        }
+    }
+
+    #[test]
+    fn test_scale2x_smooths_diagonal() {
+        let mut output = Vec::new();
+        FilterKind::Scale2x.create().apply(&diagonal(), &mut output);
+
+        /* Pixel (11, 10) is black with white to the left and below */
+        assert_eq!(rgb_at(&output, 512, 22, 21), (255, 255, 255));
+        assert_eq!(rgb_at(&output, 512, 23, 20), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_edge_corners_are_blended() {
+        for kind in [FilterKind::Hq2x, FilterKind::Xbrz2x] {
+            let mut output = Vec::new();
+            kind.create().apply(&diagonal(), &mut output);
+
+            let (r, _, _) = rgb_at(&output, 512, 22, 21);
+            assert!(r > 0 && r < 255, "{}: {}", kind.name(), r);
+        }
+    }
+
+    #[test]
+    fn test_hq2x_isolated_pixel() {
+        let mut output = Vec::new();
+        FilterKind::Hq2x.create().apply(&frame_with(|x, y| if (x, y) == (10, 10) { (255, 255, 255) } else { (0, 0, 0) }), &mut output);
+
+        /* Every neighbour differs, so each corner is 14:1:1 with its two black neighbours */
+        for (x, y) in [(20, 20), (21, 20), (20, 21), (21, 21)] {
+            assert_eq!(rgb_at(&output, 512, x, y), (223, 223, 223));
+        }
+        assert_eq!(rgb_at(&output, 512, 22, 20), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_xbrz_diagonal_line() {
+        let mut output = Vec::new();
+        FilterKind::Xbrz2x.create().apply(&diagonal(), &mut output);
+        assert_eq!(rgb_at(&output, 512, 22, 21), (127, 127, 127));
+        assert_eq!(rgb_at(&output, 512, 22, 20), (0, 0, 0));
+
+        FilterKind::Xbrz3x.create().apply(&diagonal(), &mut output);
+        assert_eq!(rgb_at(&output, 768, 33, 32), (223, 223, 223));
+        assert_eq!(rgb_at(&output, 768, 34, 32), (31, 31, 31));
+        assert_eq!(rgb_at(&output, 768, 33, 31), (31, 31, 31));
+        assert_eq!(rgb_at(&output, 768, 35, 30), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_scanlines_darken_odd_lines() {
+        let mut output = Vec::new();
+        FilterKind::Scanlines.create().apply(&frame_with(|_, _| (200, 100, 50)), &mut output);
+
+        assert_eq!(rgb_at(&output, 512, 3, 0), (200, 100, 50));
+        assert_eq!(rgb_at(&output, 512, 3, 1), (100, 50, 25));
+    }
+
+    #[test]
+    fn test_crt_mask_columns() {
+        let mut output = Vec::new();
+        FilterKind::Crt.create().apply(&frame_with(|_, _| (100, 100, 100)), &mut output);
+
+        assert_eq!(rgb_at(&output, 768, 0, 0), (100, 70, 70));
+        assert_eq!(rgb_at(&output, 768, 1, 0), (70, 100, 70));
+        assert_eq!(rgb_at(&output, 768, 2, 0), (70, 70, 100));
+    }
+}
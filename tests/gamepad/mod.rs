@@ -0,0 +1 @@
+pub mod test_gamepad;
\ No newline at end of file
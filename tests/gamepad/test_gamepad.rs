@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::joypad::Buttons;
+    use nes::player::gamepad::{ self, GamepadButton, GamepadSettings, GamepadState };
+
+    #[test]
+    fn test_default_mapping() {
+        let settings = GamepadSettings::default();
+
+        assert_eq!(settings.map(GamepadButton::B), Buttons::A);
+        assert_eq!(settings.map(GamepadButton::A), Buttons::B);
+        assert_eq!(settings.map(GamepadButton::DPadLeft), Buttons::Left);
+        assert_eq!(settings.map(GamepadButton::Back), Buttons::Select);
+        assert_eq!(settings.map(GamepadButton::Start), Buttons::Start);
+        assert_eq!(settings.map(GamepadButton::X), Buttons::empty());
+    }
+
+    #[test]
+    fn test_custom_face_buttons() {
+        let settings = GamepadSettings {
+            a: GamepadButton::from_name("rightshoulder").unwrap(),
+            b: GamepadButton::from_name("X").unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(settings.map(GamepadButton::RightShoulder), Buttons::A);
+        assert_eq!(settings.map(GamepadButton::X), Buttons::B);
+        assert_eq!(settings.map(GamepadButton::B), Buttons::empty());
+        assert_eq!(GamepadButton::from_name("trigger"), None);
+    }
+
+    #[test]
+    fn test_stick_deadzone() {
+        let settings = GamepadSettings { deadzone: 0.5, ..Default::default() };
+
+        assert_eq!(settings.stick_buttons(0, 0), Buttons::empty());
+        assert_eq!(settings.stick_buttons(-16000, 16000), Buttons::empty());
+        assert_eq!(settings.stick_buttons(-20000, 0), Buttons::Left);
+        assert_eq!(settings.stick_buttons(i16::MAX, i16::MIN), Buttons::Right | Buttons::Up);
+        assert_eq!(settings.stick_buttons(0, 20000), Buttons::Down);
+    }
+
+    #[test]
+    fn test_gamepad_state() {
+        let settings = GamepadSettings::default();
+        let mut state = GamepadState::new(Some(1));
+
+        state.press(GamepadButton::DPadUp, &settings);
+        state.press(GamepadButton::B, &settings);
+        state.move_stick(Some(-30000), None);
+        assert_eq!(state.buttons(&settings), Buttons::Up | Buttons::A | Buttons::Left);
+
+        state.release(GamepadButton::DPadUp, &settings);
+        state.move_stick(None, Some(30000));
+        assert_eq!(state.buttons(&settings), Buttons::A | Buttons::Left | Buttons::Down);
+        assert_eq!(state.port, Some(1));
+
+        state.press(GamepadButton::Y, &settings);
+        assert_eq!(state.turbo_buttons(), Buttons::A);
+        assert_eq!(state.buttons(&settings), Buttons::A | Buttons::Left | Buttons::Down);
+        state.release(GamepadButton::Y, &settings);
+        assert_eq!(state.turbo_buttons(), Buttons::empty());
+    }
+
+    #[test]
+    fn test_free_port() {
+        assert_eq!(gamepad::free_port([].into_iter()), Some(0));
+        assert_eq!(gamepad::free_port([Some(0)].into_iter()), Some(1));
+        assert_eq!(gamepad::free_port([Some(1), None].into_iter()), Some(0));
+        assert_eq!(gamepad::free_port([Some(0), Some(1), None].into_iter()), Some(2));
+        assert_eq!(gamepad::free_port([Some(0), Some(3), Some(1), Some(2)].into_iter()), None);
+    }
+}
@@ -69,7 +69,7 @@ impl TestRom {
     pub fn default_rom() -> Rom {
         Rom {
             prg_rom: vec![1; 2 * PRG_ROM_PAGE_SIZE],
-            chr_rom: vec![2; 1 * CHR_ROM_PAGE_SIZE],
+            chr_rom: vec![2; CHR_ROM_PAGE_SIZE],
             mapper: 0,
             screen_mirroring: Mirroring::VERTICAL,
         }
@@ -79,4 +79,33 @@ impl TestRom {
 pub fn default_ppu(mirroring: Mirroring) -> Ppu {
     let test_rom = TestRom::default_rom();
     Ppu::new(test_rom.chr_rom, mirroring)
-}
\ No newline at end of file
+}
+
+/*
+    8000: LDA #$80, STA $2000   ; Enable NMI
+    8005: JMP $8005
+    9000: INC $00               ; Count NMIs
+          LDA #$01, STA $4016, LDA #$00, STA $4016
+          LDA $4016, AND #$01, STA $01    ; Player 1 A
+          LDA $4017, AND #$01, STA $02    ; Player 2 A
+          RTI
+ */
+pub fn counter_rom() -> Rom {
+    let mut prg_rom = vec![0xEA; 2 * PRG_ROM_PAGE_SIZE];
+    prg_rom[0x0000..0x0008].copy_from_slice(&[0xA9, 0x80, 0x8D, 0x00, 0x20, 0x4C, 0x05, 0x80]);
+    prg_rom[0x1000..0x101B].copy_from_slice(&[
+        0xE6, 0x00,
+        0xA9, 0x01, 0x8D, 0x16, 0x40, 0xA9, 0x00, 0x8D, 0x16, 0x40,
+        0xAD, 0x16, 0x40, 0x29, 0x01, 0x85, 0x01,
+        0xAD, 0x17, 0x40, 0x29, 0x01, 0x85, 0x02,
+        0x40,
+    ]);
+    prg_rom[0x7FFA..].copy_from_slice(&[0x00, 0x90, 0x00, 0x80, 0x00, 0x90]);
+
+    Rom {
+        prg_rom,
+        chr_rom: vec![0; CHR_ROM_PAGE_SIZE],
+        mapper: 0,
+        screen_mirroring: Mirroring::VERTICAL,
+    }
+}
@@ -0,0 +1 @@
+pub mod test_input;
\ No newline at end of file
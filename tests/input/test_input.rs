@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::joypad::Buttons;
+    use nes::player::input::{ Input, Socd };
+    use nes::player::microphone::peak_level;
+
+    fn frames(input: &mut Input, port: usize, count: usize) -> Vec<bool> {
+        (0..count).map(|_| input.next_frame()[port].contains(Buttons::A)).collect()
+    }
+
+    #[test]
+    fn test_turbo_rate() {
+        let mut input = Input::new(30, Socd::Allow);
+        input.press_turbo(0, Buttons::A);
+        assert_eq!(frames(&mut input, 0, 4), [true, false, true, false]);
+
+        let mut input = Input::new(15, Socd::Allow);
+        input.next_frame();
+        input.press_turbo(1, Buttons::A);
+        assert_eq!(frames(&mut input, 1, 6), [true, true, false, false, true, true]);
+
+        input.release_turbo(1, Buttons::A);
+        assert_eq!(frames(&mut input, 1, 2), [false, false]);
+        assert_eq!(Input::new(100, Socd::Allow).turbo_rate, 30);
+    }
+
+    #[test]
+    fn test_turbo_with_held() {
+        let mut input = Input::new(30, Socd::Allow);
+        input.press(0, Buttons::A);
+        input.press_turbo(0, Buttons::A | Buttons::B);
+
+        assert_eq!(input.next_frame()[0], Buttons::A | Buttons::B);
+        assert_eq!(input.next_frame()[0], Buttons::A);
+    }
+
+    #[test]
+    fn test_socd() {
+        let held = |socd| {
+            let mut input = Input::new(15, socd);
+            input.press(0, Buttons::Right | Buttons::Up);
+            input.press(0, Buttons::Left | Buttons::Down);
+            input.next_frame()[0]
+        };
+
+        assert_eq!(held(Socd::Allow), Buttons::Left | Buttons::Right | Buttons::Up | Buttons::Down);
+        assert_eq!(held(Socd::Neutral), Buttons::empty());
+        assert_eq!(held(Socd::LastWins), Buttons::Left | Buttons::Down);
+
+        let mut input = Input::new(15, Socd::LastWins);
+        input.press(0, Buttons::Left);
+        input.press(0, Buttons::Right);
+        input.release(0, Buttons::Right);
+        assert_eq!(input.next_frame()[0], Buttons::Left);
+        assert_eq!(Socd::from_name("last-wins"), Some(Socd::LastWins));
+        assert_eq!(Socd::from_name("first-wins"), None);
+    }
+
+    #[test]
+    fn test_macro() {
+        let mut input = Input::default();
+        assert!(!input.play_macro(0));
+
+        assert!(input.toggle_macro_recording(0));
+        input.press(0, Buttons::B);
+        input.next_frame();
+        input.press(0, Buttons::A);
+        input.next_frame();
+        input.release(0, Buttons::A | Buttons::B);
+        input.next_frame();
+        assert!(!input.toggle_macro_recording(0));
+        assert_eq!(input.macro_frames(0), [Buttons::B, Buttons::A | Buttons::B, Buttons::empty()]);
+
+        input.press(0, Buttons::Start);
+        assert!(input.play_macro(0));
+        let played: Vec<Buttons> = (0..4).map(|_| input.next_frame()[0]).collect();
+        assert_eq!(played, [Buttons::B | Buttons::Start, Buttons::A | Buttons::B | Buttons::Start, Buttons::Start, Buttons::Start]);
+        assert_eq!(input.next_frame()[1], Buttons::empty());
+    }
+
+    #[test]
+    fn test_microphone_level() {
+        assert_eq!(peak_level(&[]), 0.0);
+        assert_eq!(peak_level(&[100, -16384, 8192]), 0.5);
+        assert_eq!(peak_level(&[i16::MIN]), 1.0);
+    }
+}
\ No newline at end of file
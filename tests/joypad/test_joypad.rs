@@ -3,19 +3,82 @@ mod test {
     use nes::emulator::joypad::Buttons;
     use nes::emulator::bus::Bus;
     use nes::emulator::memory::Mem;
-    use crate::helpers::{ TestRom, load_into_memory, check };
+    use crate::helpers::TestRom;
     use expect_test::expect;
 
+    fn read_port(bus: &mut Bus, addr: u16, count: usize) -> String {
+        (0..count).map(|_| format!("{:02X}", bus.mem_read(addr))).collect::<Vec<_>>().join(" ")
+    }
+
     #[test]
     fn test_joypad_read_correctly() {
         let mut bus = Bus::new(TestRom::default_rom());
-        
-        bus.joypad.press(Buttons::A);
+
+        bus.set_buttons(0, Buttons::A | Buttons::Start | Buttons::Right);
+        bus.mem_write(0x4016, 0x01);
+        bus.mem_write(0x4016, 0x00);
+
+        let expected = expect!["41 40 40 41 40 40 40 41 41 41"];
+        expected.assert_eq(&read_port(&mut bus, 0x4016, 10));
+    }
+
+    #[test]
+    fn test_second_joypad() {
+        let mut bus = Bus::new(TestRom::default_rom());
+
+        bus.set_buttons(0, Buttons::A);
+        bus.set_buttons(1, Buttons::B | Buttons::Down);
+        bus.mem_write(0x4016, 0x01);
         bus.mem_write(0x4016, 0x00);
-        
-        let button_1 = bus.mem_read(0x4016);
-        println!("Button 1: {} {:08b}", button_1, bus.joypad.state.bits());
-        let button_1 = bus.mem_read(0x4016);
-        println!("Button 1: {} {:08b}", button_1, bus.joypad.state.bits());
+
+        let expected = expect!["40 41 40 40 40 41 40 40 41"];
+        expected.assert_eq(&read_port(&mut bus, 0x4017, 9));
+        let expected = expect!["41 40"];
+        expected.assert_eq(&read_port(&mut bus, 0x4016, 2));
+    }
+
+    #[test]
+    fn test_strobe_high_returns_a() {
+        let mut bus = Bus::new(TestRom::default_rom());
+
+        bus.set_buttons(0, Buttons::A);
+        bus.mem_write(0x4016, 0x01);
+        let expected = expect!["41 41 41"];
+        expected.assert_eq(&read_port(&mut bus, 0x4016, 3));
+
+        bus.set_buttons(0, Buttons::empty());
+        let expected = expect!["40"];
+        expected.assert_eq(&read_port(&mut bus, 0x4016, 1));
+    }
+
+    #[test]
+    fn test_buttons_latched_on_strobe() {
+        let mut bus = Bus::new(TestRom::default_rom());
+
+        bus.mem_write(0x4016, 0x01);
+        bus.mem_write(0x4016, 0x00);
+        bus.set_buttons(0, Buttons::A);
+
+        let expected = expect!["40"];
+        expected.assert_eq(&read_port(&mut bus, 0x4016, 1));
+    }
+
+    #[test]
+    fn test_four_score_reports() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        bus.set_four_score(true);
+
+        bus.set_buttons(0, Buttons::A);
+        bus.set_buttons(1, Buttons::B);
+        bus.set_buttons(2, Buttons::Start);
+        bus.set_buttons(3, Buttons::Right);
+        bus.mem_write(0x4016, 0x01);
+        bus.mem_write(0x4016, 0x00);
+
+        let bits = |bus: &mut Bus, addr| (0..26).map(|_| (bus.mem_read(addr) & 1).to_string()).collect::<String>();
+        let expected = expect!["10000000000100000001000011"];
+        expected.assert_eq(&bits(&mut bus, 0x4016));
+        let expected = expect!["01000000000000010010000011"];
+        expected.assert_eq(&bits(&mut bus, 0x4017));
     }
 }
\ No newline at end of file
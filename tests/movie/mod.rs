@@ -0,0 +1 @@
+pub mod test_movie;
\ No newline at end of file
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::joypad::Buttons;
+    use nes::emulator::movie::{ self, Movie, MovieFrame, MoviePlayback, MovieRecording };
+    use crate::helpers::counter_rom;
+
+    const FM2: &str = "version 3
+emuVersion 22020
+rerecordCount 4
+palFlag 0
+romFilename counter
+guid 452DE2C3-EF43-2FA9-77AC-0677FC51543B
+fourscore 0
+port0 1
+port1 1
+port2 0
+comment author someone
+|0|.......A|........||
+|0|R..U....|......B.||
+|1|........|........||
+|2|RLDUTSBA|........||
+";
+
+    #[test]
+    fn test_parse_fm2() {
+        let movie = Movie::parse(FM2).unwrap();
+
+        assert_eq!(movie.rom_filename, "counter");
+        assert_eq!(movie.rerecord_count, 4);
+        assert_eq!(movie.comments, vec!["author someone"]);
+        assert_eq!(movie.rom_checksum, None);
+        assert_eq!(movie.frames, vec![
+            MovieFrame { commands: 0, ports: [Buttons::A, Buttons::empty(), Buttons::empty(), Buttons::empty()] },
+            MovieFrame { commands: 0, ports: [Buttons::Right | Buttons::Up, Buttons::B, Buttons::empty(), Buttons::empty()] },
+            MovieFrame { commands: movie::SOFT_RESET, ports: [Buttons::empty(), Buttons::empty(), Buttons::empty(), Buttons::empty()] },
+            MovieFrame { commands: movie::HARD_RESET, ports: [Buttons::all(), Buttons::empty(), Buttons::empty(), Buttons::empty()] },
+        ]);
+    }
+
+    #[test]
+    fn test_fm2_round_trip() {
+        let mut nes = Nes::new(counter_rom());
+        let mut movie = Movie::record(&mut nes, "counter", false);
+        movie.frames = Movie::parse(FM2).unwrap().frames;
+
+        let text = movie.to_fm2();
+        assert!(text.contains("\n|2|RLDUTSBA|........||\n"));
+        assert!(text.contains("\ncomment savestate is not an FCEUX state, only this emulator can play this movie\nsavestate base64:TkVT"));
+        assert_eq!(Movie::parse(&text).unwrap(), movie);
+    }
+
+    #[test]
+    fn test_four_score() {
+        let text = "fourscore 1\n|0|.......A|......B.|...U....|....T...||\n";
+        let movie = Movie::parse(text).unwrap();
+
+        assert!(movie.four_score);
+        assert_eq!(movie.frames[0].ports, [Buttons::A, Buttons::B, Buttons::Up, Buttons::Start]);
+        assert!(movie.to_fm2().contains("\nfourscore 1\n"));
+        assert!(movie.to_fm2().ends_with(&text[12..]));
+
+        let mut nes = Nes::new(counter_rom());
+        MoviePlayback::start(movie, &mut nes).unwrap();
+        assert!(nes.four_score());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Movie::parse("|x|........|........||"), Err("line 1: invalid commands 'x'".to_string()));
+        assert_eq!(Movie::parse("|0|RL|........||"), Err("line 1: expected 8 buttons in 'RL'".to_string()));
+        assert_eq!(Movie::parse("romChecksum base64:AAAA"), Err("line 1: romChecksum is not an MD5".to_string()));
+        assert_eq!(Movie::parse("romChecksum 0x+1"), Err("line 1: invalid hex '+1'".to_string()));
+        assert_eq!(Movie::parse("romChecksum 0x0é0"), Err("line 1: invalid hex '0é0'".to_string()));
+        assert_eq!(Movie::parse("savestate 0x46435358"),
+            Err("line 1: savestate is not from this emulator, only movies recorded from power on can be played".to_string()));
+    }
+
+    #[test]
+    fn test_record_and_play_back() {
+        let mut nes = Nes::new(counter_rom());
+        let mut recording = MovieRecording::start(&mut nes, "counter", true);
+
+        for frame in 0..6 {
+            let buttons = if frame % 2 == 0 { Buttons::A } else { Buttons::empty() };
+            let commands = if frame == 3 { movie::SOFT_RESET } else { 0 };
+            movie::run_commands(&mut nes, commands);
+            nes.set_buttons(0, buttons);
+            recording.record_frame(&nes, commands);
+            nes.run_frame();
+        }
+        let expected = nes.save_state();
+
+        let mut other = Nes::new(counter_rom());
+        let mut playback = MoviePlayback::start(Movie::parse(&recording.movie.to_fm2()).unwrap(), &mut other).unwrap();
+        while playback.next_frame(&mut other).is_some() {
+            other.run_frame();
+        }
+
+        assert!(playback.is_finished());
+        assert_eq!(playback.position(), 6);
+        assert_eq!(other.cpu.bus.cpu_vram[0x00], nes.cpu.bus.cpu_vram[0x00]);
+        assert!(other.save_state() == expected);
+    }
+
+    #[test]
+    fn test_rerecord() {
+        let mut nes = Nes::new(counter_rom());
+        let mut recording = MovieRecording::start(&mut nes, "counter", true);
+
+        for _ in 0..3 {
+            recording.record_frame(&nes, 0);
+            nes.run_frame();
+        }
+        let state = nes.save_state();
+        for _ in 0..3 {
+            recording.record_frame(&nes, 0);
+            nes.run_frame();
+        }
+
+        nes.load_state(&state).unwrap();
+        recording.rerecord(&nes);
+
+        assert_eq!(recording.movie.frames.len(), 3);
+        assert_eq!(recording.movie.rerecord_count, 1);
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut nes = Nes::new(counter_rom());
+        let mut recording = MovieRecording::start(&mut nes, "counter", true);
+        for frame in 0..6 {
+            nes.set_buttons(0, if frame < 3 { Buttons::A } else { Buttons::B });
+            recording.record_frame(&nes, 0);
+            nes.run_frame();
+        }
+
+        let mut playback = MoviePlayback::start(recording.movie, &mut nes).unwrap();
+        let mut states = Vec::new();
+        while playback.next_frame(&mut nes).is_some() {
+            states.push(nes.save_state());
+            nes.run_frame();
+        }
+
+        /* Going back to frame 2 plays frame 2 again, not what follows frame 6 */
+        nes.load_state(&states[2]).unwrap();
+        playback.seek(&nes);
+        assert_eq!(playback.position(), 2);
+        assert!(!playback.is_finished());
+        assert_eq!(playback.next_frame(&mut nes).unwrap().ports[0], Buttons::A);
+        assert_eq!(playback.next_frame(&mut nes).unwrap().ports[0], Buttons::B);
+    }
+
+    #[test]
+    fn test_different_rom() {
+        let mut nes = Nes::new(counter_rom());
+        let movie = Movie::record(&mut nes, "counter", true);
+
+        let mut rom = counter_rom();
+        rom.chr_rom[0] = 1;
+        let mut other = Nes::new(rom);
+
+        assert_eq!(MoviePlayback::start(movie, &mut other).err(), Some("Movie was recorded with a different ROM".to_string()));
+    }
+}
@@ -0,0 +1 @@
+pub mod test_nes;
\ No newline at end of file
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::joypad::Buttons;
+    use crate::helpers::counter_rom;
+    use nes::emulator::frame::Frame;
+
+    #[test]
+    fn test_run_frame() {
+        let mut nes = Nes::new(counter_rom());
+        assert_eq!(nes.cpu.program_counter, 0x8000);
+
+        for _ in 0..3 {
+            nes.run_frame();
+        }
+
+        assert_eq!(nes.frame_count(), 3);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x00], 3);
+        assert_eq!(nes.framebuffer().pixels.len(), Frame::WIDTH * Frame::HEIGHT);
+        assert_eq!(nes.audio_samples(), vec![0.0; 2201]); // 3 frames at 60.1 fps, silent without an APU
+        assert!(nes.audio_samples().is_empty());
+        nes.run_frame();
+        assert_eq!(nes.audio_samples().len(), 734);
+    }
+
+    #[test]
+    fn test_set_buttons() {
+        let mut nes = Nes::new(counter_rom());
+
+        nes.set_buttons(0, Buttons::A | Buttons::Start);
+        nes.run_frame();
+        assert_eq!(nes.cpu.bus.cpu_vram[0x01], 1);
+
+        nes.set_buttons(0, Buttons::Start);
+        nes.run_frame();
+        assert_eq!(nes.cpu.bus.cpu_vram[0x01], 0);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x02], 0);
+
+        nes.set_buttons(1, Buttons::A);
+        nes.run_frame();
+        assert_eq!(nes.cpu.bus.cpu_vram[0x01], 0);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x02], 1);
+        assert_eq!(nes.buttons(1), Buttons::A);
+    }
+
+    #[test]
+    fn test_reset_and_power_cycle() {
+        let mut nes = Nes::new(counter_rom());
+        nes.run_frame();
+        nes.run_frame();
+
+        nes.reset();
+        assert_eq!(nes.cpu.program_counter, 0x8000);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x00], 2); // RAM survives a reset
+
+        nes.power_cycle();
+        assert_eq!(nes.cpu.program_counter, 0x8000);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x00], 0);
+        assert_eq!(nes.frame_count(), 2);
+    }
+
+    #[test]
+    fn test_load_rom() {
+        let mut nes = Nes::new(counter_rom());
+        nes.run_frame();
+
+        let mut rom = counter_rom();
+        rom.prg_rom[0x7FFC..0x7FFE].copy_from_slice(&[0x05, 0x80]);
+        nes.load_rom(rom.clone());
+
+        assert_eq!(nes.rom(), &rom);
+        assert_eq!(nes.cpu.program_counter, 0x8005);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x00], 0);
+    }
+}
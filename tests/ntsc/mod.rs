@@ -0,0 +1 @@
+pub mod test_ntsc;
\ No newline at end of file
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod test {
+    use nes::player::ntsc::{ NtscFilter, NTSC_WIDTH };
+    use nes::emulator::palette::{ Palette, NtscParams };
+
+    const WIDTH: usize = 256;
+    const HEIGHT: usize = 240;
+
+    fn rgb_at(output: &[u8], x: usize, y: usize) -> (u8, u8, u8) {
+        let base = (y * NTSC_WIDTH + x) * 3;
+        (output[base], output[base + 1], output[base + 2])
+    }
+
+    #[test]
+    fn test_output_size() {
+        let mut ntsc = NtscFilter::new(NtscParams::default());
+        let output = ntsc.filter(&vec![0x0F; WIDTH * HEIGHT]);
+
+        assert_eq!(output.len(), NTSC_WIDTH * HEIGHT * 3);
+    }
+
+    #[test]
+    fn test_flat_colour_matches_palette() {
+        let params = NtscParams::default();
+        let palette = Palette::generate(&params);
+        let mut ntsc = NtscFilter::new(params);
+
+        for pixel in [0x16, 0x1A, 0x12, 0x30, 0x0F] {
+            let output = ntsc.filter(&vec![pixel; WIDTH * HEIGHT]);
+            let (r, g, b) = rgb_at(output, NTSC_WIDTH / 2, HEIGHT / 2);
+            let (er, eg, eb) = palette.pixel(pixel);
+
+            assert!((r as i16 - er as i16).abs() <= 1, "{:02X}: {} {}", pixel, r, er);
+            assert!((g as i16 - eg as i16).abs() <= 1, "{:02X}: {} {}", pixel, g, eg);
+            assert!((b as i16 - eb as i16).abs() <= 1, "{:02X}: {} {}", pixel, b, eb);
+        }
+    }
+
+    #[test]
+    fn test_colour_bleeds_across_edges() {
+        let mut ntsc = NtscFilter::new(NtscParams::default());
+        let pixels: Vec<u16> = (0..WIDTH * HEIGHT)
+            .map(|i| if i % WIDTH < WIDTH / 2 { 0x16 } else { 0x0F })
+            .collect();
+        let output = ntsc.filter(&pixels);
+
+        let red = rgb_at(output, 10, 0);
+        let edge = rgb_at(output, NTSC_WIDTH / 2, 0);
+
+        assert_ne!(edge, red);
+        assert_ne!(edge, (0, 0, 0));
+        assert_eq!(rgb_at(output, NTSC_WIDTH - 1, 0), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_dot_crawl_cycles_every_three_frames() {
+        let mut ntsc = NtscFilter::new(NtscParams::default());
+        let pixels: Vec<u16> = (0..WIDTH * HEIGHT)
+            .map(|i| if i % 2 == 0 { 0x30 } else { 0x0F })
+            .collect();
+
+        let frames: Vec<Vec<u8>> = (0..4).map(|_| ntsc.filter(&pixels).to_vec()).collect();
+
+        assert_ne!(frames[0], frames[1]);
+        assert_ne!(frames[1], frames[2]);
+        assert_eq!(frames[0], frames[3]);
+    }
+}
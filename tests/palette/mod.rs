@@ -0,0 +1 @@
+pub mod test_palette;
\ No newline at end of file
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::palette::{ Palette, NtscParams };
+    use expect_test::expect;
+
+    #[test]
+    fn test_invalid_palette_size() {
+        let palette = Palette::from_bytes(&[0; 100]);
+
+        let expected = expect![[r#"
+            Err(
+                "Invalid palette size: 100 bytes (expected 192 or 1536)",
+            )
+        "#]];
+        expected.assert_debug_eq(&palette);
+    }
+
+    #[test]
+    fn test_load_64_color_palette() {
+        let mut bytes = vec![0; 192];
+        bytes[0x16 * 3..0x16 * 3 + 3].copy_from_slice(&[200, 100, 50]);
+        let palette = Palette::from_bytes(&bytes).unwrap();
+
+        let expected = expect!["(200, 100, 50) (200, 75, 37) (149, 100, 37) (149, 75, 37)"];
+        expected.assert_eq(&format!("{:?} {:?} {:?} {:?}",
+            palette.color(0x16, 0b000),
+            palette.color(0x16, 0b001),
+            palette.color(0x16, 0b010),
+            palette.color(0x16, 0b111),
+        ));
+    }
+
+    #[test]
+    fn test_load_emphasis_palette() {
+        let bytes: Vec<u8> = (0..1536).map(|i| (i / 192) as u8).collect();
+        let palette = Palette::from_bytes(&bytes).unwrap();
+
+        let expected = expect!["(0, 0, 0) (3, 3, 3) (7, 7, 7)"];
+        expected.assert_eq(&format!("{:?} {:?} {:?}",
+            palette.color(0x3F, 0b000),
+            palette.color(0x00, 0b011),
+            palette.color(0x3F, 0b111),
+        ));
+    }
+
+    #[test]
+    fn test_generated_palette() {
+        let palette = Palette::generate(&NtscParams::default());
+
+        let expected = expect!["(0, 0, 0) (255, 255, 255) (148, 63, 52) (40, 126, 11) (74, 73, 199)"];
+        expected.assert_eq(&format!("{:?} {:?} {:?} {:?} {:?}",
+            palette.color(0x0F, 0),
+            palette.color(0x30, 0),
+            palette.color(0x16, 0),
+            palette.color(0x1A, 0),
+            palette.color(0x12, 0),
+        ));
+    }
+
+    #[test]
+    fn test_generated_palette_emphasis() {
+        let palette = Palette::generate(&NtscParams::default());
+        let (r, g, b) = palette.color(0x30, 0b001);
+
+        assert!(r > g && r > b);
+        assert_eq!(palette.color(0x0F, 0b111), (0, 0, 0));
+    }
+}
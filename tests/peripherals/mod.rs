@@ -0,0 +1,3 @@
+pub mod test_vaus;
+pub mod test_power_pad;
+pub mod test_family_keyboard;
\ No newline at end of file
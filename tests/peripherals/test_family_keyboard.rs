@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::bus::Bus;
+    use nes::emulator::memory::Mem;
+    use nes::emulator::family_keyboard::FamilyKeyboard;
+    use nes::emulator::input_device::DeviceKind;
+    use crate::helpers::TestRom;
+    use expect_test::expect;
+
+    /* The way Family BASIC scans, reset then both columns of every row */
+    fn scan(bus: &mut Bus, rows: usize) -> String {
+        bus.mem_write(0x4016, 0x05);
+        (0..rows)
+            .map(|_| {
+                bus.mem_write(0x4016, 0x04);
+                let low = bus.mem_read(0x4017) & 0x1E;
+                bus.mem_write(0x4016, 0x06);
+                let high = bus.mem_read(0x4017) & 0x1E;
+                format!("{:02X}{:02X}", low, high)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn keyboard_bus(keys: &[&str]) -> Bus {
+        let mut bus = Bus::new(TestRom::default_rom());
+        let mut keyboard = FamilyKeyboard::new();
+        for key in keys {
+            assert!(keyboard.set_key(key, true));
+        }
+        bus.plug_in(2, Box::new(keyboard));
+        bus
+    }
+
+    #[test]
+    fn test_matrix_scan() {
+        let mut bus = keyboard_bus(&["RETURN", "kana", "A", "space"]);
+
+        let expected = expect!["160E 1E1E 1E1E 1E1E 1E1E 1E1E 1C1E 1E1E 1E0E 0000"];
+        expected.assert_eq(&scan(&mut bus, 10));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut bus = keyboard_bus(&["A"]);
+
+        bus.mem_write(0x4016, 0x05);
+        bus.mem_write(0x4016, 0x00);
+        assert_eq!(bus.mem_read(0x4017) & 0x1E, 0);
+        assert_eq!(bus.mem_read(0x4016) & 0x01, 0); // Controller 1 still reads
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut keyboard = FamilyKeyboard::new();
+
+        assert!(!keyboard.set_key("F9", true));
+        keyboard.set_key("¥", true);
+        assert!(keyboard.is_pressed("¥"));
+        keyboard.release_all();
+        assert!(!keyboard.is_pressed("¥"));
+        assert!(DeviceKind::from_name("keyboard").is_some_and(|kind| kind.is_expansion()));
+    }
+
+    #[test]
+    fn test_microphone() {
+        let mut bus = Bus::new(TestRom::default_rom());
+
+        assert_eq!(bus.mem_read(0x4016) & 0x04, 0);
+        bus.microphone = true;
+        assert_eq!(bus.mem_read(0x4016) & 0x04, 0x04);
+        assert_eq!(bus.mem_read_debugging(0x4016) & 0x04, 0x04);
+        assert_eq!(bus.mem_read(0x4017) & 0x04, 0);
+    }
+}
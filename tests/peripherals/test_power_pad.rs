@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::bus::Bus;
+    use nes::emulator::memory::Mem;
+    use nes::emulator::power_pad::PowerPad;
+    use crate::helpers::TestRom;
+    use expect_test::expect;
+
+    /* D3 and D4 of each read */
+    fn read_lines(bus: &mut Bus, count: usize) -> String {
+        (0..count)
+            .map(|_| {
+                let value = bus.mem_read(0x4017);
+                format!("{}{}", (value >> 3) & 1, (value >> 4) & 1)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_serial_order() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        let mut power_pad = PowerPad::new();
+        for button in [2, 9, 3, 12] {
+            power_pad.set_button(button, true);
+        }
+        bus.plug_in(1, Box::new(power_pad));
+        bus.mem_write(0x4016, 0x01);
+        bus.mem_write(0x4016, 0x00);
+
+        let expected = expect!["10 01 01 10 01 01 01 01 11 11"];
+        expected.assert_eq(&read_lines(&mut bus, 10));
+    }
+
+    #[test]
+    fn test_set_button() {
+        let mut power_pad = PowerPad::new();
+
+        power_pad.set_button(1, true);
+        power_pad.set_button(12, true);
+        power_pad.set_button(13, true);
+        assert_eq!(power_pad.buttons, 0b1000_0000_0001);
+        power_pad.set_button(1, false);
+        assert!(!power_pad.is_pressed(1));
+        assert!(power_pad.is_pressed(12));
+    }
+}
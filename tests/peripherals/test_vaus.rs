@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::bus::Bus;
+    use nes::emulator::memory::Mem;
+    use nes::emulator::vaus::{ Vaus, POSITION_MIN, POSITION_MAX };
+    use crate::helpers::TestRom;
+    use expect_test::expect;
+
+    fn read_bits(bus: &mut Bus, bit: u8, count: usize) -> String {
+        (0..count).map(|_| if bus.mem_read(0x4017) & bit != 0 { '1' } else { '0' }).collect()
+    }
+
+    fn strobe(bus: &mut Bus) {
+        bus.mem_write(0x4016, 0x01);
+        bus.mem_write(0x4016, 0x00);
+    }
+
+    #[test]
+    fn test_position_inverted() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        bus.plug_in(1, Box::new(Vaus { position: 0x0C4, ..Vaus::new() }));
+        strobe(&mut bus);
+
+        let expected = expect!["100111011000"];
+        expected.assert_eq(&read_bits(&mut bus, 0x10, 12));
+    }
+
+    #[test]
+    fn test_position_latched_on_strobe() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        bus.plug_in(1, Box::new(Vaus { position: 0x1FF, ..Vaus::new() }));
+        strobe(&mut bus);
+        bus.device_mut::<Vaus>(1).unwrap().position = 0;
+
+        let expected = expect!["000000000"];
+        expected.assert_eq(&read_bits(&mut bus, 0x10, 9));
+        strobe(&mut bus);
+        let expected = expect!["111111111"];
+        expected.assert_eq(&read_bits(&mut bus, 0x10, 9));
+    }
+
+    #[test]
+    fn test_fire() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        bus.plug_in(1, Box::new(Vaus { fire: true, ..Vaus::new() }));
+
+        assert_eq!(bus.mem_read(0x4017) & 0x08, 0x08);
+        bus.device_mut::<Vaus>(1).unwrap().fire = false;
+        assert_eq!(bus.mem_read(0x4017) & 0x08, 0);
+    }
+
+    #[test]
+    fn test_aim() {
+        let mut vaus = Vaus::new();
+
+        vaus.aim(0);
+        assert_eq!(vaus.position, POSITION_MIN);
+        vaus.aim(1000);
+        assert_eq!(vaus.position, POSITION_MAX);
+    }
+}
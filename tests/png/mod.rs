@@ -0,0 +1 @@
+pub mod test_png;
\ No newline at end of file
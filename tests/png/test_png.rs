@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod test {
+    use nes::helpers::png;
+
+    #[test]
+    fn test_encode() {
+        let rgb = [0xFF, 0x00, 0x00, 0x00, 0xFF, 0x00];
+        let image = png::encode(2, 1, &rgb);
+
+        assert_eq!(&image[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']);
+        assert_eq!(&image[12..16], b"IHDR");
+        assert_eq!(&image[16..24], &[0, 0, 0, 2, 0, 0, 0, 1]);
+
+        /* Filter byte followed by the raw pixels in a single stored block */
+        let idat = &image[33..];
+        assert_eq!(&idat[4..8], b"IDAT");
+        assert_eq!(&idat[8..15], &[0x78, 0x01, 0x01, 7, 0, !7, 0xFF]);
+        assert_eq!(&idat[15..22], &[0, 0xFF, 0x00, 0x00, 0x00, 0xFF, 0x00]);
+        assert_eq!(&image[image.len() - 8..image.len() - 4], b"IEND");
+    }
+}
@@ -2,4 +2,5 @@ pub mod test_address;
 pub mod test_mirroring;
 pub mod test_scroll;
 pub mod test_status;
-pub mod test_read_write_data;
\ No newline at end of file
+pub mod test_read_write_data;
+pub mod test_output;
\ No newline at end of file
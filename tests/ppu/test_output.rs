@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::rom::Mirroring;
+    use crate::helpers::default_ppu;
+    use expect_test::expect;
+
+    const SCANLINE: usize = 341;
+
+    #[test]
+    fn test_scanline_drawn_when_finished() {
+        let mut ppu = default_ppu(Mirroring::HORIZONTAL);
+        ppu.palette_table[0] = 0x0F;
+        ppu.palette_table[3] = 0x30;
+        ppu.oam_data = [0xFF; 256]; // Sprites below the screen
+
+        ppu.tick(SCANLINE - 1);
+        assert!(ppu.scanline_output(0).iter().all(|pixel| *pixel == 0));
+
+        ppu.tick(1);
+        let expected = expect!["f f f f f f 30 f f f f f f f 30 f"];
+        expected.assert_eq(&ppu.scanline_output(0)[..16].iter().map(|pixel| format!("{:x}", pixel)).collect::<Vec<_>>().join(" "));
+        assert!(ppu.scanline_output(1).iter().all(|pixel| *pixel == 0));
+    }
+
+    #[test]
+    fn test_sprite_on_scanline() {
+        let mut ppu = default_ppu(Mirroring::HORIZONTAL);
+        ppu.palette_table[0x13] = 0x16;
+        ppu.oam_data[0..4].copy_from_slice(&[2, 0, 0b0100_0000, 10]); // y, tile, flip horizontally, x
+
+        for _ in 0..3 {
+            ppu.tick(SCANLINE);
+        }
+
+        assert_eq!(ppu.scanline_output(1)[11], 0);
+        assert_eq!(ppu.scanline_output(2)[11], 0x16);
+        assert_eq!(ppu.scanline_output(2)[16], 0);
+    }
+}
@@ -1,5 +1,4 @@
 #[cfg(test)]
-
 mod test {
     use nes::emulator::rom::Mirroring;
     use crate::helpers::{ default_ppu };
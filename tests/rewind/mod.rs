@@ -0,0 +1 @@
+pub mod test_rewind;
\ No newline at end of file
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::rewind::{ self, Rewind };
+    use crate::helpers::counter_rom;
+
+    #[test]
+    fn test_compress_round_trip() {
+        let mut data = vec![0; 1000];
+        data[3] = 7;
+        data[4] = 9;
+        data[500..700].iter_mut().for_each(|byte| *byte = 0xAA);
+
+        let compressed = rewind::compress(&data);
+
+        assert!(compressed.len() < 220);
+        assert_eq!(rewind::decompress(&compressed, data.len()), data);
+        assert_eq!(rewind::decompress(&rewind::compress(&[0; 64]), 64), vec![0; 64]);
+    }
+
+    #[test]
+    fn test_step_back() {
+        let mut nes = Nes::new(counter_rom());
+        let mut rewind = Rewind::new(rewind::DEFAULT_MEMORY, 2);
+
+        for _ in 0..10 {
+            nes.run_frame();
+            rewind.record(&nes);
+        }
+        assert_eq!(rewind.len(), 5);
+
+        let mut counts = Vec::new();
+        while rewind.step_back(&mut nes) {
+            counts.push(nes.cpu.bus.cpu_vram[0x00]);
+        }
+
+        assert_eq!(counts, vec![10, 8, 6, 4, 2]);
+        assert_eq!(nes.frame_count(), 2);
+        assert!(rewind.is_empty());
+        assert_eq!(rewind.memory_used(), 0);
+    }
+
+    #[test]
+    fn test_failed_step_back_keeps_snapshot() {
+        let mut nes = Nes::new(counter_rom());
+        let mut rewind = Rewind::new(rewind::DEFAULT_MEMORY, 1);
+        nes.run_frame();
+        rewind.record(&nes);
+
+        nes.set_four_score(true); // The snapshot no longer loads
+        assert!(!rewind.step_back(&mut nes));
+        assert_eq!(rewind.len(), 1);
+
+        nes.set_four_score(false);
+        assert!(rewind.step_back(&mut nes));
+        assert!(rewind.is_empty());
+    }
+
+    #[test]
+    fn test_memory_is_bounded() {
+        let mut nes = Nes::new(counter_rom());
+        let state_len = nes.save_state().len();
+        let mut rewind = Rewind::new(state_len + 200, 1);
+
+        for _ in 0..100 {
+            nes.run_frame();
+            rewind.record(&nes);
+            assert!(rewind.memory_used() <= state_len + 200);
+        }
+
+        let kept = rewind.len();
+        assert!(kept > 1 && kept < 100);
+
+        /* Only the newest snapshots are left */
+        for _ in 0..kept {
+            assert!(rewind.step_back(&mut nes));
+        }
+        assert_eq!(nes.frame_count(), 100 - kept + 1);
+    }
+}
@@ -1,5 +1,4 @@
 #[cfg(test)]
-
 mod test {
     use nes::emulator::rom::Rom;
     use crate::helpers::{ TestRom };
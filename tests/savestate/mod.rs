@@ -0,0 +1 @@
+pub mod test_savestate;
\ No newline at end of file
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::nes::Nes;
+    use nes::emulator::joypad::{ Buttons, Joypad };
+    use nes::emulator::memory::Mem;
+    use nes::emulator::input_device::DeviceKind;
+    use nes::emulator::region::Region;
+    use nes::emulator::savestate::{ self, VERSION };
+    use crate::helpers::counter_rom;
+
+    #[test]
+    fn test_save_and_load() {
+        let mut nes = Nes::new(counter_rom());
+        nes.set_region(Region::Pal);
+        nes.set_buttons(0, Buttons::A);
+        nes.run_frame();
+        nes.run_frame();
+        let state = nes.save_state();
+
+        nes.set_buttons(0, Buttons::empty());
+        for _ in 0..3 {
+            nes.run_frame();
+        }
+        let later = nes.save_state();
+        assert_eq!(nes.cpu.bus.cpu_vram[0x00], 5);
+
+        nes.load_state(&state).unwrap();
+        assert_eq!(nes.cpu.bus.cpu_vram[0x00], 2);
+        assert_eq!(nes.cpu.bus.cpu_vram[0x01], 1);
+        assert_eq!(nes.frame_count(), 2);
+        assert_eq!(nes.region(), Region::Pal);
+        assert_eq!(nes.save_state(), state);
+
+        /* Replaying the same input from the state ends up in the same place */
+        nes.set_buttons(0, Buttons::empty());
+        for _ in 0..3 {
+            nes.run_frame();
+        }
+        assert_eq!(nes.save_state(), later);
+    }
+
+    #[test]
+    fn test_joypad_shift_state() {
+        let mut nes = Nes::new(counter_rom());
+        nes.set_buttons(0, Buttons::B);
+        nes.cpu.bus.mem_write(0x4016, 1);
+        nes.cpu.bus.mem_write(0x4016, 0);
+        nes.cpu.bus.mem_read(0x4016);
+        let state = nes.save_state();
+
+        let mut other = Nes::new(counter_rom());
+        other.load_state(&state).unwrap();
+
+        assert_eq!(other.cpu.bus.mem_read(0x4016), nes.cpu.bus.mem_read(0x4016));
+        assert_eq!(other.device::<Joypad>(0).unwrap().buffer, nes.device::<Joypad>(0).unwrap().buffer);
+    }
+
+    #[test]
+    fn test_rejected_states() {
+        let mut nes = Nes::new(counter_rom());
+        let state = nes.save_state();
+
+        let mut rom = counter_rom();
+        rom.prg_rom[0x2000] = 0x00;
+        let mut other = Nes::new(rom);
+        assert_eq!(other.load_state(&state), Err("Save state was made with a different ROM".to_string()));
+
+        let mut old = state.clone();
+        old[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(nes.load_state(&old), Err(format!("Unsupported save state version {} (expected {})", VERSION + 1, VERSION)));
+
+        assert_eq!(nes.load_state(b"NES\x1A"), Err("Not a save state".to_string()));
+        assert_eq!(nes.load_state(&state[..100]), Err("Save state is truncated".to_string()));
+    }
+
+    #[test]
+    fn test_peripherals_stay_plugged_in() {
+        let mut nes = Nes::new(counter_rom());
+        nes.set_four_score(true);
+        nes.set_buttons(3, Buttons::Select);
+        let state = nes.save_state();
+
+        nes.set_buttons(3, Buttons::Start);
+        nes.load_state(&state).unwrap();
+        assert_eq!(nes.buttons(3), Buttons::Select);
+
+        nes.power_cycle();
+        assert!(nes.four_score());
+    }
+
+    #[test]
+    fn test_four_score_mismatch() {
+        let mut nes = Nes::new(counter_rom());
+        let state = nes.save_state();
+        nes.set_four_score(true);
+        let with_adapter = nes.save_state();
+
+        assert_eq!(nes.load_state(&state), Err("Save state was made without a Four Score, one is plugged in".to_string()));
+        nes.set_four_score(false);
+        assert_eq!(nes.load_state(&with_adapter), Err("Save state was made with a Four Score, none is plugged in".to_string()));
+    }
+
+    #[test]
+    fn test_device_mismatch() {
+        let mut nes = Nes::new(counter_rom());
+        nes.plug_in(1, DeviceKind::Vaus.create());
+        let state = nes.save_state();
+
+        let mut other = Nes::new(counter_rom());
+        assert_eq!(other.load_state(&state), Err("Save state was made with a Vaus paddle in port 2, not a controller".to_string()));
+        other.plug_in(1, DeviceKind::Vaus.create());
+        other.plug_in(2, DeviceKind::FamilyKeyboard.create());
+        assert_eq!(other.load_state(&state), Err("Save state was made with nothing in the expansion port, not a Family BASIC keyboard".to_string()));
+        nes.plug_in(2, DeviceKind::FamilyKeyboard.create());
+        assert_eq!(other.load_state(&nes.save_state()), Ok(()));
+    }
+
+    #[test]
+    fn test_failed_load_keeps_state() {
+        let mut nes = Nes::new(counter_rom());
+        nes.run_frame();
+        let before = nes.save_state();
+
+        let mut other = Nes::new(counter_rom());
+        for _ in 0..4 {
+            other.run_frame();
+        }
+        let state = other.save_state();
+
+        assert!(nes.load_state(&state[..state.len() - 1]).is_err());
+        assert_eq!(nes.save_state(), before);
+    }
+
+    #[test]
+    fn test_mid_frame_output() {
+        let mut nes = Nes::new(counter_rom());
+        nes.run_frame();
+        for _ in 0..3000 {
+            assert!(!nes.step());
+        }
+        nes.cpu.bus.ppu.output[0] = 0x16;
+        let state = nes.save_state();
+
+        let mut other = Nes::new(counter_rom());
+        other.load_state(&state).unwrap();
+        assert_eq!(other.cpu.bus.ppu.output, nes.cpu.bus.ppu.output);
+
+        /* The rest of the frame is drawn on top of the pixels from before the save */
+        nes.run_frame();
+        other.run_frame();
+        nes.step();
+        other.step();
+        assert_eq!(other.framebuffer().pixels[0], 0x16);
+        assert_eq!(other.framebuffer().pixels, nes.framebuffer().pixels);
+    }
+
+    #[test]
+    fn test_slot_path() {
+        assert_eq!(savestate::slot_path("roms/game.nes", 1), "roms/game.ss1");
+        assert_eq!(savestate::slot_path("game", 10), "game.ss10");
+    }
+}
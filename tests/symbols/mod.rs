@@ -0,0 +1 @@
+pub mod test_symbols;
\ No newline at end of file
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test {
+    use nes::helpers::symbols::{ Symbols, SymbolFormat };
+    use std::env;
+    use std::fs;
+
+    const DBG: &str = "\
+version\tmajor=2,minor=0
+seg\tid=0,name=\"CODE\",start=0x00C000,size=0x0100,addrsize=absolute,type=ro,oname=\"game.nes\",ooffs=16
+scope\tid=0,name=\"\",mod=0,size=256
+scope\tid=1,name=\"UpdatePlayer\",mod=0,type=scope,size=32,parent=0
+sym\tid=0,name=\"@loop\",addrsize=absolute,scope=1,def=20,val=0xC5F5,seg=0,type=lab
+sym\tid=1,name=\"UpdatePlayer\",addrsize=absolute,scope=0,def=12,ref=15,val=0xC5F5,seg=0,type=lab
+sym\tid=2,name=\"PlayerX\",addrsize=zeropage,size=1,scope=0,def=3,val=0x86,seg=1,type=lab
+sym\tid=3,name=\"BUTTON_A\",addrsize=zeropage,scope=0,def=4,val=0x80,type=equ
+sym\tid=4,name=\"Reset\",addrsize=absolute,scope=0,def=5,type=imp";
+
+    #[test]
+    fn test_ca65_dbg() {
+        let symbols = Symbols::parse(DBG, SymbolFormat::Ca65Dbg, 0x8000).unwrap();
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols.name(0xC5F5), Some("UpdatePlayer"));
+        assert_eq!(symbols.name(0x0086), Some("PlayerX"));
+        assert_eq!(symbols.name(0x0080), None); // Equates are not addresses
+        assert_eq!(symbols.address("@loop"), Some(0xC5F5));
+        assert_eq!(symbols.address("Reset"), None);
+    }
+
+    #[test]
+    fn test_fceux_nl() {
+        let text = "$0086#PlayerX#X position\n$0300/10#Buffer#\n$C000##no name\n#comment\n$C5F5#UpdatePlayer#\\\nmore comment";
+        let symbols = Symbols::parse(text, SymbolFormat::FceuxNl, 0x8000).unwrap();
+
+        assert_eq!(symbols.name(0x0086), Some("PlayerX"));
+        assert_eq!(symbols.name(0x0300), Some("Buffer"));
+        assert_eq!(symbols.name(0xC000), None);
+        assert_eq!(symbols.address("UpdatePlayer"), Some(0xC5F5));
+        assert_eq!(Symbols::parse("$XYZ#Bad#", SymbolFormat::FceuxNl, 0x8000), Err("line 1: invalid address '$XYZ'".to_string()));
+    }
+
+    #[test]
+    fn test_mesen_mlb() {
+        let text = "P:05F5:UpdatePlayer:moves the player\nR:0086:PlayerX\nR:0900:Mirrored\nW:6000-60FF:Save\nG:2000:PPUCTRL\nNesPrgRom:0000:Reset\nP:0010::comment only";
+
+        let symbols = Symbols::parse(text, SymbolFormat::MesenMlb, 0x4000).unwrap();
+        assert_eq!(symbols.name(0xC5F5), Some("UpdatePlayer"));
+        assert_eq!(symbols.name(0x85F5), Some("UpdatePlayer")); // 16 KiB is mirrored
+        assert_eq!(symbols.address("UpdatePlayer"), Some(0xC5F5));
+        assert_eq!(symbols.name(0x0086), Some("PlayerX"));
+        assert_eq!(symbols.name(0x0100), Some("Mirrored"));
+        assert_eq!(symbols.name(0x6000), Some("Save"));
+        assert_eq!(symbols.name(0x2000), Some("PPUCTRL"));
+        assert_eq!(symbols.address("Reset"), Some(0xC000));
+
+        let symbols = Symbols::parse(text, SymbolFormat::MesenMlb, 0x8000).unwrap();
+        assert_eq!(symbols.address("UpdatePlayer"), Some(0x85F5));
+        assert_eq!(symbols.name(0xC5F5), None);
+
+        let text = "NesChrRom:0010:Tiles\nNesPaletteRam:00:Background\nNesPpuMemory:2000:Nametable\nG:10000:OutOfRange\nR:0010:Counter";
+        let symbols = Symbols::parse(text, SymbolFormat::MesenMlb, 0x8000).unwrap();
+        assert_eq!(symbols.name(0x0010), Some("Counter"));
+        assert_eq!(symbols.address("Tiles"), None);
+        assert_eq!(symbols.address("Background"), None);
+        assert_eq!(symbols.address("Nametable"), None);
+
+        assert_eq!(Symbols::parse("R:zz:Name", SymbolFormat::MesenMlb, 0x8000), Err("line 1: invalid address 'zz'".to_string()));
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = env::temp_dir();
+        let nl = dir.join("nes_test_symbols.nl");
+        let mlb = dir.join("nes_test_symbols.mlb");
+        fs::write(&nl, "$0086#PlayerX#").unwrap();
+        fs::write(&mlb, "R:0086:Other\nR:0087:PlayerY").unwrap();
+        let paths = [nl.to_str().unwrap().to_string(), mlb.to_str().unwrap().to_string()];
+
+        let symbols = Symbols::load_all(&paths, 0x8000).unwrap();
+        assert_eq!(symbols.name(0x0086), Some("PlayerX"));
+        assert_eq!(symbols.name(0x0087), Some("PlayerY"));
+
+        assert_eq!(
+            Symbols::load("game.sym", 0x8000),
+            Err("Unknown symbol file game.sym (expected .dbg, .nl or .mlb)".to_string())
+        );
+        assert!(Symbols::load("missing.nl", 0x8000).unwrap_err().starts_with("Could not read symbol file missing.nl"));
+    }
+}
@@ -3,4 +3,25 @@ pub mod helpers;
 pub mod trace;
 pub mod rom;
 pub mod ppu;
-pub mod joypad;
\ No newline at end of file
+pub mod joypad;
+pub mod palette;
+pub mod ntsc;
+pub mod filters;
+pub mod display;
+pub mod cli;
+pub mod png;
+pub mod nes;
+pub mod savestate;
+pub mod rewind;
+pub mod movie;
+pub mod gamepad;
+pub mod bindings;
+pub mod input;
+pub mod zapper;
+pub mod peripherals;
+pub mod debugger;
+pub mod disassembler;
+pub mod symbols;
+pub mod cdl;
+pub mod checksum;
+pub mod wav;
\ No newline at end of file
@@ -3,6 +3,8 @@ mod test {
     use nes::emulator::cpu::Cpu;
     use nes::emulator::bus::Bus;
     use nes::emulator::memory::Mem;
+    use nes::helpers::trace::trace_with_symbols;
+    use nes::helpers::symbols::{ Symbols, SymbolFormat };
     use crate::helpers::{ TestRom, load_into_memory, check };
     use expect_test::expect;
 
@@ -41,4 +43,26 @@ mod test {
             0000  11 10     ORA ($10),Y = 0200 @ 0200 = AA  A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:0
             0002  00        BRK                             A:AA X:00 Y:00 P:A4 SP:FD PPU:  0, 15 CYC:5"#]])
     }
+
+    #[test]
+    fn test_format_trace_symbols() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        load_into_memory(&mut bus, vec![0xa5, 0x86, 0x20, 0x10, 0x00, 0xd0, 0xfb, 0x6c, 0x10, 0x00], 0x0000);
+        let symbols = Symbols::parse("$0086#PlayerX#\n$0010#UpdatePlayer#\n$0002#Loop#", SymbolFormat::FceuxNl, 0x8000).unwrap();
+
+        let mut cpu = Cpu::new(bus);
+        cpu.program_counter = 0x00;
+        let lines: Vec<String> = [0x00, 0x02, 0x05, 0x07].iter()
+            .map(|pc| {
+                cpu.program_counter = *pc;
+                trace_with_symbols(&mut cpu, &symbols)
+            })
+            .collect();
+
+        expect![[r#"
+            0000  A5 86     LDA PlayerX = 00                A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:0
+            0002  20 10 00  JSR UpdatePlayer                A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:0
+            0005  D0 FB     BNE Loop                        A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:0
+            0007  6C 10 00  JMP (UpdatePlayer) = 0000       A:00 X:00 Y:00 P:24 SP:FD PPU:  0,  0 CYC:0"#]].assert_eq(&lines.join("\n"));
+    }
 }
\ No newline at end of file
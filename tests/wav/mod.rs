@@ -0,0 +1 @@
+pub mod test_wav;
\ No newline at end of file
@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod test {
+    use nes::helpers::wav;
+
+    #[test]
+    fn test_encode() {
+        let data = wav::encode(44_100, &[0.0, 1.0, -1.0, 2.0]);
+
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(&data[4..8], &44u32.to_le_bytes());
+        assert_eq!(&data[8..16], b"WAVEfmt ");
+        assert_eq!(&data[20..24], &[1, 0, 1, 0]); // PCM, mono
+        assert_eq!(&data[24..28], &44_100u32.to_le_bytes());
+        assert_eq!(&data[34..36], &[16, 0]);
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(&data[40..44], &8u32.to_le_bytes());
+        assert_eq!(&data[44..], &[0x00, 0x00, 0xFF, 0x7F, 0x01, 0x80, 0xFF, 0x7F]);
+    }
+}
@@ -0,0 +1 @@
+pub mod test_zapper;
\ No newline at end of file
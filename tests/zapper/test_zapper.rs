@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod test {
+    use nes::emulator::bus::Bus;
+    use nes::emulator::memory::Mem;
+    use nes::emulator::ppu::Ppu;
+    use nes::emulator::rom::Mirroring;
+    use nes::emulator::zapper::Zapper;
+    use nes::emulator::input_device::InputDevice;
+    use nes::emulator::frame::Frame;
+    use crate::helpers::{ default_ppu, TestRom };
+
+    const WHITE: u16 = 0x30;
+    const BLACK: u16 = 0x0F;
+
+    /* A white 4x4 target at 100,50 on a black screen */
+    fn target_ppu(scanline: usize) -> Ppu {
+        let mut ppu = default_ppu(Mirroring::HORIZONTAL);
+        ppu.output = vec![BLACK; Frame::WIDTH * Frame::HEIGHT];
+        for y in 50..54 {
+            ppu.output[y * Frame::WIDTH + 100..y * Frame::WIDTH + 104].fill(WHITE);
+        }
+        ppu.scanline = scanline;
+        ppu
+    }
+
+    fn aimed(x: usize, y: usize) -> Zapper {
+        Zapper { aim: Some((x, y)), trigger: false }
+    }
+
+    #[test]
+    fn test_light_after_beam_passes() {
+        assert!(aimed(101, 51).light_detected(&target_ppu(52)));
+        assert!(aimed(101, 51).light_detected(&target_ppu(70)));
+        assert!(!aimed(101, 51).light_detected(&target_ppu(49))); // Not drawn yet
+        assert!(!aimed(101, 51).light_detected(&target_ppu(80))); // Faded
+    }
+
+    #[test]
+    fn test_light_around_aim() {
+        assert!(aimed(98, 48).light_detected(&target_ppu(60)));
+        assert!(!aimed(97, 51).light_detected(&target_ppu(60)));
+        assert!(!aimed(150, 150).light_detected(&target_ppu(160)));
+        assert!(!Zapper::new().light_detected(&target_ppu(60)));
+    }
+
+    #[test]
+    fn test_read_bits() {
+        let ppu = target_ppu(60);
+
+        assert_eq!(Zapper::new().read(&ppu), 0b0000_1000);
+        assert_eq!(Zapper { trigger: true, ..aimed(101, 51) }.read(&ppu), 0b0001_0000);
+        assert_eq!(Zapper { trigger: true, ..Zapper::new() }.read(&ppu), 0b0001_1000);
+    }
+
+    #[test]
+    fn test_port_2() {
+        let mut bus = Bus::new(TestRom::default_rom());
+        bus.plug_in(1, Box::new(Zapper { aim: None, trigger: true }));
+
+        assert_eq!(bus.mem_read(0x4017), 0x58);
+        assert_eq!(bus.mem_read_debugging(0x4017), 0x58);
+        assert_eq!(bus.mem_read(0x4016) & 1, 0); // Port 1 is still a controller
+    }
+}